@@ -0,0 +1,230 @@
+//! Approved roots for delete and rename operations.
+//!
+//! `batch_delete` and `batch_rename` only act inside a root directory the user
+//! picked in a folder dialog opened by the backend. Every target is checked
+//! after resolving `..` and symbolic links, so a buggy or compromised frontend
+//! cannot reach outside the folder the user chose.
+
+use crate::protected_paths::resolve_path;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+/// Root directories the user has chosen in this session, shared as Tauri managed state.
+#[derive(Debug, Clone, Default)]
+pub struct ApprovedRoots {
+    roots: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl ApprovedRoots {
+    /// Approves a directory as an operation root.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PathBuf)` - The canonical path of the approved root
+    /// * `Err(String)` - If the path is not an existing directory
+    pub fn approve(&self, dir: &Path) -> Result<PathBuf, String> {
+        let root = fs::canonicalize(dir)
+            .map_err(|e| format!("Cannot resolve {}: {}", dir.display(), e))?;
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", root.display()));
+        }
+
+        self.roots
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(root.clone());
+        Ok(root)
+    }
+
+    /// Looks up an approved root.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PathBuf)` - The canonical path of the root
+    /// * `Err(String)` - If the root was not chosen by the user in this session
+    pub fn resolve(&self, root: &str) -> Result<PathBuf, String> {
+        let not_approved = || format!("Not an approved root: {}", root);
+        let resolved = fs::canonicalize(root).map_err(|_| not_approved())?;
+
+        if self
+            .roots
+            .lock()
+            .map_err(|e| e.to_string())?
+            .contains(&resolved)
+        {
+            Ok(resolved)
+        } else {
+            Err(not_approved())
+        }
+    }
+}
+
+/// Checks whether a path lies strictly inside a root, after resolving `..`
+/// and symbolic links in its parent directories.
+///
+/// The root itself is not inside the root.
+pub(crate) fn is_within_root(path: &Path, root: &Path) -> bool {
+    resolve_path(path).is_some_and(|resolved| resolved != root && resolved.starts_with(root))
+}
+
+/// Checks that every path lies inside a root.
+///
+/// # Returns
+///
+/// * `Ok(())` - Every path is inside the root
+/// * `Err(String)` - One line per path outside the root
+pub(crate) fn ensure_within_root<'a>(
+    paths: impl IntoIterator<Item = &'a str>,
+    root: &Path,
+) -> Result<(), String> {
+    let errors: Vec<String> = paths
+        .into_iter()
+        .filter(|path| !is_within_root(Path::new(path), root))
+        .map(|path| format!("{} is outside the approved root {}", path, root.display()))
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Asks the user to choose the root directory for delete and rename operations.
+///
+/// Opens a native folder dialog and approves the chosen directory for the rest
+/// of the session. Operations started with this root may only touch paths
+/// inside it.
+///
+/// # Arguments
+///
+/// * `title` - Title of the dialog
+/// * `default_path` - Directory the dialog opens in
+/// * `app` - The application handle
+/// * `roots` - The approved roots managed by Tauri
+///
+/// # Returns
+///
+/// * `Ok(Some(String))` - The canonical path of the chosen root
+/// * `Ok(None)` - The user cancelled the dialog
+/// * `Err(String)` - If the chosen path could not be resolved
+#[tauri::command]
+pub async fn choose_operation_root(
+    title: Option<String>,
+    default_path: Option<String>,
+    app: AppHandle,
+    roots: State<'_, ApprovedRoots>,
+) -> Result<Option<String>, String> {
+    // The blocking dialog must not run on the main thread
+    let picked = tokio::task::spawn_blocking(move || {
+        let mut dialog = app.dialog().file();
+        if let Some(title) = title {
+            dialog = dialog.set_title(title);
+        }
+        if let Some(default_path) = default_path {
+            dialog = dialog.set_directory(default_path);
+        }
+        dialog.blocking_pick_folder()
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?;
+
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| format!("Invalid folder: {}", e))?;
+
+    let root = roots.approve(&path)?;
+    Ok(Some(root.to_string_lossy().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Tests only approved directories resolve as roots.
+    #[test]
+    fn test_approve_and_resolve() {
+        let dir = tempdir().unwrap();
+        let roots = ApprovedRoots::default();
+        let root = dir.path().to_string_lossy().to_string();
+
+        assert!(roots.resolve(&root).is_err());
+        let approved = roots.approve(dir.path()).unwrap();
+        assert_eq!(roots.resolve(&root).unwrap(), approved);
+        // The same directory reached through `..`
+        let indirect = dir.path().join("..").join(dir.path().file_name().unwrap());
+        assert_eq!(
+            roots.resolve(&indirect.to_string_lossy()).unwrap(),
+            approved
+        );
+    }
+
+    /// Tests files cannot be approved as roots.
+    #[test]
+    fn test_approve_rejects_files() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        fs::write(&file, "x").unwrap();
+
+        assert!(ApprovedRoots::default().approve(&file).is_err());
+        assert!(ApprovedRoots::default()
+            .approve(&dir.path().join("missing"))
+            .is_err());
+    }
+
+    /// Tests `..` cannot escape the root.
+    #[test]
+    fn test_is_within_root_resolves_dot_dot() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir(&root).unwrap();
+        let root = fs::canonicalize(&root).unwrap();
+
+        assert!(is_within_root(&root.join("a.txt"), &root));
+        assert!(!is_within_root(&root.join("../a.txt"), &root));
+        assert!(!is_within_root(&root, &root));
+        assert!(!is_within_root(&dir.path().join("rootsibling.txt"), &root));
+    }
+
+    /// Tests symbolic links inside the root cannot be used to reach outside it.
+    #[cfg(unix)]
+    #[test]
+    fn test_is_within_root_resolves_symlinks() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("root");
+        let outside = dir.path().join("outside");
+        fs::create_dir(&root).unwrap();
+        fs::create_dir(&outside).unwrap();
+        let root = fs::canonicalize(&root).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+
+        assert!(!is_within_root(&root.join("link").join("a.txt"), &root));
+        // The link itself lives inside the root
+        assert!(is_within_root(&root.join("link"), &root));
+    }
+
+    /// Tests every path outside the root is reported.
+    #[test]
+    fn test_ensure_within_root() {
+        let dir = tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        let inside = root.join("a.txt").to_string_lossy().to_string();
+
+        assert!(ensure_within_root([inside.as_str()], &root).is_ok());
+
+        let errors =
+            ensure_within_root([inside.as_str(), "/etc/passwd", "/tmp"], &root).unwrap_err();
+        assert_eq!(errors.lines().count(), 2);
+        assert!(errors.contains("/etc/passwd is outside the approved root"));
+    }
+}
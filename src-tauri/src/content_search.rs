@@ -0,0 +1,640 @@
+//! File content search for the file remover and batch renamer.
+//!
+//! `search_file_contents` finds files whose text matches a literal or regex,
+//! line by line, and returns them as `FileMatchResult`s so they can be
+//! selected for deletion or renaming like name matches. Files that look
+//! binary, or are larger than a size limit, are skipped. Text is decoded
+//! from UTF-8, or from UTF-16 when it starts with a byte order mark; bytes
+//! that are not valid UTF-8 are replaced.
+
+use crate::operations::{CancellationToken, OperationRegistry, CANCELLED_ERROR};
+use crate::remove::{compile_regex, FileMatchResult, MatchTarget, PatternType, SearchProgress};
+use crate::rename_rules::char_offset;
+use crate::search_expression::{Candidate, FilterExpression, SearchExpression};
+use crate::search_filters::parse_size;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::ipc::Channel;
+use tauri::State;
+use walkdir::WalkDir;
+
+/// Files containing a NUL byte in their first 8 KiB are treated as binary
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// Files larger than this are skipped unless the search sets its own limit
+const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Number of matching lines reported per file unless the search sets its own limit
+const DEFAULT_MAX_HITS: usize = 5;
+
+/// Longest snippet in bytes; longer lines are cut around the first match
+const MAX_SNIPPET_LEN: usize = 200;
+
+/// Bytes kept before the first match when a long line is cut
+const SNIPPET_CONTEXT: usize = 40;
+
+// ==================== Types ====================
+
+/// A matching line in a file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContentHit {
+    /// Line number, starting at 1
+    pub line: usize,
+    /// The line, cut around the first match if it is long
+    pub snippet: String,
+    /// Character ranges where the pattern matched (start, end), within
+    /// `snippet`; they count characters, not bytes
+    pub match_ranges: Vec<(usize, usize)>,
+}
+
+/// Optional limits for a content search, as sent by the frontend
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContentSearchOptions {
+    /// Largest file to read, such as `10MB` (default 10 MiB)
+    pub max_file_size: Option<String>,
+    /// Number of matching lines to report per file (default 5)
+    pub max_hits: Option<usize>,
+}
+
+/// A content search with its pattern, file filter and limits compiled
+#[derive(Debug, Clone)]
+struct ContentSearch {
+    regex: Regex,
+    files: Option<SearchExpression>,
+    max_file_size: u64,
+    max_hits: usize,
+}
+
+// ==================== Decoding ====================
+
+/// Decodes UTF-16 code units, replacing invalid ones.
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Decodes file contents as text.
+///
+/// A UTF-8 or UTF-16 byte order mark selects that encoding. Otherwise a NUL
+/// byte near the start marks the file as binary, and invalid UTF-8 sequences
+/// are replaced, keeping the valid text around them intact.
+///
+/// # Returns
+///
+/// * `Some(String)` - The decoded text
+/// * `None` - If the contents look binary
+fn decode_text(bytes: &[u8]) -> Option<String> {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return Some(String::from_utf8_lossy(rest).into_owned());
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return Some(decode_utf16(rest, u16::from_le_bytes));
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return Some(decode_utf16(rest, u16::from_be_bytes));
+    }
+
+    if bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0) {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+// ==================== Matching ====================
+
+/// Cuts a long line around its first match.
+///
+/// # Arguments
+///
+/// * `line` - The matching line
+/// * `ranges` - The sorted byte ranges of the matches within the line
+///
+/// # Returns
+///
+/// The snippet and the ranges that fall within it, relative to the snippet.
+fn make_snippet(line: &str, ranges: &[(usize, usize)]) -> (String, Vec<(usize, usize)>) {
+    if line.len() <= MAX_SNIPPET_LEN {
+        return (line.to_string(), ranges.to_vec());
+    }
+
+    let first = ranges.first().map_or(0, |range| range.0);
+    let mut start = first.saturating_sub(SNIPPET_CONTEXT);
+    while !line.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + MAX_SNIPPET_LEN).min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let ranges = ranges
+        .iter()
+        .filter(|(range_start, _)| *range_start < end)
+        .map(|&(range_start, range_end)| (range_start - start, range_end.min(end) - start))
+        .collect();
+    (line[start..end].to_string(), ranges)
+}
+
+/// Searches text line by line.
+///
+/// # Arguments
+///
+/// * `text` - The decoded file contents
+/// * `regex` - The compiled pattern
+/// * `max_hits` - How many matching lines to report
+///
+/// # Returns
+///
+/// * `Some(Vec<ContentHit>)` - The first `max_hits` matching lines
+/// * `None` - If no line matches
+fn search_text(text: &str, regex: &Regex, max_hits: usize) -> Option<Vec<ContentHit>> {
+    // Most files do not match at all
+    if !regex.is_match(text) {
+        return None;
+    }
+
+    let mut hits = Vec::new();
+    let mut matched = false;
+    for (index, line) in text.lines().enumerate() {
+        if matched && hits.len() >= max_hits {
+            break;
+        }
+
+        let ranges: Vec<(usize, usize)> = regex
+            .find_iter(line)
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        if ranges.is_empty() {
+            continue;
+        }
+
+        matched = true;
+        if hits.len() < max_hits {
+            let (snippet, byte_ranges) = make_snippet(line, &ranges);
+            let match_ranges = byte_ranges
+                .into_iter()
+                .map(|(start, end)| (char_offset(&snippet, start), char_offset(&snippet, end)))
+                .collect();
+            hits.push(ContentHit {
+                line: index + 1,
+                snippet,
+                match_ranges,
+            });
+        }
+    }
+
+    matched.then_some(hits)
+}
+
+impl ContentSearch {
+    /// Compiles a content search.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ContentSearch)` - The compiled search
+    /// * `Err(String)` - If the pattern is empty, invalid or not a simple or
+    ///   regex pattern, or a filter or limit is invalid
+    fn compile(
+        pattern: &str,
+        pattern_type: &PatternType,
+        case_sensitive: bool,
+        expression: Option<FilterExpression>,
+        options: ContentSearchOptions,
+    ) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Err("Pattern cannot be empty".to_string());
+        }
+        let regex = match pattern_type {
+            PatternType::Simple => compile_regex(&regex::escape(pattern), case_sensitive)?,
+            PatternType::Regex => compile_regex(pattern, case_sensitive)?,
+            PatternType::Extension | PatternType::Glob => {
+                return Err("Content search supports simple and regex patterns".to_string())
+            }
+        };
+
+        Ok(ContentSearch {
+            regex,
+            files: expression.map(|e| e.compile()).transpose()?,
+            max_file_size: options
+                .max_file_size
+                .as_deref()
+                .map(parse_size)
+                .transpose()?
+                .unwrap_or(DEFAULT_MAX_FILE_SIZE),
+            max_hits: options.max_hits.unwrap_or(DEFAULT_MAX_HITS),
+        })
+    }
+
+    /// Searches one file.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the file is excluded by the file filter, too large, binary,
+    /// unreadable, or does not match.
+    fn search_file(&self, path: &Path, base_path: &Path) -> Option<FileMatchResult> {
+        let candidate = Candidate::new(path, base_path)?;
+        if let Some(files) = &self.files {
+            if !files.matches(&candidate) {
+                return None;
+            }
+        }
+
+        let metadata = candidate.metadata().ok()?;
+        if metadata.len() > self.max_file_size {
+            return None;
+        }
+
+        let text = decode_text(&fs::read(path).ok()?)?;
+        let hits = search_text(&text, &self.regex, self.max_hits)?;
+
+        let mut result = FileMatchResult::new(
+            path,
+            candidate.name().to_string(),
+            Vec::new(),
+            MatchTarget::Name,
+            metadata,
+        );
+        result.content_hits = hits;
+        Some(result)
+    }
+}
+
+/// Runs a content search, reporting progress through a callback.
+///
+/// Files are collected first, then searched in parallel. Symbolic links are
+/// not followed.
+///
+/// # Arguments
+///
+/// * `base_path` - The directory to search in
+/// * `include_subdirs` - Whether to search subdirectories
+/// * `search` - The compiled search
+/// * `cancel` - Stops the search when cancelled
+/// * `on_progress` - Receives progress events
+///
+/// # Returns
+///
+/// * `Ok(Vec<FileMatchResult>)` - The matching files, with their first hits
+/// * `Err(String)` - If the search is cancelled
+fn run_content_search<F>(
+    base_path: &str,
+    include_subdirs: bool,
+    search: &ContentSearch,
+    cancel: &CancellationToken,
+    on_progress: F,
+) -> Result<Vec<FileMatchResult>, String>
+where
+    F: Fn(SearchProgress),
+{
+    on_progress(SearchProgress::Started {
+        base_path: base_path.to_string(),
+    });
+
+    // Phase 1: Collect the files to read
+    let mut files = Vec::new();
+    let mut last_progress_dir = String::new();
+    let progress_interval = 100; // Send progress every 100 files
+
+    let walker = if include_subdirs {
+        WalkDir::new(base_path)
+    } else {
+        WalkDir::new(base_path).max_depth(1)
+    };
+
+    for entry in walker.into_iter().filter_map(|e| e.ok()) {
+        if cancel.is_cancelled() {
+            on_progress(SearchProgress::Cancelled {
+                files_found: files.len(),
+                matches_found: 0,
+            });
+            return Err(CANCELLED_ERROR.to_string());
+        }
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        if files.len().is_multiple_of(progress_interval) {
+            if let Some(parent) = entry.path().parent() {
+                let current_dir = parent.to_string_lossy().to_string();
+                if current_dir != last_progress_dir {
+                    last_progress_dir = current_dir.clone();
+                    on_progress(SearchProgress::Scanning {
+                        current_dir,
+                        files_found: files.len(),
+                    });
+                }
+            }
+        }
+
+        files.push(entry);
+    }
+
+    on_progress(SearchProgress::Matching {
+        total_files: files.len(),
+    });
+
+    // Phase 2: Read and search the files with Rayon parallelization
+    let results: Vec<FileMatchResult> = files
+        .par_iter()
+        .filter_map(|entry| {
+            // Remaining files are skipped once cancelled
+            if cancel.is_cancelled() {
+                return None;
+            }
+            search.search_file(entry.path(), Path::new(base_path))
+        })
+        .collect();
+
+    if cancel.is_cancelled() {
+        on_progress(SearchProgress::Cancelled {
+            files_found: files.len(),
+            matches_found: results.len(),
+        });
+        return Err(CANCELLED_ERROR.to_string());
+    }
+
+    on_progress(SearchProgress::Completed {
+        matches_found: results.len(),
+    });
+    Ok(results)
+}
+
+// ==================== Tauri Commands ====================
+
+/// Searches file contents for a literal or regex, with progress streaming.
+///
+/// Each line is matched separately. Matching files are returned as
+/// `FileMatchResult`s with their first matching lines in `content_hits`, so
+/// they can be passed to `batch_delete` or the renamer like name matches.
+///
+/// # Arguments
+///
+/// * `base_path` - The directory to search in
+/// * `pattern` - The text or regex to find
+/// * `pattern_type` - `simple` for literal text (default) or `regex`
+/// * `include_subdirs` - Whether to search subdirectories
+/// * `case_sensitive` - Whether the search should be case-sensitive
+/// * `expression` - A filter expression selecting which files to read
+/// * `options` - The maximum file size and number of hits per file
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `operations` - The operation registry managed by Tauri
+/// * `on_progress` - Channel to send progress events
+///
+/// # Returns
+///
+/// * `Ok(Vec<FileMatchResult>)` - The matching files with their hits
+/// * `Err(String)` - If the pattern, filter or options are invalid, or the
+///   search is cancelled
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_file_contents(
+    base_path: String,
+    pattern: String,
+    pattern_type: Option<PatternType>,
+    include_subdirs: bool,
+    case_sensitive: bool,
+    expression: Option<FilterExpression>,
+    options: Option<ContentSearchOptions>,
+    operation_id: Option<u64>,
+    operations: State<'_, OperationRegistry>,
+    on_progress: Channel<SearchProgress>,
+) -> Result<Vec<FileMatchResult>, String> {
+    let operation = operations.begin(operation_id);
+    let search = ContentSearch::compile(
+        &pattern,
+        &pattern_type.unwrap_or_default(),
+        case_sensitive,
+        expression,
+        options.unwrap_or_default(),
+    )?;
+
+    // Run the heavy work in a blocking thread to keep the main thread responsive
+    tokio::task::spawn_blocking(move || {
+        run_content_search(
+            &base_path,
+            include_subdirs,
+            &search,
+            operation.token(),
+            |event| {
+                let _ = on_progress.send(event);
+            },
+        )
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tempfile::tempdir;
+
+    /// Compiles a search with default limits.
+    fn compile(pattern: &str, pattern_type: PatternType) -> ContentSearch {
+        ContentSearch::compile(
+            pattern,
+            &pattern_type,
+            false,
+            None,
+            ContentSearchOptions::default(),
+        )
+        .unwrap()
+    }
+
+    /// Runs a search without progress events.
+    fn run(dir: &Path, search: &ContentSearch) -> Vec<FileMatchResult> {
+        let mut results = run_content_search(
+            &dir.to_string_lossy(),
+            true,
+            search,
+            &CancellationToken::default(),
+            |_| {},
+        )
+        .unwrap();
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+        results
+    }
+
+    /// Tests text is decoded from UTF-8 and UTF-16, and binary is detected.
+    #[test]
+    fn test_decode_text() {
+        assert_eq!(decode_text(b"plain").unwrap(), "plain");
+        assert_eq!(decode_text(b"\xEF\xBB\xBFbom").unwrap(), "bom");
+        assert_eq!(decode_text(b"\xFF\xFEh\0i\0").unwrap(), "hi");
+        assert_eq!(decode_text(b"\xFE\xFF\0h\0i").unwrap(), "hi");
+        assert_eq!(decode_text(b"caf\xE9").unwrap(), "caf\u{FFFD}");
+        assert_eq!(decode_text(b"ELF\0\x01\x02"), None);
+    }
+
+    /// Tests a stray invalid byte does not garble the UTF-8 text around it.
+    #[test]
+    fn test_decode_text_stray_invalid_byte() {
+        let bytes = "naïve \u{2014} café\n".as_bytes().to_vec();
+        let mut with_stray = b"\xFF".to_vec();
+        with_stray.extend(&bytes);
+
+        assert_eq!(
+            decode_text(&with_stray).unwrap(),
+            "\u{FFFD}naïve \u{2014} café\n"
+        );
+    }
+
+    /// Tests long lines are cut around the first match.
+    #[test]
+    fn test_make_snippet() {
+        assert_eq!(
+            make_snippet("short line", &[(6, 10)]),
+            ("short line".to_string(), vec![(6, 10)])
+        );
+
+        let line = format!("{}needle{}", "a".repeat(300), "b".repeat(300));
+        let (snippet, ranges) = make_snippet(&line, &[(300, 306)]);
+        assert_eq!(snippet.len(), MAX_SNIPPET_LEN);
+        assert_eq!(ranges, vec![(SNIPPET_CONTEXT, SNIPPET_CONTEXT + 6)]);
+        assert_eq!(&snippet[ranges[0].0..ranges[0].1], "needle");
+
+        // Cuts never split a character
+        let line = "é".repeat(300);
+        let (snippet, _) = make_snippet(&line, &[(401, 403)]);
+        assert!(snippet.chars().all(|c| c == 'é'));
+    }
+
+    /// Tests hits report line numbers and stop at the limit.
+    #[test]
+    fn test_search_text_hits() {
+        let regex = compile_regex("todo", false).unwrap();
+        let text = "first\nTODO: one\r\nsecond\ntodo two todo\ntodo three\n";
+
+        let hits = search_text(text, &regex, 2).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].line, 2);
+        assert_eq!(hits[0].snippet, "TODO: one");
+        assert_eq!(hits[1].line, 4);
+        assert_eq!(hits[1].match_ranges, vec![(0, 4), (9, 13)]);
+
+        // Ranges count characters, not bytes
+        let hits = search_text("naïve café: todo", &regex, 5).unwrap();
+        assert_eq!(hits[0].match_ranges, vec![(12, 16)]);
+        let chars: Vec<char> = hits[0].snippet.chars().collect();
+        assert_eq!(chars[12..16].iter().collect::<String>(), "todo");
+
+        // A limit of zero still reports the file as a match
+        assert_eq!(search_text(text, &regex, 0), Some(Vec::new()));
+        assert_eq!(search_text("nothing", &regex, 5), None);
+    }
+
+    /// Tests literal patterns are not read as regexes.
+    #[test]
+    fn test_literal_pattern() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "version a.b").unwrap();
+        fs::write(dir.path().join("b.txt"), "version axb").unwrap();
+
+        let literal = run(dir.path(), &compile("a.b", PatternType::Simple));
+        assert_eq!(literal.len(), 1);
+        assert_eq!(literal[0].name, "a.txt");
+        assert_eq!(literal[0].content_hits[0].match_ranges, vec![(8, 11)]);
+
+        assert_eq!(
+            run(dir.path(), &compile("a.b", PatternType::Regex)).len(),
+            2
+        );
+    }
+
+    /// Tests binary, oversized and filtered-out files are skipped.
+    #[test]
+    fn test_skipped_files() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("text.log"), "error here").unwrap();
+        fs::write(dir.path().join("binary.log"), b"error\0here").unwrap();
+        fs::write(
+            dir.path().join("big.log"),
+            format!("error{}", " ".repeat(2048)),
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub").join("other.txt"), "error").unwrap();
+
+        let search = ContentSearch::compile(
+            "error",
+            &PatternType::Simple,
+            false,
+            Some(
+                serde_json::from_str(
+                    r#"{ "type": "pattern", "pattern": "log", "patternType": "extension" }"#,
+                )
+                .unwrap(),
+            ),
+            ContentSearchOptions {
+                max_file_size: Some("1KB".to_string()),
+                max_hits: None,
+            },
+        )
+        .unwrap();
+
+        let results = run(dir.path(), &search);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "text.log");
+        assert_eq!(results[0].match_target, MatchTarget::Name);
+        assert!(results[0].match_ranges.is_empty());
+    }
+
+    /// Tests invalid searches are refused.
+    #[test]
+    fn test_compile_errors() {
+        let compile = |pattern: &str, pattern_type: PatternType, max_file_size: Option<&str>| {
+            ContentSearch::compile(
+                pattern,
+                &pattern_type,
+                false,
+                None,
+                ContentSearchOptions {
+                    max_file_size: max_file_size.map(str::to_string),
+                    max_hits: None,
+                },
+            )
+        };
+
+        assert_eq!(
+            compile("", PatternType::Simple, None).unwrap_err(),
+            "Pattern cannot be empty"
+        );
+        assert!(compile("[invalid", PatternType::Regex, None).is_err());
+        assert!(compile("*.txt", PatternType::Glob, None).is_err());
+        assert!(compile("x", PatternType::Simple, Some("lots")).is_err());
+    }
+
+    /// Tests a cancelled search reports how far it got.
+    #[test]
+    fn test_cancelled() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "x").unwrap();
+        let cancel = CancellationToken::default();
+        cancel.cancel();
+        let events = Mutex::new(Vec::new());
+
+        let result = run_content_search(
+            &dir.path().to_string_lossy(),
+            true,
+            &compile("x", PatternType::Simple),
+            &cancel,
+            |event| events.lock().unwrap().push(event),
+        );
+
+        assert_eq!(result.unwrap_err(), CANCELLED_ERROR);
+        assert!(matches!(
+            events.lock().unwrap().last(),
+            Some(SearchProgress::Cancelled { .. })
+        ));
+    }
+}
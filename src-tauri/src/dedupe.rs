@@ -0,0 +1,646 @@
+//! Replacing duplicate files with links.
+//!
+//! `dedupe_with_links` replaces the extra copies found by `find_duplicates`
+//! with links to the copy being kept, so every path keeps working while the
+//! contents are stored once. A reflink clone shares the data but stays an
+//! independent file; a hard link makes both paths the same file. Each link is
+//! created under a temporary name next to the duplicate, compared byte for
+//! byte with the duplicate, and only then renamed over it.
+
+use crate::approved_roots::{is_within_root, ApprovedRoots};
+use crate::operations::{CancellationToken, OperationRegistry};
+use crate::protected_paths::{ProtectedPathPolicy, ProtectedPaths, ProtectionReason};
+use crate::rename::is_same_file;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, FileTimes, Metadata};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tauri::ipc::Channel;
+use tauri::State;
+
+/// Buffer size for the byte-for-byte comparison
+const COMPARE_BUFFER_SIZE: usize = 64 * 1024;
+
+// ==================== Types ====================
+
+/// Progress events for replacing duplicates with links.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum DedupeProgress {
+    /// Replacement has started
+    Started {
+        /// Total number of duplicates to replace
+        total_files: usize,
+    },
+    /// A duplicate was processed
+    Progress {
+        /// Current duplicate index (1-based)
+        current: usize,
+        /// Total number of duplicates
+        total: usize,
+        /// Path of the duplicate just processed
+        current_path: String,
+    },
+    /// Replacement completed
+    Completed {
+        /// Number of duplicates replaced with links
+        replaced: usize,
+        /// Number of duplicates that could not be replaced
+        failed: usize,
+        /// Bytes freed by the replacements
+        bytes_reclaimed: u64,
+    },
+    /// Replacement was cancelled; the remaining duplicates were left untouched
+    Cancelled {
+        /// Number of duplicates replaced before cancellation
+        replaced: usize,
+        /// Number of failures before cancellation
+        failed: usize,
+        /// Total number of duplicates requested
+        total: usize,
+    },
+}
+
+/// Which kind of link to replace duplicates with
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkMode {
+    /// A reflink clone where the filesystem supports it, otherwise a hard link
+    #[default]
+    Auto,
+    /// Always a hard link
+    HardLink,
+    /// Always a reflink clone; fails on filesystems without reflinks
+    Reflink,
+}
+
+/// The kind of link a duplicate was replaced with
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkKind {
+    /// Both paths are now the same file
+    HardLink,
+    /// The duplicate is a copy-on-write clone sharing the kept copy's data
+    Reflink,
+}
+
+/// A copy to keep and the duplicates to replace with links to it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupeGroup {
+    /// The copy to keep
+    pub keep: String,
+    /// The copies to replace
+    pub duplicates: Vec<String>,
+}
+
+/// Result of replacing duplicates with links
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DedupeResult {
+    /// Duplicates that were replaced, with the kind of link used
+    pub replaced: Vec<(String, LinkKind)>,
+    /// Duplicates that were already hard links to the kept copy
+    pub already_linked: Vec<String>,
+    /// Duplicates that could not be replaced, with error messages
+    pub failed: Vec<(String, String)>,
+    /// Duplicates refused by the protected-path policy, with the reason; they
+    /// are also listed in `failed`
+    pub protected: Vec<(String, ProtectionReason)>,
+    /// Bytes freed by the replacements
+    pub bytes_reclaimed: u64,
+    /// The approved root the operation was restricted to
+    pub root: Option<String>,
+}
+
+/// What happened to a single duplicate
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Replacement {
+    /// The duplicate was replaced, freeing this many bytes
+    Linked(LinkKind, u64),
+    /// The duplicate already was the kept file
+    AlreadyLinked,
+}
+
+// ==================== Linking ====================
+
+/// Creates a reflink clone of `from` at `to` with the `FICLONE` ioctl.
+///
+/// Supported by btrfs, XFS and other copy-on-write filesystems; fails on
+/// filesystems without reflinks and across filesystems.
+#[cfg(target_os = "linux")]
+fn reflink(from: &Path, to: &Path) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let source = File::open(from)?;
+    let dest = File::options().write(true).create_new(true).open(to)?;
+
+    // SAFETY: both descriptors stay open for the duration of the call
+    let result = unsafe { libc::ioctl(dest.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+    if result == -1 {
+        let error = io::Error::last_os_error();
+        drop(dest);
+        let _ = fs::remove_file(to);
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Creates a reflink clone of `from` at `to`.
+#[cfg(not(target_os = "linux"))]
+fn reflink(_from: &Path, _to: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reflinks are not supported on this platform",
+    ))
+}
+
+/// Gives a reflink clone the permissions and timestamps of the duplicate it replaces.
+fn copy_attributes(clone: &Path, duplicate: &Metadata) -> io::Result<()> {
+    let times = FileTimes::new()
+        .set_accessed(duplicate.accessed()?)
+        .set_modified(duplicate.modified()?);
+    File::options().write(true).open(clone)?.set_times(times)?;
+    fs::set_permissions(clone, duplicate.permissions())
+}
+
+/// Creates a link to `keep` at `temp_path`.
+///
+/// # Returns
+///
+/// * `Ok(LinkKind)` - The kind of link created
+/// * `Err(String)` - If the link could not be created; nothing is left behind
+fn create_link(
+    keep: &Path,
+    temp_path: &Path,
+    mode: LinkMode,
+    duplicate: &Metadata,
+) -> Result<LinkKind, String> {
+    let clone = || -> io::Result<()> {
+        reflink(keep, temp_path)?;
+        copy_attributes(temp_path, duplicate).inspect_err(|_| {
+            let _ = fs::remove_file(temp_path);
+        })
+    };
+    let hard_link = || {
+        fs::hard_link(keep, temp_path).map_err(|e| {
+            if e.kind() == io::ErrorKind::CrossesDevices {
+                "Cannot hard link across filesystems".to_string()
+            } else {
+                format!("Cannot create hard link: {}", e)
+            }
+        })
+    };
+
+    match mode {
+        LinkMode::HardLink => hard_link().map(|_| LinkKind::HardLink),
+        LinkMode::Reflink => clone()
+            .map(|_| LinkKind::Reflink)
+            .map_err(|e| format!("Cannot create reflink: {}", e)),
+        LinkMode::Auto => match clone() {
+            Ok(()) => Ok(LinkKind::Reflink),
+            Err(_) => hard_link().map(|_| LinkKind::HardLink),
+        },
+    }
+}
+
+/// Compares the contents of two files byte for byte.
+fn contents_equal(a: &Path, b: &Path) -> io::Result<bool> {
+    let mut a = File::open(a)?;
+    let mut b = File::open(b)?;
+    if a.metadata()?.len() != b.metadata()?.len() {
+        return Ok(false);
+    }
+
+    let mut buffer_a = vec![0u8; COMPARE_BUFFER_SIZE];
+    let mut buffer_b = vec![0u8; COMPARE_BUFFER_SIZE];
+
+    loop {
+        let read = a.read(&mut buffer_a)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut buffer_b[..read])?;
+        if buffer_a[..read] != buffer_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Builds the temporary name a link is created under, next to the duplicate.
+fn temp_link_path(duplicate: &Path) -> PathBuf {
+    let file_name = duplicate
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_name = format!(".{}.{}.linking", file_name, std::process::id());
+
+    match duplicate.parent() {
+        Some(parent) => parent.join(temp_name),
+        None => PathBuf::from(temp_name),
+    }
+}
+
+/// Bytes freed by replacing a file, which only frees its data when no other
+/// hard link points to it.
+#[cfg(unix)]
+fn reclaimable_bytes(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    if metadata.nlink() > 1 {
+        0
+    } else {
+        metadata.len()
+    }
+}
+
+/// Bytes freed by replacing a file.
+#[cfg(not(unix))]
+fn reclaimable_bytes(metadata: &Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Replaces a duplicate with a link to the kept copy.
+///
+/// # Arguments
+///
+/// * `keep` - The copy to keep
+/// * `duplicate` - The copy to replace
+/// * `mode` - Which kind of link to create
+///
+/// # Returns
+///
+/// * `Ok(Replacement)` - The duplicate was replaced or already was the kept file
+/// * `Err(String)` - If the files differ or the link could not be put in
+///   place; the duplicate is left untouched
+fn replace_with_link(keep: &Path, duplicate: &Path, mode: LinkMode) -> Result<Replacement, String> {
+    let keep_metadata = fs::symlink_metadata(keep)
+        .map_err(|e| format!("Cannot read the kept copy {}: {}", keep.display(), e))?;
+    if !keep_metadata.is_file() {
+        return Err(format!("The kept copy {} is not a file", keep.display()));
+    }
+
+    let metadata = fs::symlink_metadata(duplicate).map_err(|e| e.to_string())?;
+    if !metadata.is_file() {
+        return Err("Not a file".to_string());
+    }
+    if is_same_file(keep, duplicate) {
+        return Ok(Replacement::AlreadyLinked);
+    }
+    if metadata.len() != keep_metadata.len() {
+        return Err("Contents differ from the kept copy".to_string());
+    }
+
+    let temp_path = temp_link_path(duplicate);
+    if temp_path.symlink_metadata().is_ok() {
+        return Err(format!(
+            "Temporary path {} already exists",
+            temp_path.display()
+        ));
+    }
+
+    let kind = create_link(keep, &temp_path, mode, &metadata)?;
+
+    // Compare what is about to replace the duplicate, as late as possible
+    let replace = || -> Result<(), String> {
+        match contents_equal(&temp_path, duplicate) {
+            Ok(true) => {}
+            Ok(false) => return Err("Contents differ from the kept copy".to_string()),
+            Err(e) => return Err(format!("Cannot compare contents: {}", e)),
+        }
+        fs::rename(&temp_path, duplicate).map_err(|e| e.to_string())
+    };
+
+    if let Err(e) = replace() {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    Ok(Replacement::Linked(kind, reclaimable_bytes(&metadata)))
+}
+
+/// Replaces the duplicates of each group with links to its kept copy.
+///
+/// # Arguments
+///
+/// * `groups` - The kept copies and their duplicates
+/// * `mode` - Which kind of link to create
+/// * `policy` - The protected-path policy, restricted to the approved root
+/// * `cancel` - Stops the run before the next duplicate when cancelled
+/// * `on_progress` - Called with (index, path) after each duplicate
+///
+/// # Returns
+///
+/// A `DedupeResult`; duplicates not reached before cancellation are not listed.
+fn run_dedupe<F>(
+    groups: Vec<DedupeGroup>,
+    mode: LinkMode,
+    policy: &ProtectedPathPolicy,
+    cancel: &CancellationToken,
+    mut on_progress: F,
+) -> DedupeResult
+where
+    F: FnMut(usize, &str),
+{
+    let mut replaced = Vec::new();
+    let mut already_linked = Vec::new();
+    let mut failed = Vec::new();
+    let mut protected = Vec::new();
+    let mut bytes_reclaimed = 0;
+    let mut index = 0;
+
+    'groups: for group in groups {
+        let keep = Path::new(&group.keep);
+        let keep_outside_root = policy
+            .scope()
+            .is_some_and(|root| !is_within_root(keep, root));
+
+        for duplicate in group.duplicates {
+            if cancel.is_cancelled() {
+                break 'groups;
+            }
+
+            let path = Path::new(&duplicate);
+            if let Some(reason) = policy.check(path) {
+                failed.push((
+                    duplicate.clone(),
+                    format!("Refusing to replace protected path ({})", reason.describe()),
+                ));
+                protected.push((duplicate.clone(), reason));
+            } else if keep_outside_root {
+                failed.push((
+                    duplicate.clone(),
+                    format!("The kept copy {} is outside the approved root", group.keep),
+                ));
+            } else {
+                match replace_with_link(keep, path, mode) {
+                    Ok(Replacement::Linked(kind, bytes)) => {
+                        bytes_reclaimed += bytes;
+                        replaced.push((duplicate.clone(), kind));
+                    }
+                    Ok(Replacement::AlreadyLinked) => already_linked.push(duplicate.clone()),
+                    Err(e) => failed.push((duplicate.clone(), e)),
+                }
+            }
+
+            on_progress(index, &duplicate);
+            index += 1;
+        }
+    }
+
+    DedupeResult {
+        replaced,
+        already_linked,
+        failed,
+        protected,
+        bytes_reclaimed,
+        root: policy
+            .scope()
+            .map(|root| root.to_string_lossy().to_string()),
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Replaces duplicate files with links to the copy being kept.
+///
+/// Each duplicate is replaced with a reflink clone or a hard link to its
+/// group's kept copy. The link is compared byte for byte with the duplicate
+/// right before it is renamed over it; duplicates whose contents differ are
+/// left untouched. Like `batch_delete`, only duplicates inside the approved
+/// root and not protected are replaced.
+///
+/// When started with an `operation_id`, the run can be stopped with
+/// `cancel_operation`; duplicates not yet reached are left in place.
+///
+/// # Arguments
+///
+/// * `groups` - The kept copies and their duplicates, as reported by
+///   `find_duplicates`
+/// * `mode` - Reflink clones with a hard link fallback (default), or always
+///   hard links or reflinks
+/// * `root` - Root from `choose_operation_root`; paths outside it are refused
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `protected_paths` - The protected-path settings managed by Tauri
+/// * `roots` - The approved roots managed by Tauri
+/// * `operations` - The operation registry managed by Tauri
+/// * `on_progress` - Channel to send progress events
+///
+/// # Returns
+///
+/// * `Ok(DedupeResult)` - The replaced and failed duplicates and the bytes reclaimed
+/// * `Err(String)` - If `root` is not an approved root
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn dedupe_with_links(
+    groups: Vec<DedupeGroup>,
+    mode: Option<LinkMode>,
+    root: String,
+    operation_id: Option<u64>,
+    protected_paths: State<'_, ProtectedPaths>,
+    roots: State<'_, ApprovedRoots>,
+    operations: State<'_, OperationRegistry>,
+    on_progress: Channel<DedupeProgress>,
+) -> Result<DedupeResult, String> {
+    let policy = protected_paths.policy().within_root(roots.resolve(&root)?);
+    let operation = operations.begin(operation_id);
+    let mode = mode.unwrap_or_default();
+
+    // Run the heavy work in a blocking thread to keep the main thread responsive
+    tokio::task::spawn_blocking(move || {
+        let total = groups.iter().map(|group| group.duplicates.len()).sum();
+        let _ = on_progress.send(DedupeProgress::Started { total_files: total });
+
+        let result = run_dedupe(groups, mode, &policy, operation.token(), |index, path| {
+            let _ = on_progress.send(DedupeProgress::Progress {
+                current: index + 1,
+                total,
+                current_path: path.to_string(),
+            });
+        });
+
+        let replaced = result.replaced.len();
+        let failed = result.failed.len();
+
+        if replaced + failed + result.already_linked.len() < total {
+            let _ = on_progress.send(DedupeProgress::Cancelled {
+                replaced,
+                failed,
+                total,
+            });
+        } else {
+            let _ = on_progress.send(DedupeProgress::Completed {
+                replaced,
+                failed,
+                bytes_reclaimed: result.bytes_reclaimed,
+            });
+        }
+
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Runs a dedupe of one group without a protected-path policy.
+    fn dedupe(keep: &Path, duplicates: &[&Path], mode: LinkMode) -> DedupeResult {
+        let group = DedupeGroup {
+            keep: keep.to_string_lossy().to_string(),
+            duplicates: duplicates
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+        };
+        run_dedupe(
+            vec![group],
+            mode,
+            &ProtectedPathPolicy::default(),
+            &CancellationToken::default(),
+            |_, _| {},
+        )
+    }
+
+    /// Tests hard linking makes the duplicate the kept file and counts the freed bytes.
+    #[test]
+    fn test_dedupe_hard_link() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let keep = dir.path().join("keep.txt");
+        let copy = dir.path().join("copy.txt");
+        fs::write(&keep, "same contents").unwrap();
+        fs::write(&copy, "same contents").unwrap();
+
+        let result = dedupe(&keep, &[&copy], LinkMode::HardLink);
+
+        assert_eq!(
+            result.replaced,
+            vec![(copy.to_string_lossy().to_string(), LinkKind::HardLink)]
+        );
+        assert_eq!(result.bytes_reclaimed, 13);
+        assert!(is_same_file(&keep, &copy));
+        assert!(!temp_link_path(&copy).exists());
+    }
+
+    /// Tests the automatic mode replaces the duplicate with whichever link the filesystem supports.
+    #[test]
+    fn test_dedupe_auto_mode() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let keep = dir.path().join("keep.bin");
+        let copy = dir.path().join("copy.bin");
+        fs::write(&keep, vec![7u8; 10_000]).unwrap();
+        fs::write(&copy, vec![7u8; 10_000]).unwrap();
+
+        let result = dedupe(&keep, &[&copy], LinkMode::Auto);
+
+        assert!(result.failed.is_empty());
+        assert_eq!(result.replaced.len(), 1);
+        if result.replaced[0].1 == LinkKind::HardLink {
+            assert!(is_same_file(&keep, &copy));
+        }
+        assert_eq!(fs::read(&copy).unwrap(), vec![7u8; 10_000]);
+    }
+
+    /// Tests files whose contents differ are left untouched.
+    #[test]
+    fn test_dedupe_refuses_different_contents() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let keep = dir.path().join("keep.txt");
+        let copy = dir.path().join("copy.txt");
+        fs::write(&keep, "contents one").unwrap();
+        fs::write(&copy, "contents two").unwrap();
+
+        let result = dedupe(&keep, &[&copy], LinkMode::HardLink);
+
+        assert!(result.replaced.is_empty());
+        assert_eq!(result.failed[0].1, "Contents differ from the kept copy");
+        assert_eq!(result.bytes_reclaimed, 0);
+        assert_eq!(fs::read_to_string(&copy).unwrap(), "contents two");
+        assert!(!is_same_file(&keep, &copy));
+        assert!(!temp_link_path(&copy).exists());
+    }
+
+    /// Tests existing hard links are reported without freeing anything.
+    #[test]
+    fn test_dedupe_already_linked() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let keep = dir.path().join("keep.txt");
+        let link = dir.path().join("link.txt");
+        fs::write(&keep, "contents").unwrap();
+        fs::hard_link(&keep, &link).unwrap();
+
+        let result = dedupe(&keep, &[&link], LinkMode::HardLink);
+
+        assert_eq!(
+            result.already_linked,
+            vec![link.to_string_lossy().to_string()]
+        );
+        assert!(result.replaced.is_empty());
+        assert_eq!(result.bytes_reclaimed, 0);
+    }
+
+    /// Tests duplicates outside the approved root are refused.
+    #[test]
+    fn test_dedupe_outside_root() {
+        let root = tempdir().expect("Failed to create temp dir");
+        let outside = tempdir().expect("Failed to create temp dir");
+        let keep = root.path().join("keep.txt");
+        let copy = outside.path().join("copy.txt");
+        fs::write(&keep, "contents").unwrap();
+        fs::write(&copy, "contents").unwrap();
+        let copy_path = copy.to_string_lossy().to_string();
+
+        let policy =
+            ProtectedPathPolicy::default().within_root(fs::canonicalize(root.path()).unwrap());
+        let result = run_dedupe(
+            vec![DedupeGroup {
+                keep: keep.to_string_lossy().to_string(),
+                duplicates: vec![copy_path.clone()],
+            }],
+            LinkMode::HardLink,
+            &policy,
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert_eq!(
+            result.protected,
+            vec![(copy_path, ProtectionReason::OutsideRoot)]
+        );
+        assert!(!is_same_file(&keep, &copy));
+    }
+
+    /// Tests a cancelled run leaves the remaining duplicates in place.
+    #[test]
+    fn test_dedupe_cancelled() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let keep = dir.path().join("keep.txt");
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        for path in [&keep, &first, &second] {
+            fs::write(path, "contents").unwrap();
+        }
+        let cancel = CancellationToken::default();
+
+        let result = run_dedupe(
+            vec![DedupeGroup {
+                keep: keep.to_string_lossy().to_string(),
+                duplicates: vec![
+                    first.to_string_lossy().to_string(),
+                    second.to_string_lossy().to_string(),
+                ],
+            }],
+            LinkMode::HardLink,
+            &ProtectedPathPolicy::default(),
+            &cancel,
+            |_, _| cancel.cancel(),
+        );
+
+        assert_eq!(result.replaced.len(), 1);
+        assert!(is_same_file(&keep, &first));
+        assert!(!is_same_file(&keep, &second));
+    }
+}
@@ -0,0 +1,735 @@
+//! Duplicate file finder.
+//!
+//! `find_duplicates` groups files with identical contents in three passes,
+//! each only looking at files that survived the one before: files are
+//! bucketed by size, buckets are split by a hash of the first 4 KiB, and the
+//! remaining candidates by a hash of their whole contents. Each group names a
+//! suggested copy to keep; the other copies can be passed to `batch_delete`.
+
+use crate::approved_roots::is_within_root;
+use crate::operations::{CancellationToken, OperationRegistry, CANCELLED_ERROR};
+use crate::search_expression::{Candidate, FilterExpression, SearchExpression};
+use crate::search_filters::{parse_size, TimeKind};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::ipc::Channel;
+use tauri::State;
+use walkdir::WalkDir;
+
+/// Bytes hashed by the partial pass; smaller files are fully hashed by it
+const PARTIAL_HASH_LEN: u64 = 4 * 1024;
+
+/// Buffer size for the full-content hash
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Send progress every 100 files
+const PROGRESS_INTERVAL: usize = 100;
+
+// ==================== Types ====================
+
+/// Progress events for the duplicate finder.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum DuplicateProgress {
+    /// The search has started
+    Started {
+        /// The base directory being searched
+        base_path: String,
+    },
+    /// Currently scanning directories - sent periodically during traversal
+    Scanning {
+        /// Current directory being scanned
+        current_dir: String,
+        /// Number of files found so far
+        files_found: usize,
+    },
+    /// Files with a matching size are being hashed
+    Hashing {
+        /// Whether the first bytes or the whole contents are hashed
+        stage: HashStage,
+        /// Number of files hashed so far in this stage
+        files_hashed: usize,
+        /// Number of files to hash in this stage
+        total_files: usize,
+    },
+    /// The search completed successfully
+    Completed {
+        /// Number of duplicate groups found
+        groups_found: usize,
+        /// Bytes used by the copies beyond the first in each group
+        wasted_bytes: u64,
+    },
+    /// The search was cancelled before it finished
+    Cancelled {
+        /// Number of files found before cancellation
+        files_found: usize,
+    },
+}
+
+/// A hashing pass of the duplicate finder
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HashStage {
+    /// Hashing the first 4 KiB of each file
+    Partial,
+    /// Hashing the whole contents
+    Full,
+}
+
+/// Which copy in a group of duplicates is suggested to keep
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum KeepStrategy {
+    /// The copy modified longest ago
+    #[default]
+    Oldest,
+    /// The copy with the shortest path, then the oldest
+    ShortestPath,
+    /// The oldest copy inside a folder, or the oldest copy if none is inside it
+    InFolder {
+        /// The preferred folder
+        folder: String,
+    },
+}
+
+/// A file in a group of duplicates
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DuplicateFile {
+    /// Full path to the file
+    pub path: String,
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+}
+
+/// Files with identical contents
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// Size of each copy in bytes
+    pub size: u64,
+    /// SHA-256 of the contents as lowercase hex
+    pub hash: String,
+    /// The copy suggested to keep
+    pub keep: DuplicateFile,
+    /// The other copies, which can be deleted or replaced with links
+    pub duplicates: Vec<DuplicateFile>,
+}
+
+/// Result of a duplicate search
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DuplicateReport {
+    /// Duplicate groups, the most wasted space first
+    pub groups: Vec<DuplicateGroup>,
+    /// Number of files considered
+    pub files_scanned: usize,
+    /// Bytes used by all the copies beyond the one kept in each group
+    pub wasted_bytes: u64,
+}
+
+/// A file considered by the search
+#[derive(Debug, Clone)]
+struct FileInfo {
+    path: PathBuf,
+    size: u64,
+    modified_ms: Option<u64>,
+}
+
+/// A duplicate search with its filter and options compiled
+#[derive(Debug, Clone)]
+struct DuplicateSearch {
+    min_size: u64,
+    files: Option<SearchExpression>,
+    keep: KeepStrategy,
+    /// The canonical folder of an `InFolder` strategy, if it exists
+    keep_folder: Option<PathBuf>,
+}
+
+// ==================== Hashing ====================
+
+/// Hashes up to `limit` bytes of a file with SHA-256.
+fn hash_file(path: &Path, limit: Option<u64>) -> io::Result<[u8; 32]> {
+    let file = File::open(path)?;
+    let mut reader: Box<dyn Read> = match limit {
+        Some(limit) => Box::new(file.take(limit)),
+        None => Box::new(file),
+    };
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize().into())
+}
+
+/// Formats a digest as lowercase hex.
+fn to_hex(digest: &[u8; 32]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Splits groups of candidates by a hash of their contents, in parallel.
+///
+/// Files that cannot be read are dropped, as are groups left with a single
+/// file. Stops early, with an incomplete result, once cancelled.
+///
+/// # Arguments
+///
+/// * `groups` - Candidate groups; files in different groups are never merged
+/// * `stage` - The stage reported in progress events
+/// * `cancel` - Stops hashing when cancelled
+/// * `on_progress` - Receives progress events
+fn split_by_hash<F>(
+    groups: Vec<Vec<FileInfo>>,
+    stage: HashStage,
+    cancel: &CancellationToken,
+    on_progress: &F,
+) -> Vec<([u8; 32], Vec<FileInfo>)>
+where
+    F: Fn(DuplicateProgress) + Sync,
+{
+    let limit = match stage {
+        HashStage::Partial => Some(PARTIAL_HASH_LEN),
+        HashStage::Full => None,
+    };
+    let files: Vec<(usize, FileInfo)> = groups
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, group)| group.into_iter().map(move |file| (index, file)))
+        .collect();
+    let total_files = files.len();
+    let files_hashed = AtomicUsize::new(0);
+
+    on_progress(DuplicateProgress::Hashing {
+        stage,
+        files_hashed: 0,
+        total_files,
+    });
+
+    let hashed: Vec<(usize, [u8; 32], FileInfo)> = files
+        .into_par_iter()
+        .filter_map(|(index, file)| {
+            // Remaining files are skipped once cancelled
+            if cancel.is_cancelled() {
+                return None;
+            }
+
+            let digest = hash_file(&file.path, limit).ok();
+            let done = files_hashed.fetch_add(1, Ordering::Relaxed) + 1;
+            if done.is_multiple_of(PROGRESS_INTERVAL) {
+                on_progress(DuplicateProgress::Hashing {
+                    stage,
+                    files_hashed: done,
+                    total_files,
+                });
+            }
+            digest.map(|digest| (index, digest, file))
+        })
+        .collect();
+
+    let mut split: HashMap<(usize, [u8; 32]), Vec<FileInfo>> = HashMap::new();
+    for (index, digest, file) in hashed {
+        split.entry((index, digest)).or_default().push(file);
+    }
+
+    split
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|((_, digest), files)| (digest, files))
+        .collect()
+}
+
+// ==================== Finding ====================
+
+impl DuplicateSearch {
+    /// Compiles a duplicate search.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DuplicateSearch)` - The compiled search
+    /// * `Err(String)` - If the minimum size or filter expression is invalid
+    fn compile(
+        min_size: Option<String>,
+        keep: KeepStrategy,
+        expression: Option<FilterExpression>,
+    ) -> Result<Self, String> {
+        let keep_folder = match &keep {
+            KeepStrategy::InFolder { folder } => fs::canonicalize(folder).ok(),
+            _ => None,
+        };
+
+        Ok(DuplicateSearch {
+            // Empty files are all identical, so they are skipped by default
+            min_size: min_size
+                .as_deref()
+                .map(parse_size)
+                .transpose()?
+                .unwrap_or(1),
+            files: expression.map(|e| e.compile()).transpose()?,
+            keep,
+            keep_folder,
+        })
+    }
+
+    /// Picks the copy to keep from a group.
+    ///
+    /// # Returns
+    ///
+    /// The index of the copy in `files`.
+    fn choose_keep(&self, files: &[FileInfo]) -> usize {
+        let oldest = |a: &FileInfo, b: &FileInfo| {
+            let time = |file: &FileInfo| file.modified_ms.unwrap_or(u64::MAX);
+            time(a).cmp(&time(b)).then_with(|| a.path.cmp(&b.path))
+        };
+        let shortest = |a: &FileInfo, b: &FileInfo| {
+            let length = |file: &FileInfo| file.path.as_os_str().len();
+            length(a).cmp(&length(b)).then_with(|| oldest(a, b))
+        };
+        let in_folder = |file: &FileInfo| {
+            self.keep_folder
+                .as_ref()
+                .is_some_and(|folder| is_within_root(&file.path, folder))
+        };
+
+        let indices = 0..files.len();
+        let best = match self.keep {
+            KeepStrategy::Oldest => indices.min_by(|&a, &b| oldest(&files[a], &files[b])),
+            KeepStrategy::ShortestPath => indices.min_by(|&a, &b| shortest(&files[a], &files[b])),
+            KeepStrategy::InFolder { .. } => indices.min_by(|&a, &b| {
+                // Copies inside the folder sort first
+                in_folder(&files[b])
+                    .cmp(&in_folder(&files[a]))
+                    .then_with(|| oldest(&files[a], &files[b]))
+            }),
+        };
+        best.unwrap_or(0)
+    }
+}
+
+impl From<&FileInfo> for DuplicateFile {
+    fn from(file: &FileInfo) -> Self {
+        DuplicateFile {
+            path: file.path.to_string_lossy().to_string(),
+            modified_ms: file.modified_ms,
+        }
+    }
+}
+
+/// Lists the regular files a duplicate search considers.
+///
+/// Symbolic links are not followed, and on Unix further hard links to a
+/// file already listed are skipped, since they use no extra space.
+///
+/// # Returns
+///
+/// * `Ok(Vec<FileInfo>)` - The files
+/// * `Err(String)` - If the search is cancelled
+fn collect_files<F>(
+    base_path: &str,
+    search: &DuplicateSearch,
+    cancel: &CancellationToken,
+    on_progress: &F,
+) -> Result<Vec<FileInfo>, String>
+where
+    F: Fn(DuplicateProgress),
+{
+    let mut files = Vec::new();
+    #[cfg(unix)]
+    let mut seen = HashSet::new();
+    let mut last_progress_dir = String::new();
+
+    for entry in WalkDir::new(base_path).into_iter().filter_map(|e| e.ok()) {
+        if cancel.is_cancelled() {
+            on_progress(DuplicateProgress::Cancelled {
+                files_found: files.len(),
+            });
+            return Err(CANCELLED_ERROR.to_string());
+        }
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let Some(candidate) = Candidate::new(path, Path::new(base_path)) else {
+            continue;
+        };
+        if let Some(filter) = &search.files {
+            if !filter.matches(&candidate) {
+                continue;
+            }
+        }
+        let Ok(metadata) = candidate.metadata() else {
+            continue;
+        };
+        if metadata.len() < search.min_size {
+            continue;
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if !seen.insert((metadata.dev(), metadata.ino())) {
+                continue;
+            }
+        }
+
+        if files.len().is_multiple_of(PROGRESS_INTERVAL) {
+            if let Some(parent) = path.parent() {
+                let current_dir = parent.to_string_lossy().to_string();
+                if current_dir != last_progress_dir {
+                    last_progress_dir = current_dir.clone();
+                    on_progress(DuplicateProgress::Scanning {
+                        current_dir,
+                        files_found: files.len(),
+                    });
+                }
+            }
+        }
+
+        files.push(FileInfo {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified_ms: TimeKind::Modified.read(metadata),
+        });
+    }
+
+    Ok(files)
+}
+
+/// Finds duplicate files, reporting progress through a callback.
+///
+/// # Arguments
+///
+/// * `base_path` - The directory to search, including subdirectories
+/// * `search` - The compiled search
+/// * `cancel` - Stops the search when cancelled
+/// * `on_progress` - Receives progress events
+///
+/// # Returns
+///
+/// * `Ok(DuplicateReport)` - The duplicate groups
+/// * `Err(String)` - If the search is cancelled
+fn run_find_duplicates<F>(
+    base_path: &str,
+    search: &DuplicateSearch,
+    cancel: &CancellationToken,
+    on_progress: F,
+) -> Result<DuplicateReport, String>
+where
+    F: Fn(DuplicateProgress) + Sync,
+{
+    on_progress(DuplicateProgress::Started {
+        base_path: base_path.to_string(),
+    });
+
+    // Pass 1: Only files of the same size can be identical
+    let files = collect_files(base_path, search, cancel, &on_progress)?;
+    let files_scanned = files.len();
+    let mut by_size: HashMap<u64, Vec<FileInfo>> = HashMap::new();
+    for file in files {
+        by_size.entry(file.size).or_default().push(file);
+    }
+    let same_size: Vec<Vec<FileInfo>> = by_size
+        .into_values()
+        .filter(|files| files.len() > 1)
+        .collect();
+
+    // Pass 2: Split by the first bytes, which is the whole file for small files
+    let partial = split_by_hash(same_size, HashStage::Partial, cancel, &on_progress);
+    let (complete, larger): (Vec<_>, Vec<_>) = partial
+        .into_iter()
+        .partition(|(_, files)| files[0].size <= PARTIAL_HASH_LEN);
+
+    // Pass 3: Split larger files by their whole contents
+    let larger = larger.into_iter().map(|(_, files)| files).collect();
+    let full = split_by_hash(larger, HashStage::Full, cancel, &on_progress);
+
+    if cancel.is_cancelled() {
+        on_progress(DuplicateProgress::Cancelled {
+            files_found: files_scanned,
+        });
+        return Err(CANCELLED_ERROR.to_string());
+    }
+
+    let mut groups: Vec<DuplicateGroup> = complete
+        .into_iter()
+        .chain(full)
+        .map(|(digest, files)| {
+            let keep = search.choose_keep(&files);
+            let mut duplicates: Vec<DuplicateFile> = files
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != keep)
+                .map(|(_, file)| file.into())
+                .collect();
+            duplicates.sort_by(|a, b| a.path.cmp(&b.path));
+
+            DuplicateGroup {
+                size: files[keep].size,
+                hash: to_hex(&digest),
+                keep: (&files[keep]).into(),
+                duplicates,
+            }
+        })
+        .collect();
+
+    let wasted = |group: &DuplicateGroup| group.size * group.duplicates.len() as u64;
+    groups.sort_by(|a, b| {
+        wasted(b)
+            .cmp(&wasted(a))
+            .then_with(|| a.keep.path.cmp(&b.keep.path))
+    });
+    let wasted_bytes = groups.iter().map(wasted).sum();
+
+    on_progress(DuplicateProgress::Completed {
+        groups_found: groups.len(),
+        wasted_bytes,
+    });
+
+    Ok(DuplicateReport {
+        groups,
+        files_scanned,
+        wasted_bytes,
+    })
+}
+
+// ==================== Tauri Commands ====================
+
+/// Finds groups of files with identical contents under a directory.
+///
+/// Files are compared by size, then by a hash of their first 4 KiB, then by
+/// a SHA-256 of their whole contents. Each group suggests a copy to keep;
+/// the remaining copies can be passed to `batch_delete`.
+///
+/// # Arguments
+///
+/// * `base_path` - The directory to search, including subdirectories
+/// * `min_size` - Smallest file to consider, such as `1MB` (default 1 byte,
+///   which skips empty files)
+/// * `keep` - How the copy to keep is chosen (default oldest)
+/// * `expression` - A filter expression selecting which files to consider
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `operations` - The operation registry managed by Tauri
+/// * `on_progress` - Channel to send progress events
+///
+/// # Returns
+///
+/// * `Ok(DuplicateReport)` - The duplicate groups, the most wasted space first
+/// * `Err(String)` - If the minimum size or filter expression is invalid,
+///   or the search is cancelled
+#[tauri::command]
+pub async fn find_duplicates(
+    base_path: String,
+    min_size: Option<String>,
+    keep: Option<KeepStrategy>,
+    expression: Option<FilterExpression>,
+    operation_id: Option<u64>,
+    operations: State<'_, OperationRegistry>,
+    on_progress: Channel<DuplicateProgress>,
+) -> Result<DuplicateReport, String> {
+    let operation = operations.begin(operation_id);
+    let search = DuplicateSearch::compile(min_size, keep.unwrap_or_default(), expression)?;
+
+    // Run the heavy work in a blocking thread to keep the main thread responsive
+    tokio::task::spawn_blocking(move || {
+        run_find_duplicates(&base_path, &search, operation.token(), |event| {
+            let _ = on_progress.send(event);
+        })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    /// Runs a search without progress events.
+    fn find(dir: &Path, search: &DuplicateSearch) -> DuplicateReport {
+        run_find_duplicates(
+            &dir.to_string_lossy(),
+            search,
+            &CancellationToken::default(),
+            |_| {},
+        )
+        .unwrap()
+    }
+
+    /// Writes a file and sets its modification time to `age_days` days ago.
+    fn write_aged(path: &Path, contents: &[u8], age_days: u64) {
+        fs::write(path, contents).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_days * 24 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    /// Tests identical files are grouped, including files that only differ after the partial hash.
+    #[test]
+    fn test_find_duplicates_groups() {
+        let dir = tempdir().unwrap();
+        let base = dir.path();
+        fs::create_dir(base.join("sub")).unwrap();
+        write_aged(&base.join("a.txt"), b"same", 3);
+        write_aged(&base.join("b.txt"), b"same", 2);
+        write_aged(&base.join("sub").join("c.txt"), b"same", 1);
+        write_aged(&base.join("d.txt"), b"diff", 1);
+        write_aged(&base.join("empty1"), b"", 1);
+        write_aged(&base.join("empty2"), b"", 1);
+
+        // Same first 4 KiB, different tails
+        let mut large = vec![7u8; 10_000];
+        fs::write(base.join("large1.bin"), &large).unwrap();
+        fs::write(base.join("large2.bin"), &large).unwrap();
+        large[9_999] = 8;
+        fs::write(base.join("large3.bin"), &large).unwrap();
+
+        let search = DuplicateSearch::compile(None, KeepStrategy::Oldest, None).unwrap();
+        let report = find(base, &search);
+
+        // Empty files are skipped by default
+        assert_eq!(report.files_scanned, 7);
+        assert_eq!(report.groups.len(), 2);
+        assert_eq!(report.wasted_bytes, 10_000 + 2 * 4);
+
+        let large_group = &report.groups[0];
+        assert_eq!(large_group.size, 10_000);
+        assert_eq!(large_group.duplicates.len(), 1);
+        assert!(!large_group.keep.path.ends_with("large3.bin"));
+        assert!(!large_group.duplicates[0].path.ends_with("large3.bin"));
+
+        let small_group = &report.groups[1];
+        assert!(small_group.keep.path.ends_with("a.txt"));
+        assert_eq!(small_group.duplicates.len(), 2);
+        assert_eq!(small_group.hash.len(), 64);
+    }
+
+    /// Tests each strategy for choosing the copy to keep.
+    #[test]
+    fn test_keep_strategies() {
+        let dir = tempdir().unwrap();
+        let base = dir.path();
+        fs::create_dir_all(base.join("archive").join("nested")).unwrap();
+        write_aged(&base.join("archive").join("nested").join("x.txt"), b"x", 1);
+        write_aged(&base.join("long-name.txt"), b"x", 5);
+        write_aged(&base.join("x.txt"), b"x", 3);
+
+        let keep = |strategy: KeepStrategy| {
+            let search = DuplicateSearch::compile(None, strategy, None).unwrap();
+            let report = find(base, &search);
+            report.groups[0].keep.path.clone()
+        };
+
+        assert!(keep(KeepStrategy::Oldest).ends_with("long-name.txt"));
+        assert!(keep(KeepStrategy::ShortestPath)
+            .ends_with(&format!("{}x.txt", std::path::MAIN_SEPARATOR)));
+        assert!(!keep(KeepStrategy::ShortestPath).contains("archive"));
+        let archive = base.join("archive").to_string_lossy().to_string();
+        assert!(keep(KeepStrategy::InFolder { folder: archive }).contains("nested"));
+        // Falls back to the oldest when no copy is in the folder
+        let missing = base.join("missing").to_string_lossy().to_string();
+        assert!(keep(KeepStrategy::InFolder { folder: missing }).ends_with("long-name.txt"));
+    }
+
+    /// Tests hard links to the same file are not reported as duplicates.
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_counted_once() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "same").unwrap();
+        fs::hard_link(dir.path().join("a.txt"), dir.path().join("b.txt")).unwrap();
+
+        let search = DuplicateSearch::compile(None, KeepStrategy::Oldest, None).unwrap();
+        let report = find(dir.path(), &search);
+        assert_eq!(report.files_scanned, 1);
+        assert!(report.groups.is_empty());
+    }
+
+    /// Tests the minimum size and filter expression limit the files considered.
+    #[test]
+    fn test_min_size_and_filter() {
+        let dir = tempdir().unwrap();
+        for name in ["a.log", "b.log", "c.txt", "d.txt"] {
+            fs::write(dir.path().join(name), "same").unwrap();
+        }
+        fs::write(dir.path().join("e.txt"), "").unwrap();
+        fs::write(dir.path().join("f.txt"), "").unwrap();
+
+        let filter = serde_json::from_str(
+            r#"{ "type": "pattern", "pattern": "txt", "patternType": "extension" }"#,
+        )
+        .unwrap();
+        let search =
+            DuplicateSearch::compile(Some("0".to_string()), KeepStrategy::Oldest, Some(filter))
+                .unwrap();
+        let report = find(dir.path(), &search);
+        // c.txt and d.txt, and the two empty files
+        assert_eq!(report.files_scanned, 4);
+        assert_eq!(report.groups.len(), 2);
+
+        let search =
+            DuplicateSearch::compile(Some("1KB".to_string()), KeepStrategy::Oldest, None).unwrap();
+        assert!(find(dir.path(), &search).groups.is_empty());
+
+        assert!(
+            DuplicateSearch::compile(Some("big".to_string()), KeepStrategy::Oldest, None).is_err()
+        );
+    }
+
+    /// Tests progress events and cancellation.
+    #[test]
+    fn test_progress_and_cancel() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "same").unwrap();
+        fs::write(dir.path().join("b.txt"), "same").unwrap();
+        let search = DuplicateSearch::compile(None, KeepStrategy::Oldest, None).unwrap();
+
+        let events = Mutex::new(Vec::new());
+        run_find_duplicates(
+            &dir.path().to_string_lossy(),
+            &search,
+            &CancellationToken::default(),
+            |event| events.lock().unwrap().push(event),
+        )
+        .unwrap();
+        let events = events.into_inner().unwrap();
+        assert!(matches!(events[0], DuplicateProgress::Started { .. }));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DuplicateProgress::Hashing {
+                stage: HashStage::Partial,
+                total_files: 2,
+                ..
+            }
+        )));
+        assert!(matches!(
+            events.last(),
+            Some(DuplicateProgress::Completed {
+                groups_found: 1,
+                wasted_bytes: 4
+            })
+        ));
+
+        let cancel = CancellationToken::default();
+        cancel.cancel();
+        let result = run_find_duplicates(&dir.path().to_string_lossy(), &search, &cancel, |_| {});
+        assert_eq!(result.unwrap_err(), CANCELLED_ERROR);
+    }
+}
@@ -0,0 +1,620 @@
+//! Empty directory finder and cleaner.
+//!
+//! `find_empty_dirs` walks a tree bottom-up and reports every directory that
+//! holds nothing but other empty directories, and optionally junk files such
+//! as `.DS_Store` or `Thumbs.db`. `delete_empty_dirs` checks each directory
+//! again and removes it with its empty subdirectories and junk, deepest first.
+
+use crate::approved_roots::ApprovedRoots;
+use crate::operations::{CancellationToken, OperationRegistry, CANCELLED_ERROR};
+use crate::protected_paths::{ProtectedPathPolicy, ProtectedPaths, ProtectionReason};
+use crate::remove::DeleteProgress;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::ipc::Channel;
+use tauri::State;
+use walkdir::WalkDir;
+
+/// Files that operating systems leave behind in folders, compared case-insensitively
+const JUNK_FILES: &[&str] = &[".DS_Store", "Thumbs.db", "ehthumbs.db", "desktop.ini"];
+
+/// Send progress every 100 directories
+const PROGRESS_INTERVAL: usize = 100;
+
+// ==================== Types ====================
+
+/// Progress events for the empty directory finder.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum EmptyDirProgress {
+    /// The search has started
+    Started {
+        /// The directory being searched
+        base_path: String,
+    },
+    /// Periodic update while walking the tree
+    Scanning {
+        /// Directory currently being checked
+        current_dir: String,
+        /// Number of directories checked so far
+        dirs_scanned: usize,
+    },
+    /// The search completed
+    Completed {
+        /// Number of empty directories found
+        empty_dirs: usize,
+    },
+    /// The search was cancelled
+    Cancelled {
+        /// Number of directories checked before cancellation
+        dirs_scanned: usize,
+    },
+}
+
+/// A directory that holds nothing worth keeping
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmptyDir {
+    /// Path of the directory
+    pub path: String,
+    /// Junk files directly inside the directory, removed along with it
+    pub junk_files: Vec<String>,
+}
+
+/// Result of an empty directory search
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmptyDirReport {
+    /// The empty directories, deepest first; a directory is listed after its
+    /// empty subdirectories
+    pub dirs: Vec<EmptyDir>,
+    /// Number of directories checked
+    pub dirs_scanned: usize,
+}
+
+/// Result of deleting empty directories
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmptyDirCleanup {
+    /// Directories that were removed, including empty subdirectories, deepest first
+    pub deleted_dirs: Vec<String>,
+    /// Junk files removed along with them
+    pub junk_files: Vec<String>,
+    /// Directories that could not be removed, with error messages
+    pub failed: Vec<(String, String)>,
+    /// Directories refused by the protected-path policy, with the reason;
+    /// they are also listed in `failed`
+    pub protected: Vec<(String, ProtectionReason)>,
+    /// The approved root the operation was restricted to
+    pub root: Option<String>,
+}
+
+// ==================== Finding ====================
+
+/// Checks whether a file name is a known junk file.
+fn is_junk_file(name: &str) -> bool {
+    JUNK_FILES
+        .iter()
+        .any(|junk| junk.eq_ignore_ascii_case(name))
+}
+
+/// Finds the empty directories below a base directory.
+///
+/// The tree is walked bottom-up, so each directory is decided after all of
+/// its contents. Symbolic links are never followed and count as contents.
+/// A directory that cannot be read is treated as not empty, as is every
+/// directory above it.
+///
+/// # Arguments
+///
+/// * `base_path` - The directory to search; it is never reported itself
+/// * `include_junk` - Whether junk files count as empty
+/// * `cancel` - Stops the search when cancelled
+/// * `on_progress` - Receives progress events
+///
+/// # Returns
+///
+/// * `Ok(EmptyDirReport)` - The empty directories, deepest first
+/// * `Err(String)` - If the base path is not a directory or the search is cancelled
+fn run_find_empty_dirs<F>(
+    base_path: &Path,
+    include_junk: bool,
+    cancel: &CancellationToken,
+    mut on_progress: F,
+) -> Result<EmptyDirReport, String>
+where
+    F: FnMut(EmptyDirProgress),
+{
+    if !base_path.is_dir() {
+        return Err(format!("Not a directory: {}", base_path.display()));
+    }
+
+    on_progress(EmptyDirProgress::Started {
+        base_path: base_path.to_string_lossy().to_string(),
+    });
+
+    // Directories known to hold something worth keeping
+    let mut not_empty: HashSet<PathBuf> = HashSet::new();
+    let mut junk: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    let mut dirs = Vec::new();
+    let mut dirs_scanned = 0;
+
+    let keep_parent = |not_empty: &mut HashSet<PathBuf>, path: &Path| {
+        if let Some(parent) = path.parent() {
+            not_empty.insert(parent.to_path_buf());
+        }
+    };
+
+    for entry in WalkDir::new(base_path).contents_first(true) {
+        if cancel.is_cancelled() {
+            on_progress(EmptyDirProgress::Cancelled { dirs_scanned });
+            return Err(CANCELLED_ERROR.to_string());
+        }
+
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                if let Some(path) = e.path() {
+                    not_empty.insert(path.to_path_buf());
+                    keep_parent(&mut not_empty, path);
+                }
+                continue;
+            }
+        };
+        if entry.depth() == 0 {
+            continue;
+        }
+
+        let path = entry.path();
+        if !entry.file_type().is_dir() {
+            let name = entry.file_name().to_string_lossy();
+            if include_junk && entry.file_type().is_file() && is_junk_file(&name) {
+                if let Some(parent) = path.parent() {
+                    junk.entry(parent.to_path_buf())
+                        .or_default()
+                        .push(path.to_string_lossy().to_string());
+                }
+            } else {
+                keep_parent(&mut not_empty, path);
+            }
+            continue;
+        }
+
+        dirs_scanned += 1;
+        if dirs_scanned.is_multiple_of(PROGRESS_INTERVAL) {
+            on_progress(EmptyDirProgress::Scanning {
+                current_dir: path.to_string_lossy().to_string(),
+                dirs_scanned,
+            });
+        }
+
+        if not_empty.remove(path) {
+            keep_parent(&mut not_empty, path);
+        } else {
+            dirs.push(EmptyDir {
+                path: path.to_string_lossy().to_string(),
+                junk_files: junk.remove(path).unwrap_or_default(),
+            });
+        }
+    }
+
+    on_progress(EmptyDirProgress::Completed {
+        empty_dirs: dirs.len(),
+    });
+
+    Ok(EmptyDirReport { dirs, dirs_scanned })
+}
+
+// ==================== Deletion ====================
+
+/// Lists the contents of an empty directory, deepest first, ending with the
+/// directory itself.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(PathBuf, bool)>)` - Each entry and whether it is a directory
+/// * `Err(String)` - If the directory holds anything other than empty
+///   directories and, with `include_junk`, junk files
+fn empty_tree(dir: &Path, include_junk: bool) -> Result<Vec<(PathBuf, bool)>, String> {
+    let metadata = fs::symlink_metadata(dir).map_err(|e| e.to_string())?;
+    if !metadata.is_dir() {
+        return Err("Not a directory".to_string());
+    }
+
+    let mut entries = Vec::new();
+    for entry in WalkDir::new(dir).contents_first(true) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let is_dir = entry.file_type().is_dir();
+        let is_junk = include_junk
+            && entry.file_type().is_file()
+            && is_junk_file(&entry.file_name().to_string_lossy());
+
+        if !is_dir && !is_junk {
+            return Err(format!(
+                "Directory is not empty: {}",
+                entry.path().display()
+            ));
+        }
+        entries.push((entry.into_path(), is_dir));
+    }
+
+    Ok(entries)
+}
+
+/// Deletes empty directories with their empty subdirectories, deepest first.
+///
+/// Each directory is checked again before anything is removed, so one that
+/// gained contents since it was found is left alone. Directories refused by
+/// `policy` are not touched and are reported in both `failed` and `protected`.
+///
+/// # Arguments
+///
+/// * `dirs` - The directories to delete
+/// * `include_junk` - Whether junk files are removed along with the directories
+/// * `policy` - The protected-path policy, restricted to the approved root
+/// * `cancel` - Token checked before each directory
+/// * `on_deleted` - Called with the 0-based index and path of each processed directory
+///
+/// # Returns
+///
+/// An `EmptyDirCleanup`; directories not reached before cancellation are not listed.
+fn run_delete_empty_dirs<F>(
+    mut dirs: Vec<String>,
+    include_junk: bool,
+    policy: &ProtectedPathPolicy,
+    cancel: &CancellationToken,
+    mut on_deleted: F,
+) -> EmptyDirCleanup
+where
+    F: FnMut(usize, &str),
+{
+    let mut deleted_dirs = Vec::new();
+    let mut junk_files = Vec::new();
+    let mut failed = Vec::new();
+    let mut protected = Vec::new();
+
+    // Deepest first, so nested directories in the list are removed before their parents
+    dirs.sort_by_key(|dir| std::cmp::Reverse(Path::new(dir).components().count()));
+
+    for (index, dir) in dirs.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            break;
+        }
+
+        let path = Path::new(&dir);
+        if let Some(reason) = policy.check(path) {
+            failed.push((
+                dir.clone(),
+                format!("Refusing to delete protected path ({})", reason.describe()),
+            ));
+            protected.push((dir.clone(), reason));
+            on_deleted(index, &dir);
+            continue;
+        }
+
+        match empty_tree(path, include_junk) {
+            Ok(entries) => {
+                for (entry, is_dir) in entries {
+                    let result = if is_dir {
+                        fs::remove_dir(&entry)
+                    } else {
+                        fs::remove_file(&entry)
+                    };
+                    if let Err(e) = result {
+                        failed.push((dir.clone(), format!("{}: {}", entry.display(), e)));
+                        break;
+                    }
+
+                    let entry = entry.to_string_lossy().to_string();
+                    if is_dir {
+                        deleted_dirs.push(entry);
+                    } else {
+                        junk_files.push(entry);
+                    }
+                }
+            }
+            Err(e) => failed.push((dir.clone(), e)),
+        }
+
+        on_deleted(index, &dir);
+    }
+
+    EmptyDirCleanup {
+        deleted_dirs,
+        junk_files,
+        failed,
+        protected,
+        root: policy
+            .scope()
+            .map(|root| root.to_string_lossy().to_string()),
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Finds empty directories below a base directory.
+///
+/// A directory is empty when it holds nothing, or only other empty
+/// directories. With `include_junk`, files such as `.DS_Store`, `Thumbs.db`,
+/// `ehthumbs.db` and `desktop.ini` do not count as contents either.
+///
+/// # Arguments
+///
+/// * `base_path` - The directory to search; it is never reported itself
+/// * `include_junk` - Whether junk files count as empty (default false)
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `operations` - The operation registry managed by Tauri
+/// * `on_progress` - Channel to send progress events
+///
+/// # Returns
+///
+/// * `Ok(EmptyDirReport)` - The empty directories, deepest first
+/// * `Err(String)` - If the base path is not a directory or the search is cancelled
+#[tauri::command]
+pub async fn find_empty_dirs(
+    base_path: String,
+    include_junk: Option<bool>,
+    operation_id: Option<u64>,
+    operations: State<'_, OperationRegistry>,
+    on_progress: Channel<EmptyDirProgress>,
+) -> Result<EmptyDirReport, String> {
+    let operation = operations.begin(operation_id);
+
+    // Run the heavy work in a blocking thread to keep the main thread responsive
+    tokio::task::spawn_blocking(move || {
+        run_find_empty_dirs(
+            Path::new(&base_path),
+            include_junk.unwrap_or(false),
+            operation.token(),
+            |event| {
+                let _ = on_progress.send(event);
+            },
+        )
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Deletes empty directories found by `find_empty_dirs`.
+///
+/// Each directory is removed with its empty subdirectories, deepest first,
+/// after checking again that it is still empty. Like `batch_delete`, only
+/// directories inside the approved root and not protected are removed.
+///
+/// When started with an `operation_id`, the cleanup can be stopped with
+/// `cancel_operation`; directories not yet reached are left in place.
+///
+/// # Arguments
+///
+/// * `dirs` - The directories to delete
+/// * `include_junk` - Whether junk files are removed along with the directories
+///   (default false); without it, a directory holding junk is not empty
+/// * `root` - Root from `choose_operation_root`; paths outside it are refused
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `protected_paths` - The protected-path settings managed by Tauri
+/// * `roots` - The approved roots managed by Tauri
+/// * `operations` - The operation registry managed by Tauri
+/// * `on_progress` - Channel to send progress events
+///
+/// # Returns
+///
+/// * `Ok(EmptyDirCleanup)` - The removed directories and junk files, and failures
+/// * `Err(String)` - If `root` is not an approved root
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn delete_empty_dirs(
+    dirs: Vec<String>,
+    include_junk: Option<bool>,
+    root: String,
+    operation_id: Option<u64>,
+    protected_paths: State<'_, ProtectedPaths>,
+    roots: State<'_, ApprovedRoots>,
+    operations: State<'_, OperationRegistry>,
+    on_progress: Channel<DeleteProgress>,
+) -> Result<EmptyDirCleanup, String> {
+    let policy = protected_paths.policy().within_root(roots.resolve(&root)?);
+    let operation = operations.begin(operation_id);
+
+    // Run the heavy work in a blocking thread to keep the main thread responsive
+    tokio::task::spawn_blocking(move || {
+        let total = dirs.len();
+        let _ = on_progress.send(DeleteProgress::Started { total_files: total });
+
+        let mut processed = 0;
+        let result = run_delete_empty_dirs(
+            dirs,
+            include_junk.unwrap_or(false),
+            &policy,
+            operation.token(),
+            |index, path| {
+                processed = index + 1;
+                let _ = on_progress.send(DeleteProgress::Progress {
+                    current: index + 1,
+                    total,
+                    current_path: path.to_string(),
+                });
+            },
+        );
+
+        let failed = result.failed.len();
+        let successful = processed - failed;
+
+        if processed < total {
+            let _ = on_progress.send(DeleteProgress::Cancelled {
+                successful,
+                failed,
+                total,
+            });
+        } else {
+            let _ = on_progress.send(DeleteProgress::Completed { successful, failed });
+        }
+
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Finds the empty directories below a base directory, without progress.
+    fn find(base_path: &Path, include_junk: bool) -> Vec<String> {
+        run_find_empty_dirs(
+            base_path,
+            include_junk,
+            &CancellationToken::default(),
+            |_| {},
+        )
+        .unwrap()
+        .dirs
+        .into_iter()
+        .map(|dir| dir.path)
+        .collect()
+    }
+
+    /// Converts a path to the string form used in results.
+    fn path_string(path: &Path) -> String {
+        path.to_string_lossy().to_string()
+    }
+
+    /// Tests directories holding only empty directories are found, deepest first.
+    #[test]
+    fn test_find_nested_empty_dirs() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let nested = dir.path().join("a").join("b").join("c");
+        let kept = dir.path().join("kept");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(kept.join("empty")).unwrap();
+        fs::write(kept.join("file.txt"), "contents").unwrap();
+
+        let mut found = find(dir.path(), false);
+        let kept_empty = found
+            .iter()
+            .position(|path| *path == path_string(&kept.join("empty")))
+            .map(|index| found.remove(index));
+
+        assert!(kept_empty.is_some());
+        assert_eq!(
+            found,
+            vec![
+                path_string(&nested),
+                path_string(&dir.path().join("a").join("b")),
+                path_string(&dir.path().join("a")),
+            ]
+        );
+    }
+
+    /// Tests junk files only count as empty when asked to.
+    #[test]
+    fn test_find_empty_dirs_with_junk() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let photos = dir.path().join("photos");
+        fs::create_dir(&photos).unwrap();
+        fs::write(photos.join(".DS_Store"), "junk").unwrap();
+        fs::write(photos.join("thumbs.db"), "junk").unwrap();
+
+        assert!(find(dir.path(), false).is_empty());
+
+        let report =
+            run_find_empty_dirs(dir.path(), true, &CancellationToken::default(), |_| {}).unwrap();
+        assert_eq!(report.dirs.len(), 1);
+        assert_eq!(report.dirs[0].path, path_string(&photos));
+        assert_eq!(report.dirs[0].junk_files.len(), 2);
+    }
+
+    /// Tests a cancelled search reports how far it got.
+    #[test]
+    fn test_find_empty_dirs_cancelled() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        fs::create_dir(dir.path().join("empty")).unwrap();
+        let cancel = CancellationToken::default();
+        cancel.cancel();
+        let mut events = Vec::new();
+
+        let result = run_find_empty_dirs(dir.path(), false, &cancel, |event| events.push(event));
+
+        assert_eq!(result, Err(CANCELLED_ERROR.to_string()));
+        assert!(matches!(
+            events.last(),
+            Some(EmptyDirProgress::Cancelled { .. })
+        ));
+    }
+
+    /// Tests deleting removes empty subdirectories and junk before their parents.
+    #[test]
+    fn test_delete_empty_dirs() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let top = dir.path().join("a");
+        let nested = top.join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("Thumbs.db"), "junk").unwrap();
+
+        let result = run_delete_empty_dirs(
+            vec![path_string(&top), path_string(&nested)],
+            true,
+            &ProtectedPathPolicy::default(),
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert!(result.failed.is_empty());
+        assert_eq!(
+            result.deleted_dirs,
+            vec![path_string(&nested), path_string(&top)]
+        );
+        assert_eq!(
+            result.junk_files,
+            vec![path_string(&nested.join("Thumbs.db"))]
+        );
+        assert!(!top.exists());
+    }
+
+    /// Tests a directory that gained contents since it was found is left alone.
+    #[test]
+    fn test_delete_empty_dirs_no_longer_empty() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let top = dir.path().join("a");
+        let nested = top.join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("new.txt"), "contents").unwrap();
+
+        let result = run_delete_empty_dirs(
+            vec![path_string(&top)],
+            false,
+            &ProtectedPathPolicy::default(),
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.deleted_dirs.is_empty());
+        assert!(nested.join("new.txt").exists());
+    }
+
+    /// Tests the approved root itself and paths outside it are refused.
+    #[test]
+    fn test_delete_empty_dirs_outside_root() {
+        let root = tempdir().expect("Failed to create temp dir");
+        let outside = tempdir().expect("Failed to create temp dir");
+        let policy =
+            ProtectedPathPolicy::default().within_root(fs::canonicalize(root.path()).unwrap());
+
+        let result = run_delete_empty_dirs(
+            vec![path_string(root.path()), path_string(outside.path())],
+            false,
+            &policy,
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert_eq!(result.protected.len(), 2);
+        assert!(result
+            .protected
+            .iter()
+            .all(|(_, reason)| *reason == ProtectionReason::OutsideRoot));
+        assert!(root.path().exists());
+        assert!(outside.path().exists());
+    }
+}
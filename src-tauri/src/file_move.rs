@@ -0,0 +1,310 @@
+//! File moves that work across filesystems.
+//!
+//! `fs::rename` only works within a single filesystem; moving a file onto
+//! another mount fails with `EXDEV`. In that case the file is copied next to
+//! its destination, verified, given the source's permissions and timestamps,
+//! moved into place, and only then is the source deleted.
+
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, FileTimes, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Size of the buffer used when copying across filesystems
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Checks whether an error means the rename crossed a filesystem boundary.
+pub(crate) fn is_cross_device(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::CrossesDevices
+}
+
+/// Moves a file, copying it when the destination is on another filesystem.
+///
+/// # Arguments
+///
+/// * `from` - The file to move
+/// * `to` - The destination path
+/// * `on_bytes` - Called with (bytes copied, total bytes) while a
+///   cross-filesystem copy is in progress; never called for plain renames
+///
+/// # Returns
+///
+/// * `Ok(())` - The file now lives at `to` and `from` no longer exists
+/// * `Err(io::Error)` - The move failed; `from` is left untouched and no
+///   partial copy remains
+pub(crate) fn move_file<F>(from: &Path, to: &Path, on_bytes: F) -> io::Result<()>
+where
+    F: FnMut(u64, u64),
+{
+    match fs::rename(from, to) {
+        Err(e) if is_cross_device(&e) => copy_verify_delete(from, to, on_bytes),
+        result => result,
+    }
+}
+
+/// Builds the temporary name a cross-filesystem copy is written to.
+///
+/// The copy lives next to the destination, so the final step is an atomic
+/// rename on the destination filesystem.
+fn temp_copy_path(to: &Path) -> PathBuf {
+    let file_name = to
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_name = format!(".{}.{}.copying", file_name, std::process::id());
+
+    match to.parent() {
+        Some(parent) => parent.join(temp_name),
+        None => PathBuf::from(temp_name),
+    }
+}
+
+/// Computes the SHA-256 of a file's contents.
+fn hash_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Copies `from` into `temp_path`, returning the SHA-256 of the bytes read.
+fn copy_contents<F>(
+    from: &Path,
+    temp_path: &Path,
+    total: u64,
+    on_bytes: &mut F,
+) -> io::Result<Vec<u8>>
+where
+    F: FnMut(u64, u64),
+{
+    let mut source = File::open(from)?;
+    let mut dest = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp_path)?;
+
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    let mut copied = 0u64;
+
+    on_bytes(0, total);
+    loop {
+        let read = source.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        dest.write_all(&buffer[..read])?;
+        hasher.update(&buffer[..read]);
+        copied += read as u64;
+        on_bytes(copied, total);
+    }
+
+    dest.sync_all()?;
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Moves a file by copying it, verifying the copy, and deleting the source.
+///
+/// Permissions and access/modification times are copied from the source.
+/// Any failure before the source is deleted removes the partial copy.
+///
+/// # Arguments
+///
+/// * `from` - The file to move
+/// * `to` - The destination path
+/// * `on_bytes` - Called with (bytes copied, total bytes) after each chunk
+///
+/// # Returns
+///
+/// * `Ok(())` - The file was moved
+/// * `Err(io::Error)` - The move failed and `from` is untouched
+pub(crate) fn copy_verify_delete<F>(from: &Path, to: &Path, mut on_bytes: F) -> io::Result<()>
+where
+    F: FnMut(u64, u64),
+{
+    let metadata = fs::symlink_metadata(from)?;
+    if metadata.is_dir() {
+        return Err(io::Error::other(
+            "cannot move a directory across filesystems",
+        ));
+    }
+    if metadata.file_type().is_symlink() {
+        return Err(io::Error::other(
+            "cannot move a symbolic link across filesystems",
+        ));
+    }
+
+    let temp_path = temp_copy_path(to);
+    if temp_path.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("temporary path {} already exists", temp_path.display()),
+        ));
+    }
+
+    let mut copy = || -> io::Result<()> {
+        let source_hash = copy_contents(from, &temp_path, metadata.len(), &mut on_bytes)?;
+
+        let times = FileTimes::new()
+            .set_accessed(metadata.accessed()?)
+            .set_modified(metadata.modified()?);
+        File::options()
+            .write(true)
+            .open(&temp_path)?
+            .set_times(times)?;
+
+        if hash_file(&temp_path)? != source_hash {
+            return Err(io::Error::other("copy verification failed"));
+        }
+
+        fs::set_permissions(&temp_path, metadata.permissions())?;
+        fs::rename(&temp_path, to)
+    };
+
+    if let Err(e) = copy() {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    if let Err(e) = fs::remove_file(from) {
+        // Keep exactly one copy of the file
+        let _ = fs::remove_file(to);
+        return Err(io::Error::new(
+            e.kind(),
+            format!("copied but could not remove the source: {}", e),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    /// Tests a cross-filesystem move copies the contents and removes the source.
+    #[test]
+    fn test_copy_verify_delete_moves_file() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let from = dir.path().join("source.bin");
+        let to = dir.path().join("dest.bin");
+        let contents: Vec<u8> = (0..COPY_BUFFER_SIZE * 2 + 10).map(|i| i as u8).collect();
+        fs::write(&from, &contents).unwrap();
+
+        let mut progress = Vec::new();
+        copy_verify_delete(&from, &to, |copied, total| progress.push((copied, total))).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read(&to).unwrap(), contents);
+
+        let total = contents.len() as u64;
+        assert_eq!(progress.first(), Some(&(0, total)));
+        assert_eq!(progress.last(), Some(&(total, total)));
+        assert!(progress.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        // No temporary copy is left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    /// Tests permissions and modification time are preserved.
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_verify_delete_preserves_metadata() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let from = dir.path().join("script.sh");
+        let to = dir.path().join("moved.sh");
+        fs::write(&from, "#!/bin/sh").unwrap();
+
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        File::options()
+            .write(true)
+            .open(&from)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        fs::set_permissions(&from, fs::Permissions::from_mode(0o750)).unwrap();
+
+        copy_verify_delete(&from, &to, |_, _| {}).unwrap();
+
+        let metadata = fs::metadata(&to).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o750);
+        assert_eq!(metadata.modified().unwrap(), modified);
+    }
+
+    /// Tests failures leave the source untouched and no partial copy behind.
+    #[test]
+    fn test_copy_verify_delete_failure_cleans_up() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let from = dir.path().join("source.txt");
+        fs::write(&from, "data").unwrap();
+
+        // Destination directory does not exist
+        let to = dir.path().join("missing").join("dest.txt");
+        assert!(copy_verify_delete(&from, &to, |_, _| {}).is_err());
+        assert!(from.exists());
+
+        // Destination is an existing directory, so the final rename fails
+        let to = dir.path().join("occupied");
+        fs::create_dir(&to).unwrap();
+        fs::write(to.join("keep"), "x").unwrap();
+        assert!(copy_verify_delete(&from, &to, |_, _| {}).is_err());
+        assert!(from.exists());
+
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["occupied", "source.txt"]);
+    }
+
+    /// Tests directories are not copied across filesystems.
+    #[test]
+    fn test_copy_verify_delete_rejects_directories() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let from = dir.path().join("folder");
+        fs::create_dir(&from).unwrap();
+
+        let err = copy_verify_delete(&from, &dir.path().join("moved"), |_, _| {}).unwrap_err();
+        assert!(err.to_string().contains("directory"));
+        assert!(from.is_dir());
+    }
+
+    /// Tests EXDEV is recognised as a cross-filesystem error.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_is_cross_device() {
+        // EXDEV on Linux
+        assert!(is_cross_device(&io::Error::from_raw_os_error(18)));
+        assert!(!is_cross_device(&io::Error::from(io::ErrorKind::NotFound)));
+    }
+
+    /// Tests moves within a filesystem are plain renames without progress.
+    #[test]
+    fn test_move_file_same_filesystem() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let from = dir.path().join("a.txt");
+        let to = dir.path().join("b.txt");
+        fs::write(&from, "data").unwrap();
+
+        let mut calls = 0;
+        move_file(&from, &to, |_, _| calls += 1).unwrap();
+
+        assert_eq!(calls, 0);
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "data");
+    }
+}
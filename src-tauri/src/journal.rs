@@ -0,0 +1,691 @@
+//! Persistent undo journal for batch renames.
+//!
+//! Every batch executed by `batch_rename` is recorded in a JSON journal in the
+//! app data directory, together with enough file metadata to tell whether a
+//! renamed file has been touched since. Batches can later be reversed with
+//! `undo_last_rename` or `undo_rename_batch`, including after an app restart.
+
+use crate::operations::CancellationToken;
+use crate::rename::{execute_renames, is_same_file};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+/// File name of the journal inside the app data directory
+pub const JOURNAL_FILE_NAME: &str = "rename-journal.json";
+
+/// Maximum number of batches kept in the journal; older batches are dropped
+const MAX_BATCHES: usize = 100;
+
+/// Sequence number used to keep batch ids unique within one process;
+/// shared with the delete quarantine
+static BATCH_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+// ==================== Types ====================
+
+/// A single rename recorded in the journal
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// Path of the file before the rename
+    pub old_path: String,
+    /// Path of the file after the rename
+    pub new_path: String,
+    /// File size in bytes right after the rename
+    pub size: u64,
+    /// Modification time (milliseconds since the Unix epoch) right after the rename
+    pub modified_ms: Option<u64>,
+}
+
+/// A batch of renames recorded in the journal
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RenameBatch {
+    /// Unique identifier of the batch
+    pub id: String,
+    /// When the batch was executed (seconds since the Unix epoch)
+    pub timestamp: u64,
+    /// Renames that succeeded in this batch and have not been undone
+    pub entries: Vec<JournalEntry>,
+    /// Whether every entry of the batch has been undone
+    pub undone: bool,
+    /// The approved root the batch was restricted to
+    #[serde(default)]
+    pub root: Option<String>,
+}
+
+/// Result of undoing a rename batch
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UndoResult {
+    /// Identifier of the batch that was undone
+    pub batch_id: String,
+    /// Original paths that were restored
+    pub restored: Vec<String>,
+    /// Entries that were left in place (current path, reason)
+    pub skipped: Vec<(String, String)>,
+}
+
+/// On-disk layout of the journal file
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct JournalFile {
+    batches: Vec<RenameBatch>,
+}
+
+/// Handle to the rename journal, shared as Tauri managed state.
+///
+/// Reads and writes are serialized through an internal lock so concurrent
+/// commands never interleave journal updates.
+#[derive(Debug, Clone)]
+pub struct RenameJournal {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+// ==================== Journal ====================
+
+/// Returns the current time as milliseconds since the Unix epoch.
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Reads the modification time of a file as milliseconds since the Unix epoch.
+pub(crate) fn modified_ms(metadata: &fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+}
+
+/// Builds a batch id that is unique across processes and batches.
+///
+/// # Arguments
+///
+/// * `now` - The current time in milliseconds since the Unix epoch
+pub(crate) fn new_batch_id(now: u64) -> String {
+    format!(
+        "{:x}-{:x}-{}",
+        now,
+        std::process::id(),
+        BATCH_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+impl RenameJournal {
+    /// Creates a journal backed by the given file.
+    ///
+    /// The file and its parent directory are created on the first write.
+    pub fn new(path: PathBuf) -> Self {
+        RenameJournal {
+            path,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Loads the journal from disk, treating a missing file as empty.
+    fn load(&self) -> Result<JournalFile, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Failed to parse rename journal: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(JournalFile::default()),
+            Err(e) => Err(format!("Failed to read rename journal: {}", e)),
+        }
+    }
+
+    /// Writes the journal atomically by replacing it with a fully written copy.
+    fn save(&self, journal: &JournalFile) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create journal directory: {}", e))?;
+        }
+
+        let contents = serde_json::to_string_pretty(journal)
+            .map_err(|e| format!("Failed to serialize rename journal: {}", e))?;
+        let temp_path = self.path.with_extension("json.tmp");
+
+        fs::write(&temp_path, contents)
+            .and_then(|_| fs::rename(&temp_path, &self.path))
+            .map_err(|e| format!("Failed to write rename journal: {}", e))
+    }
+
+    /// Records a batch of completed renames.
+    ///
+    /// # Arguments
+    ///
+    /// * `renames` - The (old_path, new_path) pairs that were renamed successfully
+    /// * `root` - The approved root the batch was restricted to, if any
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(String))` - The id of the recorded batch
+    /// * `Ok(None)` - If there was nothing to record
+    /// * `Err(String)` - If the journal could not be read or written
+    pub fn record(
+        &self,
+        renames: &[(String, String)],
+        root: Option<&Path>,
+    ) -> Result<Option<String>, String> {
+        if renames.is_empty() {
+            return Ok(None);
+        }
+
+        let entries = renames
+            .iter()
+            .map(|(old_path, new_path)| {
+                let metadata = fs::symlink_metadata(new_path).ok();
+                JournalEntry {
+                    old_path: old_path.clone(),
+                    new_path: new_path.clone(),
+                    size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                    modified_ms: metadata.as_ref().and_then(modified_ms),
+                }
+            })
+            .collect();
+
+        let now = now_ms();
+        let batch = RenameBatch {
+            id: new_batch_id(now),
+            timestamp: now / 1000,
+            entries,
+            undone: false,
+            root: root.map(|root| root.to_string_lossy().to_string()),
+        };
+        let id = batch.id.clone();
+
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut journal = self.load()?;
+        journal.batches.push(batch);
+        if journal.batches.len() > MAX_BATCHES {
+            let excess = journal.batches.len() - MAX_BATCHES;
+            journal.batches.drain(..excess);
+        }
+        self.save(&journal)?;
+
+        Ok(Some(id))
+    }
+
+    /// Returns all recorded batches, oldest first.
+    pub fn batches(&self) -> Result<Vec<RenameBatch>, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        Ok(self.load()?.batches)
+    }
+
+    /// Reverses a recorded batch.
+    ///
+    /// Restored entries are dropped from the batch. Entries that were skipped
+    /// or failed stay in it, so undoing the batch again retries them; the
+    /// batch is only marked undone once every entry has been restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_id` - The batch to undo, or `None` for the most recent batch
+    ///   that has not been fully undone yet
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(UndoResult)` - Which entries were restored and which were skipped
+    /// * `Err(String)` - If the batch does not exist, was already undone, or the
+    ///   journal could not be read or written
+    pub fn undo(&self, batch_id: Option<&str>) -> Result<UndoResult, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut journal = self.load()?;
+
+        let index = match batch_id {
+            Some(id) => journal
+                .batches
+                .iter()
+                .position(|b| b.id == id)
+                .ok_or_else(|| format!("Rename batch not found: {}", id))?,
+            None => journal
+                .batches
+                .iter()
+                .rposition(|b| !b.undone)
+                .ok_or_else(|| "There is no rename to undo".to_string())?,
+        };
+
+        if journal.batches[index].undone {
+            return Err(format!(
+                "Rename batch {} has already been undone",
+                journal.batches[index].id
+            ));
+        }
+
+        let result = undo_batch(&journal.batches[index]);
+        let restored: HashSet<&str> = result.restored.iter().map(String::as_str).collect();
+        let batch = &mut journal.batches[index];
+        batch
+            .entries
+            .retain(|entry| !restored.contains(entry.old_path.as_str()));
+        batch.undone = batch.entries.is_empty();
+        self.save(&journal)?;
+
+        Ok(result)
+    }
+}
+
+// ==================== Undo ====================
+
+/// Checks whether the file at an entry's new path is still the one we renamed.
+///
+/// # Returns
+///
+/// * `None` - If the file is unchanged
+/// * `Some(String)` - The reason the entry cannot be reversed
+fn entry_changed_reason(entry: &JournalEntry) -> Option<String> {
+    let metadata = match fs::symlink_metadata(&entry.new_path) {
+        Ok(m) => m,
+        Err(_) => return Some("file no longer exists".to_string()),
+    };
+
+    if metadata.len() != entry.size || modified_ms(&metadata) != entry.modified_ms {
+        return Some("file has been modified since the rename".to_string());
+    }
+
+    None
+}
+
+/// Reverses the entries of a batch that can still be safely reversed.
+///
+/// Entries whose file has changed or disappeared are skipped, as are entries
+/// whose original path is now occupied by a file outside the batch. The
+/// remaining entries are renamed back through the same cycle-safe executor
+/// used by `batch_rename`.
+fn undo_batch(batch: &RenameBatch) -> UndoResult {
+    let mut skipped = Vec::new();
+    let mut candidates: Vec<&JournalEntry> = Vec::new();
+
+    for entry in &batch.entries {
+        match entry_changed_reason(entry) {
+            Some(reason) => skipped.push((entry.new_path.clone(), reason)),
+            None => candidates.push(entry),
+        }
+    }
+
+    // Dropping an entry can leave another entry's original path occupied,
+    // so repeat until the remaining set is stable
+    loop {
+        let sources: HashSet<&Path> = candidates
+            .iter()
+            .map(|e| Path::new(e.new_path.as_str()))
+            .collect();
+
+        let (keep, blocked): (Vec<&JournalEntry>, Vec<&JournalEntry>) =
+            candidates.iter().partition(|e| {
+                let original = Path::new(e.old_path.as_str());
+                sources.contains(original)
+                    || original.symlink_metadata().is_err()
+                    || is_same_file(Path::new(e.new_path.as_str()), original)
+            });
+
+        if blocked.is_empty() {
+            break;
+        }
+
+        for entry in blocked {
+            skipped.push((
+                entry.new_path.clone(),
+                format!("original path {} is now occupied", entry.old_path),
+            ));
+        }
+        candidates = keep;
+    }
+
+    let reversed: Vec<(String, String)> = candidates
+        .iter()
+        .map(|e| (e.new_path.clone(), e.old_path.clone()))
+        .collect();
+
+    let outcomes = execute_renames(&reversed, &CancellationToken::default(), |_| {});
+
+    let mut restored = Vec::new();
+    for ((current, original), outcome) in reversed.iter().zip(outcomes) {
+        match outcome {
+            Ok(_) => restored.push(original.clone()),
+            Err(e) => skipped.push((current.clone(), e)),
+        }
+    }
+
+    UndoResult {
+        batch_id: batch.id.clone(),
+        restored,
+        skipped,
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Lists the rename batches recorded in the journal.
+///
+/// # Returns
+///
+/// * `Ok(Vec<RenameBatch>)` - All recorded batches, oldest first
+/// * `Err(String)` - If the journal could not be read
+#[tauri::command]
+pub fn list_rename_batches(journal: State<'_, RenameJournal>) -> Result<Vec<RenameBatch>, String> {
+    journal.batches()
+}
+
+/// Undoes the most recent rename batch that has not been fully undone yet.
+///
+/// Entries whose file has changed since the rename, or whose original path
+/// is now taken by another file, are skipped and reported. They stay in the
+/// batch, so undoing it again retries them.
+///
+/// # Returns
+///
+/// * `Ok(UndoResult)` - Restored paths and skipped entries
+/// * `Err(String)` - If there is nothing to undo or the journal is unavailable
+#[tauri::command]
+pub fn undo_last_rename(journal: State<'_, RenameJournal>) -> Result<UndoResult, String> {
+    journal.undo(None)
+}
+
+/// Undoes a specific rename batch.
+///
+/// # Arguments
+///
+/// * `batch_id` - The id of the batch, as returned by `list_rename_batches`
+///
+/// # Returns
+///
+/// * `Ok(UndoResult)` - Restored paths and skipped entries
+/// * `Err(String)` - If the batch does not exist, was already undone, or the
+///   journal is unavailable
+#[tauri::command]
+pub fn undo_rename_batch(
+    batch_id: String,
+    journal: State<'_, RenameJournal>,
+) -> Result<UndoResult, String> {
+    journal.undo(Some(&batch_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    /// Helper to create a journal inside a temp directory.
+    fn journal_in(dir: &Path) -> RenameJournal {
+        RenameJournal::new(dir.join("data").join(JOURNAL_FILE_NAME))
+    }
+
+    /// Helper to create a file with the given content.
+    fn write_file(path: &Path, content: &str) {
+        File::create(path)
+            .unwrap()
+            .write_all(content.as_bytes())
+            .unwrap();
+    }
+
+    /// Helper to rename files and record the batch like `batch_rename` does.
+    fn rename_and_record(journal: &RenameJournal, pairs: &[(String, String)]) -> String {
+        for (old, new) in pairs {
+            fs::rename(old, new).unwrap();
+        }
+        journal.record(pairs, None).unwrap().unwrap()
+    }
+
+    /// Helper to build a rename pair inside a directory.
+    fn pair(dir: &Path, old: &str, new: &str) -> (String, String) {
+        (
+            dir.join(old).to_string_lossy().to_string(),
+            dir.join(new).to_string_lossy().to_string(),
+        )
+    }
+
+    /// Tests that recording creates the journal and assigns unique ids.
+    #[test]
+    fn test_record_creates_journal() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+        write_file(&dir.path().join("a"), "a");
+        write_file(&dir.path().join("c"), "c");
+
+        let first = rename_and_record(&journal, &[pair(dir.path(), "a", "b")]);
+        let second = rename_and_record(&journal, &[pair(dir.path(), "c", "d")]);
+
+        assert_ne!(first, second);
+        assert!(dir.path().join("data").join(JOURNAL_FILE_NAME).exists());
+
+        let batches = journal.batches().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].entries[0].size, 1);
+        assert!(batches[0].entries[0].modified_ms.is_some());
+        assert!(!batches[0].undone);
+    }
+
+    /// Tests that an empty batch is not recorded.
+    #[test]
+    fn test_record_empty_batch() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+
+        assert_eq!(journal.record(&[], None).unwrap(), None);
+        assert!(journal.batches().unwrap().is_empty());
+    }
+
+    /// Tests that the journal survives being reopened (app restart).
+    #[test]
+    fn test_journal_persists_across_instances() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        write_file(&dir.path().join("a"), "a");
+
+        let id = rename_and_record(&journal_in(dir.path()), &[pair(dir.path(), "a", "b")]);
+
+        let reopened = journal_in(dir.path());
+        let batches = reopened.batches().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].id, id);
+
+        let result = reopened.undo(None).unwrap();
+        assert_eq!(result.restored.len(), 1);
+        assert!(dir.path().join("a").exists());
+    }
+
+    /// Tests undoing the last batch restores original names.
+    #[test]
+    fn test_undo_last_rename() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+        write_file(&dir.path().join("one"), "1");
+        write_file(&dir.path().join("two"), "2");
+
+        rename_and_record(&journal, &[pair(dir.path(), "one", "uno")]);
+        rename_and_record(&journal, &[pair(dir.path(), "two", "dos")]);
+
+        let result = journal.undo(None).unwrap();
+        assert_eq!(result.restored.len(), 1);
+        assert!(result.skipped.is_empty());
+        assert!(dir.path().join("two").exists());
+        // The earlier batch is untouched
+        assert!(dir.path().join("uno").exists());
+
+        // The next undo picks the earlier batch
+        journal.undo(None).unwrap();
+        assert!(dir.path().join("one").exists());
+
+        assert!(journal.undo(None).is_err());
+    }
+
+    /// Tests undoing a batch by id and refusing to undo it twice.
+    #[test]
+    fn test_undo_rename_batch_by_id() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+        write_file(&dir.path().join("a"), "a");
+        write_file(&dir.path().join("b"), "b");
+
+        let first = rename_and_record(&journal, &[pair(dir.path(), "a", "a2")]);
+        rename_and_record(&journal, &[pair(dir.path(), "b", "b2")]);
+
+        let result = journal.undo(Some(&first)).unwrap();
+        assert_eq!(result.batch_id, first);
+        assert!(dir.path().join("a").exists());
+        assert!(dir.path().join("b2").exists());
+
+        let error = journal.undo(Some(&first)).unwrap_err();
+        assert!(error.contains("already been undone"));
+        assert!(journal.undo(Some("missing")).is_err());
+    }
+
+    /// Tests undoing a swap goes back through the cycle-safe executor.
+    #[test]
+    fn test_undo_swap() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+        write_file(&dir.path().join("a"), "a");
+        write_file(&dir.path().join("b"), "b");
+
+        let pairs = vec![pair(dir.path(), "a", "b"), pair(dir.path(), "b", "a")];
+        assert!(
+            execute_renames(&pairs, &CancellationToken::default(), |_| {})
+                .iter()
+                .all(|o| o.is_ok())
+        );
+        journal.record(&pairs, None).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("a")).unwrap(), "b");
+
+        let result = journal.undo(None).unwrap();
+        assert_eq!(result.restored.len(), 2);
+        assert_eq!(fs::read_to_string(dir.path().join("a")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.path().join("b")).unwrap(), "b");
+    }
+
+    /// Tests undoing a case-only rename on a simulated case-insensitive filesystem.
+    #[cfg(unix)]
+    #[test]
+    fn test_undo_case_only_rename() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+        write_file(&dir.path().join("photo.jpg"), "pixels");
+
+        let pairs = vec![pair(dir.path(), "Photo.JPG", "photo.jpg")];
+        journal.record(&pairs, None).unwrap();
+
+        // The original name resolves to the renamed file, as on vfat or exFAT
+        fs::hard_link(dir.path().join("photo.jpg"), dir.path().join("Photo.JPG")).unwrap();
+
+        let result = journal.undo(None).unwrap();
+        assert_eq!(result.restored.len(), 1, "{:?}", result.skipped);
+        assert!(dir.path().join("Photo.JPG").exists());
+        assert!(!dir.path().join("photo.jpg").exists());
+    }
+
+    /// Tests that changed, missing and blocked entries are skipped and reported.
+    #[test]
+    fn test_undo_skips_changed_entries() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+        for name in ["kept", "edited", "gone", "blocked"] {
+            write_file(&dir.path().join(name), name);
+        }
+
+        rename_and_record(
+            &journal,
+            &[
+                pair(dir.path(), "kept", "kept2"),
+                pair(dir.path(), "edited", "edited2"),
+                pair(dir.path(), "gone", "gone2"),
+                pair(dir.path(), "blocked", "blocked2"),
+            ],
+        );
+
+        write_file(&dir.path().join("edited2"), "changed content");
+        fs::remove_file(dir.path().join("gone2")).unwrap();
+        write_file(&dir.path().join("blocked"), "a new file");
+
+        let result = journal.undo(None).unwrap();
+        assert_eq!(result.restored.len(), 1);
+        assert!(result.restored[0].ends_with("kept"));
+        assert_eq!(result.skipped.len(), 3);
+        assert!(result
+            .skipped
+            .iter()
+            .any(|(_, reason)| reason.contains("modified")));
+        assert!(result
+            .skipped
+            .iter()
+            .any(|(_, reason)| reason.contains("no longer exists")));
+        assert!(result
+            .skipped
+            .iter()
+            .any(|(_, reason)| reason.contains("occupied")));
+
+        // Skipped files are left where they are
+        assert!(dir.path().join("edited2").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("blocked")).unwrap(),
+            "a new file"
+        );
+    }
+
+    /// Tests a batch with a skipped entry stays undoable and can be retried.
+    #[test]
+    fn test_undo_retries_skipped_entries() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+        write_file(&dir.path().join("a"), "a");
+        write_file(&dir.path().join("b"), "b");
+        let batch_id = rename_and_record(
+            &journal,
+            &[pair(dir.path(), "a", "a2"), pair(dir.path(), "b", "b2")],
+        );
+
+        // Something else takes b's original name
+        write_file(&dir.path().join("b"), "a new file");
+        let first = journal.undo(None).unwrap();
+        assert_eq!(first.restored.len(), 1);
+        assert_eq!(first.skipped.len(), 1);
+
+        let batch = &journal.batches().unwrap()[0];
+        assert!(!batch.undone);
+        assert_eq!(batch.entries.len(), 1);
+        assert!(batch.entries[0].old_path.ends_with("b"));
+
+        // Once the name is free again, the skipped entry is restored
+        fs::remove_file(dir.path().join("b")).unwrap();
+        let retry = journal.undo(Some(&batch_id)).unwrap();
+        assert!(retry.restored[0].ends_with("b"));
+        assert!(retry.skipped.is_empty());
+        assert_eq!(fs::read_to_string(dir.path().join("b")).unwrap(), "b");
+        assert!(journal.batches().unwrap()[0].undone);
+
+        let error = journal.undo(Some(&batch_id)).unwrap_err();
+        assert!(error.contains("already been undone"));
+    }
+
+    /// Tests that the journal keeps only the most recent batches.
+    #[test]
+    fn test_journal_is_capped() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let journal = journal_in(dir.path());
+        let pairs = vec![("/x".to_string(), "/y".to_string())];
+
+        for _ in 0..MAX_BATCHES + 5 {
+            journal.record(&pairs, None).unwrap();
+        }
+
+        assert_eq!(journal.batches().unwrap().len(), MAX_BATCHES);
+    }
+
+    /// Tests UndoResult serialization uses the Rust field names.
+    #[test]
+    fn test_undo_result_serialization() {
+        let result = UndoResult {
+            batch_id: "abc".to_string(),
+            restored: vec!["/a".to_string()],
+            skipped: vec![("/b".to_string(), "reason".to_string())],
+        };
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains("\"batch_id\":\"abc\""));
+        assert!(json.contains("\"skipped\":[[\"/b\",\"reason\"]]"));
+    }
+}
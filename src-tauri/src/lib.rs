@@ -1,18 +1,72 @@
 //! # Simple Tools Library
 //!
 //! Backend library for the Simple Tools Tauri application.
-//! Provides file system operations for batch renaming, directory listing, and file removal.
+//! Provides file system operations for batch renaming (with name previews, rename
+//! pipelines, metadata templates and an undo journal), directory listing, and
+//! file removal (permanent, to the trash, or to a restorable quarantine) guarded by
+//! a protected-path policy. Remover searches match names and paths by pattern or
+//! compound filter expression, or search file contents. A duplicate finder groups
+//! files with identical contents, whose extra copies can be replaced with hard
+//! links or reflinks. Empty directories can be found and removed. Deletes and
+//! renames are restricted to a root folder the user picked.
+//! Long-running progress commands can be cancelled.
 
+mod approved_roots;
+mod content_search;
+mod dedupe;
+mod duplicates;
+mod empty_dirs;
+mod file_move;
+mod journal;
+mod media_tags;
+mod operations;
+mod protected_paths;
+mod quarantine;
 mod remove;
 mod rename;
+mod rename_pipeline;
+mod rename_rules;
+mod rename_template;
+mod search_expression;
+mod search_filters;
+mod trash;
+
+use tauri::Manager;
 
 // Re-export types for external use
-pub use remove::{DeleteProgress, DeleteResult, FileMatchResult, PatternType, SearchProgress};
-pub use rename::{ListProgress, RenameProgress};
+pub use approved_roots::ApprovedRoots;
+pub use content_search::{ContentHit, ContentSearchOptions};
+pub use dedupe::{DedupeGroup, DedupeProgress, DedupeResult, LinkKind, LinkMode};
+pub use duplicates::{
+    DuplicateFile, DuplicateGroup, DuplicateProgress, DuplicateReport, HashStage, KeepStrategy,
+};
+pub use empty_dirs::{EmptyDir, EmptyDirCleanup, EmptyDirProgress, EmptyDirReport};
+pub use journal::{JournalEntry, RenameBatch, RenameJournal, UndoResult};
+pub use operations::{CancellationToken, OperationRegistry};
+pub use protected_paths::{ProtectedPathPolicy, ProtectedPaths, ProtectionReason};
+pub use quarantine::{PurgeResult, Quarantine, QuarantineBatch, QuarantineEntry, RestoreResult};
+pub use remove::{
+    DeleteMode, DeleteProgress, DeleteResult, FileMatchResult, MatchTarget, PatternType,
+    SearchProgress,
+};
+pub use rename::{
+    BatchRenameResult, ListProgress, OverwritePolicy, RenameProgress, RenameValidation,
+};
+pub use rename_pipeline::{CaseMode, PipelinePreview, PipelineStep, RenamePipeline, StepPreview};
+pub use rename_rules::{
+    DiffSegment, DiffSegmentType, MatchSegment, NumberingInfo, NumberingOptions, NumberingPosition,
+    RenameOptions, RenamePreview,
+};
+pub use rename_template::{RenameTemplate, TemplatePreview};
+pub use search_expression::{EntryType, FilterExpression, SearchExpression};
+pub use search_filters::{
+    parse_date, parse_duration, parse_size, SearchFilter, SearchFilters, TimeBound, TimeKind,
+    TimeRange,
+};
 
 /// Initializes and runs the Tauri application.
 ///
-/// Sets up the Tauri builder with required plugins and command handlers,
+/// Sets up the Tauri builder with required plugins, managed state and command handlers,
 /// then starts the application event loop.
 ///
 /// # Panics
@@ -23,15 +77,49 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(OperationRegistry::default())
+        .manage(ApprovedRoots::default())
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            app.manage(RenameJournal::new(
+                data_dir.join(journal::JOURNAL_FILE_NAME),
+            ));
+            app.manage(Quarantine::new(
+                data_dir.join(quarantine::QUARANTINE_DIR_NAME),
+            ));
+            app.manage(ProtectedPaths::new(data_dir));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             rename::batch_rename,
             rename::batch_rename_with_progress,
+            rename::validate_rename_plan,
+            rename_rules::preview_renames,
+            rename_pipeline::preview_pipeline,
+            rename_pipeline::rename_with_pipeline,
+            rename_template::preview_template,
+            journal::list_rename_batches,
+            journal::undo_last_rename,
+            journal::undo_rename_batch,
             rename::list_files_recursively,
             rename::list_files_with_progress,
             remove::search_files_by_pattern,
             remove::search_files_with_progress,
+            content_search::search_file_contents,
+            duplicates::find_duplicates,
+            dedupe::dedupe_with_links,
+            empty_dirs::find_empty_dirs,
+            empty_dirs::delete_empty_dirs,
             remove::batch_delete,
             remove::batch_delete_with_progress,
+            quarantine::list_quarantine,
+            quarantine::restore_deleted,
+            quarantine::purge_quarantine,
+            protected_paths::get_protected_paths,
+            protected_paths::set_protected_paths,
+            approved_roots::choose_operation_root,
+            operations::register_operation,
+            operations::cancel_operation,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,271 @@
+//! Media metadata readers.
+//!
+//! Reads EXIF data from photos (JPEG, TIFF, HEIC, PNG, WebP) and tags from
+//! audio files (ID3, Vorbis comments, MP4 atoms and the other formats
+//! supported by `lofty`) for use as rename template tokens.
+
+use chrono::NaiveDateTime;
+use exif::{In, Reader, Tag, Value};
+use lofty::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Format of EXIF date/time values
+const EXIF_DATE_FORMAT: &str = "%Y:%m:%d %H:%M:%S";
+
+// ==================== EXIF ====================
+
+/// EXIF fields used by rename templates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ExifInfo {
+    /// When the photo was taken (DateTimeOriginal)
+    pub date_taken: Option<NaiveDateTime>,
+    /// Camera manufacturer
+    pub make: Option<String>,
+    /// Camera model
+    pub model: Option<String>,
+    /// Whether the photo has GPS coordinates
+    pub has_gps: bool,
+}
+
+/// Returns the first string of an ASCII EXIF field, trimmed.
+fn exif_string(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => values
+            .first()
+            .map(|value| {
+                String::from_utf8_lossy(value)
+                    .trim_matches(['\0', ' '])
+                    .to_string()
+            })
+            .filter(|value| !value.is_empty()),
+        _ => None,
+    }
+}
+
+/// Reads the EXIF fields of a photo.
+///
+/// # Returns
+///
+/// * `Ok(ExifInfo)` - The fields found; missing fields are `None`
+/// * `Err(String)` - If the file cannot be read or has no EXIF data
+pub(crate) fn read_exif(path: &Path) -> Result<ExifInfo, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let exif = Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .map_err(|e| format!("No EXIF data: {}", e))?;
+
+    let date_taken = exif_string(&exif, Tag::DateTimeOriginal)
+        .and_then(|value| NaiveDateTime::parse_from_str(&value, EXIF_DATE_FORMAT).ok());
+
+    Ok(ExifInfo {
+        date_taken,
+        make: exif_string(&exif, Tag::Make),
+        model: exif_string(&exif, Tag::Model),
+        has_gps: exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_some(),
+    })
+}
+
+// ==================== Audio Tags ====================
+
+/// Audio tag fields used by rename templates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct AudioTags {
+    /// Track title
+    pub title: Option<String>,
+    /// Track artist
+    pub artist: Option<String>,
+    /// Album title
+    pub album: Option<String>,
+    /// Album artist
+    pub album_artist: Option<String>,
+    /// Genre
+    pub genre: Option<String>,
+    /// Track number
+    pub track: Option<u32>,
+    /// Disc number
+    pub disc: Option<u32>,
+    /// Release year
+    pub year: Option<u32>,
+}
+
+/// Reads the tags of an audio file.
+///
+/// Uses the file's primary tag format, falling back to any other tag found.
+///
+/// # Returns
+///
+/// * `Ok(AudioTags)` - The fields found; missing fields are `None`
+/// * `Err(String)` - If the file is not a supported audio file or has no tags
+pub(crate) fn read_audio_tags(path: &Path) -> Result<AudioTags, String> {
+    let tagged_file =
+        lofty::read_from_path(path).map_err(|e| format!("Not a readable audio file: {}", e))?;
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag())
+        .ok_or_else(|| "No audio tags".to_string())?;
+
+    let text = |value: Option<std::borrow::Cow<'_, str>>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    Ok(AudioTags {
+        title: text(tag.title()),
+        artist: text(tag.artist()),
+        album: text(tag.album()),
+        album_artist: text(tag.get_string(&ItemKey::AlbumArtist).map(Into::into)),
+        genre: text(tag.genre()),
+        track: tag.track(),
+        disc: tag.disk(),
+        year: tag.year(),
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod test_files {
+    //! Builders for small tagged media files used in tests.
+
+    use exif::experimental::Writer;
+    use exif::{Field, In, Tag, Value};
+    use std::io::Cursor;
+
+    /// Builds a JPEG containing only an EXIF segment with the given ASCII fields.
+    pub fn jpeg_with_exif(fields: &[(Tag, &str)]) -> Vec<u8> {
+        let fields: Vec<Field> = fields
+            .iter()
+            .map(|(tag, value)| Field {
+                tag: *tag,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![value.as_bytes().to_vec()]),
+            })
+            .collect();
+
+        let mut writer = Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&tiff);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    }
+
+    /// Builds a FLAC stream header with Vorbis comments (`KEY=value`) and no audio frames.
+    pub fn flac_with_comments(comments: &[&str]) -> Vec<u8> {
+        let mut flac = b"fLaC".to_vec();
+
+        // STREAMINFO: 4096-sample blocks, 44.1 kHz, stereo, 16-bit, no samples
+        flac.extend_from_slice(&[0x00, 0x00, 0x00, 0x22]);
+        flac.extend_from_slice(&[0x10, 0x00, 0x10, 0x00, 0, 0, 0, 0, 0, 0]);
+        flac.extend_from_slice(&[0x0A, 0xC4, 0x42, 0xF0, 0, 0, 0, 0]);
+        flac.extend_from_slice(&[0; 16]);
+
+        let vendor = b"test";
+        let mut block = (vendor.len() as u32).to_le_bytes().to_vec();
+        block.extend_from_slice(vendor);
+        block.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+        for comment in comments {
+            block.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            block.extend_from_slice(comment.as_bytes());
+        }
+
+        // VORBIS_COMMENT, marked as the last metadata block
+        flac.push(0x84);
+        flac.extend_from_slice(&(block.len() as u32).to_be_bytes()[1..]);
+        flac.extend_from_slice(&block);
+        flac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_files::*;
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// Tests EXIF fields are read from a JPEG.
+    #[test]
+    fn test_read_exif_from_jpeg() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        fs::write(
+            &path,
+            jpeg_with_exif(&[
+                (Tag::Make, "Canon"),
+                (Tag::Model, "Canon EOS R6"),
+                (Tag::DateTimeOriginal, "2024:01:31 10:20:30"),
+            ]),
+        )
+        .unwrap();
+
+        let info = read_exif(&path).unwrap();
+        assert_eq!(info.make.as_deref(), Some("Canon"));
+        assert_eq!(info.model.as_deref(), Some("Canon EOS R6"));
+        assert_eq!(
+            info.date_taken
+                .unwrap()
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            "2024-01-31 10:20:30"
+        );
+        assert!(!info.has_gps);
+    }
+
+    /// Tests files without EXIF data are reported as errors.
+    #[test]
+    fn test_read_exif_missing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "not a photo").unwrap();
+
+        assert!(read_exif(&path).unwrap_err().contains("No EXIF data"));
+    }
+
+    /// Tests Vorbis comments are read from a FLAC file.
+    #[test]
+    fn test_read_audio_tags_from_flac() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("track.flac");
+        fs::write(
+            &path,
+            flac_with_comments(&[
+                "TITLE=Song",
+                "ARTIST=Band",
+                "ALBUM=Record",
+                "ALBUMARTIST=Various",
+                "TRACKNUMBER=7",
+                "DATE=1999",
+            ]),
+        )
+        .unwrap();
+
+        let tags = read_audio_tags(&path).unwrap();
+        assert_eq!(tags.title.as_deref(), Some("Song"));
+        assert_eq!(tags.artist.as_deref(), Some("Band"));
+        assert_eq!(tags.album.as_deref(), Some("Record"));
+        assert_eq!(tags.album_artist.as_deref(), Some("Various"));
+        assert_eq!(tags.track, Some(7));
+        assert_eq!(tags.year, Some(1999));
+        assert_eq!(tags.genre, None);
+    }
+
+    /// Tests non-audio files are reported as errors.
+    #[test]
+    fn test_read_audio_tags_not_audio() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "not audio").unwrap();
+
+        assert!(read_audio_tags(&path).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Registry of cancellable long-running operations.
+//!
+//! The frontend asks for an operation id with `register_operation`, passes it
+//! to one of the `*_with_progress` commands, and can stop that command at any
+//! time with `cancel_operation`. Commands check their cancellation token
+//! between files and stop cooperatively, reporting how far they got.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+/// Error returned by commands that were cancelled before producing a result
+pub(crate) const CANCELLED_ERROR: &str = "Operation cancelled";
+
+// ==================== Types ====================
+
+/// A flag shared between a running operation and the registry.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Requests cancellation.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Tracks the cancellation tokens of registered operations.
+///
+/// Managed as Tauri state; clones share the same registry.
+#[derive(Debug, Clone, Default)]
+pub struct OperationRegistry {
+    tokens: Arc<Mutex<HashMap<u64, CancellationToken>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl OperationRegistry {
+    /// Registers a new operation.
+    ///
+    /// # Returns
+    ///
+    /// The id of the operation, unique for the lifetime of the registry.
+    pub fn register(&self) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.lock().insert(id, CancellationToken::default());
+        id
+    }
+
+    /// Returns the token a command should check for the given operation.
+    ///
+    /// Commands started without an id, or with an id that was never
+    /// registered, get a token that is never cancelled. An id that was
+    /// registered but has since been cancelled or finished gets a token that
+    /// is already cancelled.
+    pub fn token(&self, id: Option<u64>) -> CancellationToken {
+        let Some(id) = id else {
+            return CancellationToken::default();
+        };
+        if let Some(token) = self.lock().get(&id) {
+            return token.clone();
+        }
+        let token = CancellationToken::default();
+        if id <= self.next_id.load(Ordering::Relaxed) {
+            token.cancel();
+        }
+        token
+    }
+
+    /// Starts tracking a command run under the given operation id.
+    ///
+    /// The operation is forgotten when the returned guard is dropped, so it
+    /// can no longer be cancelled once the command has returned.
+    pub fn begin(&self, id: Option<u64>) -> ActiveOperation {
+        ActiveOperation {
+            registry: self.clone(),
+            id,
+            token: self.token(id),
+        }
+    }
+
+    /// Requests cancellation of an operation and forgets it.
+    ///
+    /// A command already running under the id keeps its own copy of the
+    /// token, so it still sees the cancellation. Ids that were registered but
+    /// never used by a command are removed here rather than left behind.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the operation was registered and not yet finished or
+    /// cancelled.
+    pub fn cancel(&self, id: u64) -> bool {
+        match self.lock().remove(&id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of operations currently tracked.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Forgets an operation once its command has returned.
+    pub fn finish(&self, id: u64) {
+        self.lock().remove(&id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, CancellationToken>> {
+        // A panic while holding the lock cannot leave the map inconsistent
+        self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A running operation; unregisters itself when dropped.
+#[derive(Debug)]
+pub struct ActiveOperation {
+    registry: OperationRegistry,
+    id: Option<u64>,
+    token: CancellationToken,
+}
+
+impl ActiveOperation {
+    /// The token to check for cancellation.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl Drop for ActiveOperation {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.registry.finish(id);
+        }
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Registers a cancellable operation and returns its id.
+///
+/// Pass the id as `operationId` to a `*_with_progress` command to be able to
+/// cancel it with `cancel_operation`.
+///
+/// # Returns
+///
+/// The new operation id.
+#[tauri::command]
+pub fn register_operation(operations: State<'_, OperationRegistry>) -> u64 {
+    operations.register()
+}
+
+/// Cancels a running operation.
+///
+/// Cancellation is cooperative: the command stops at its next check, sends a
+/// `cancelled` progress event, and returns.
+///
+/// # Arguments
+///
+/// * `operation_id` - The id returned by `register_operation`
+/// * `operations` - The operation registry managed by Tauri
+///
+/// # Returns
+///
+/// * `Ok(())` - Cancellation was requested
+/// * `Err(String)` - If the id is unknown, already cancelled, or its command
+///   has returned
+#[tauri::command]
+pub fn cancel_operation(
+    operation_id: u64,
+    operations: State<'_, OperationRegistry>,
+) -> Result<(), String> {
+    if operations.cancel(operation_id) {
+        Ok(())
+    } else {
+        Err(format!("No running operation with id {}", operation_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests ids are unique and tokens start uncancelled.
+    #[test]
+    fn test_register_operation() {
+        let registry = OperationRegistry::default();
+        let first = registry.register();
+        let second = registry.register();
+
+        assert_ne!(first, second);
+        assert!(!registry.token(Some(first)).is_cancelled());
+    }
+
+    /// Tests cancelling one operation leaves others running.
+    #[test]
+    fn test_cancel_operation() {
+        let registry = OperationRegistry::default();
+        let first = registry.register();
+        let second = registry.register();
+        let token = registry.token(Some(first));
+
+        assert!(registry.cancel(first));
+
+        assert!(token.is_cancelled());
+        assert!(!registry.token(Some(second)).is_cancelled());
+    }
+
+    /// Tests finished and unknown operations cannot be cancelled.
+    #[test]
+    fn test_cancel_unknown_operation() {
+        let registry = OperationRegistry::default();
+        let id = registry.register();
+        registry.finish(id);
+
+        assert!(!registry.cancel(id));
+        assert!(!registry.cancel(999));
+    }
+
+    /// Tests commands without an operation id get a token that is never cancelled.
+    #[test]
+    fn test_token_without_operation() {
+        let registry = OperationRegistry::default();
+        assert!(!registry.token(None).is_cancelled());
+        assert!(!registry.token(Some(42)).is_cancelled());
+    }
+
+    /// Tests an active operation shares the registered token and unregisters when dropped.
+    #[test]
+    fn test_active_operation_finishes_on_drop() {
+        let registry = OperationRegistry::default();
+        let id = registry.register();
+
+        let operation = registry.begin(Some(id));
+        assert!(registry.cancel(id));
+        assert!(operation.is_cancelled());
+
+        drop(operation);
+        assert!(!registry.cancel(id));
+        assert_eq!(registry.len(), 0);
+    }
+
+    /// Tests cancelling an id that no command has begun removes it, and a
+    /// command begun later with that id starts out cancelled.
+    #[test]
+    fn test_cancel_before_begin() {
+        let registry = OperationRegistry::default();
+        let id = registry.register();
+
+        assert!(registry.cancel(id));
+        assert_eq!(registry.len(), 0);
+        assert!(!registry.cancel(id));
+
+        let operation = registry.begin(Some(id));
+        assert!(operation.is_cancelled());
+        drop(operation);
+        assert_eq!(registry.len(), 0);
+    }
+
+    /// Tests clones of the registry share operations.
+    #[test]
+    fn test_registry_clones_share_state() {
+        let registry = OperationRegistry::default();
+        let clone = registry.clone();
+        let id = registry.register();
+
+        assert!(clone.cancel(id));
+        assert!(registry.token(Some(id)).is_cancelled());
+    }
+}
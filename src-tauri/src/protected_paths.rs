@@ -0,0 +1,591 @@
+//! Protected-path policy for deletes.
+//!
+//! `batch_delete` refuses to touch system directories, home directory roots,
+//! mount points, the app's own data directory and any paths the user has
+//! marked as protected. Paths are checked after resolving `..` and symbolic
+//! links, so a link or a relative path cannot be used to reach a protected
+//! location. The policy is enforced in the backend regardless of what the
+//! frontend sends.
+
+use crate::approved_roots::is_within_root;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+/// File name of the user-defined protected paths inside the app data directory
+pub const PROTECTED_PATHS_FILE_NAME: &str = "protected-paths.json";
+
+/// System directories that may not be deleted, nor anything inside them
+#[cfg(unix)]
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/opt",
+    "/proc",
+    "/sbin",
+    "/sys",
+    "/usr",
+    "/var",
+    "/System",
+    "/Library",
+    "/Applications",
+];
+
+/// Directories whose direct children are home directories
+#[cfg(unix)]
+const HOME_ROOTS: &[&str] = &["/home", "/Users"];
+
+/// Home directories outside the home roots
+#[cfg(unix)]
+const HOME_DIRS: &[&str] = &["/root"];
+
+// ==================== Types ====================
+
+/// Why a path is protected from deletion
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ProtectionReason {
+    /// The path is, contains, or is inside a system directory
+    SystemDirectory,
+    /// The path is a home directory or contains home directories
+    HomeDirectory,
+    /// The path is the root of a mounted filesystem
+    MountPoint,
+    /// The path is, contains, or is inside the app's data directory
+    AppData,
+    /// The path is, contains, or is inside a user-defined protected path
+    UserDefined,
+    /// The path is not inside the root the operation was approved for
+    OutsideRoot,
+}
+
+impl ProtectionReason {
+    /// Describes the reason for error messages.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ProtectionReason::SystemDirectory => "system directory",
+            ProtectionReason::HomeDirectory => "home directory",
+            ProtectionReason::MountPoint => "mount point",
+            ProtectionReason::AppData => "application data",
+            ProtectionReason::UserDefined => "user-protected path",
+            ProtectionReason::OutsideRoot => "outside the approved root",
+        }
+    }
+}
+
+/// The set of locations deletes may not touch.
+#[derive(Debug, Clone)]
+pub struct ProtectedPathPolicy {
+    /// Directories that may not be deleted, nor anything inside or above them
+    trees: Vec<(PathBuf, ProtectionReason)>,
+    /// Directories that may not be deleted themselves, nor anything above them
+    roots: Vec<(PathBuf, ProtectionReason)>,
+    /// Directories whose direct children may not be deleted, nor anything
+    /// above them
+    parents: Vec<(PathBuf, ProtectionReason)>,
+    /// The approved root every path must be inside, if the operation is scoped
+    scope: Option<PathBuf>,
+}
+
+/// Handle to the protected-path settings, shared as Tauri managed state.
+///
+/// User-defined paths are kept in a JSON file in the app data directory.
+#[derive(Debug, Clone)]
+pub struct ProtectedPaths {
+    app_data_dir: PathBuf,
+    user_paths: Arc<Mutex<Vec<String>>>,
+}
+
+// ==================== Path Resolution ====================
+
+/// Resolves a path the way a deletion would see it.
+///
+/// `..` and symbolic links in the parent directories are resolved, but a
+/// symbolic link in the last component is kept, because deleting a link
+/// removes the link and not its target.
+///
+/// # Returns
+///
+/// The resolved path, or `None` if the parent directory does not exist.
+pub(crate) fn resolve_path(path: &Path) -> Option<PathBuf> {
+    let absolute = std::path::absolute(path).ok()?;
+
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent).ok().map(|p| p.join(name)),
+        // The root, or a path ending in `..`
+        _ => fs::canonicalize(&absolute).ok(),
+    }
+}
+
+/// Resolves a configured location, keeping it as-is if it does not exist yet.
+fn resolve_configured(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Checks whether a path is the root of a mounted filesystem.
+#[cfg(unix)]
+fn is_mount_point(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Some(parent) = path.parent() else {
+        // The filesystem root
+        return true;
+    };
+
+    match (path.symlink_metadata(), parent.metadata()) {
+        (Ok(metadata), Ok(parent_metadata)) => {
+            metadata.is_dir() && metadata.dev() != parent_metadata.dev()
+        }
+        _ => false,
+    }
+}
+
+/// Checks whether a path is the root of a mounted filesystem.
+#[cfg(not(unix))]
+fn is_mount_point(path: &Path) -> bool {
+    // Drive roots such as `C:\`
+    path.parent().is_none()
+}
+
+// ==================== Policy ====================
+
+impl Default for ProtectedPathPolicy {
+    /// The built-in policy: system directories, home directories and mount points.
+    fn default() -> Self {
+        let mut policy = ProtectedPathPolicy {
+            trees: Vec::new(),
+            roots: Vec::new(),
+            parents: Vec::new(),
+            scope: None,
+        };
+
+        #[cfg(unix)]
+        {
+            for dir in SYSTEM_DIRS {
+                policy.add_tree(Path::new(dir), ProtectionReason::SystemDirectory);
+            }
+            for dir in HOME_ROOTS {
+                policy.add_children(Path::new(dir), ProtectionReason::HomeDirectory);
+            }
+            for dir in HOME_DIRS {
+                policy.add_root(Path::new(dir), ProtectionReason::HomeDirectory);
+            }
+        }
+
+        #[cfg(windows)]
+        for var in [
+            "SystemRoot",
+            "ProgramFiles",
+            "ProgramFiles(x86)",
+            "ProgramData",
+        ] {
+            if let Some(dir) = std::env::var_os(var) {
+                policy.add_tree(Path::new(&dir), ProtectionReason::SystemDirectory);
+            }
+        }
+
+        if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+            policy.add_root(Path::new(&home), ProtectionReason::HomeDirectory);
+        }
+
+        policy
+    }
+}
+
+impl ProtectedPathPolicy {
+    /// Protects a directory, everything inside it and every directory above it.
+    pub fn add_tree(&mut self, path: &Path, reason: ProtectionReason) {
+        self.trees.push((path.to_path_buf(), reason));
+        let resolved = resolve_configured(path);
+        if resolved != path {
+            self.trees.push((resolved, reason));
+        }
+    }
+
+    /// Protects a directory and every directory above it, but not its contents.
+    pub fn add_root(&mut self, path: &Path, reason: ProtectionReason) {
+        self.roots.push((path.to_path_buf(), reason));
+        let resolved = resolve_configured(path);
+        if resolved != path {
+            self.roots.push((resolved, reason));
+        }
+    }
+
+    /// Protects every direct child of a directory and every directory above
+    /// them, but not their contents.
+    pub fn add_children(&mut self, path: &Path, reason: ProtectionReason) {
+        self.parents.push((path.to_path_buf(), reason));
+        let resolved = resolve_configured(path);
+        if resolved != path {
+            self.parents.push((resolved, reason));
+        }
+    }
+
+    /// Restricts the policy to paths inside an approved root.
+    pub fn within_root(mut self, root: PathBuf) -> Self {
+        self.scope = Some(root);
+        self
+    }
+
+    /// The approved root the policy is restricted to, if any.
+    pub fn scope(&self) -> Option<&Path> {
+        self.scope.as_deref()
+    }
+
+    /// Checks whether a path may be deleted.
+    ///
+    /// # Returns
+    ///
+    /// * `None` - The path is not protected
+    /// * `Some(ProtectionReason)` - Why the path may not be deleted
+    pub fn check(&self, path: &Path) -> Option<ProtectionReason> {
+        if let Some(root) = &self.scope {
+            if !is_within_root(path, root) {
+                return Some(ProtectionReason::OutsideRoot);
+            }
+        }
+
+        // Paths whose parent is missing cannot be deleted anyway
+        let resolved = resolve_path(path)?;
+
+        for (tree, reason) in &self.trees {
+            if resolved.starts_with(tree) || tree.starts_with(&resolved) {
+                return Some(*reason);
+            }
+        }
+
+        for (root, reason) in &self.roots {
+            if root.starts_with(&resolved) {
+                return Some(*reason);
+            }
+        }
+
+        for (parent, reason) in &self.parents {
+            if resolved.parent() == Some(parent.as_path()) || parent.starts_with(&resolved) {
+                return Some(*reason);
+            }
+        }
+
+        if is_mount_point(&resolved) {
+            return Some(ProtectionReason::MountPoint);
+        }
+
+        None
+    }
+}
+
+// ==================== Settings ====================
+
+impl ProtectedPaths {
+    /// Creates the settings for the given app data directory, loading the
+    /// user-defined paths saved there.
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        let user_paths = fs::read_to_string(app_data_dir.join(PROTECTED_PATHS_FILE_NAME))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        ProtectedPaths {
+            app_data_dir,
+            user_paths: Arc::new(Mutex::new(user_paths)),
+        }
+    }
+
+    /// Returns the user-defined protected paths.
+    pub fn user_paths(&self) -> Result<Vec<String>, String> {
+        Ok(self.user_paths.lock().map_err(|e| e.to_string())?.clone())
+    }
+
+    /// Replaces and saves the user-defined protected paths.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The paths were saved
+    /// * `Err(String)` - If a path is not absolute or the file could not be written
+    pub fn set_user_paths(&self, paths: Vec<String>) -> Result<(), String> {
+        if let Some(relative) = paths.iter().find(|p| !Path::new(p).is_absolute()) {
+            return Err(format!("Protected path must be absolute: {}", relative));
+        }
+
+        let mut user_paths = self.user_paths.lock().map_err(|e| e.to_string())?;
+
+        fs::create_dir_all(&self.app_data_dir)
+            .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+        let contents = serde_json::to_string_pretty(&paths)
+            .map_err(|e| format!("Failed to serialize protected paths: {}", e))?;
+        fs::write(self.app_data_dir.join(PROTECTED_PATHS_FILE_NAME), contents)
+            .map_err(|e| format!("Failed to save protected paths: {}", e))?;
+
+        *user_paths = paths;
+        Ok(())
+    }
+
+    /// Builds the full policy: the built-in rules, the app data directory and
+    /// the user-defined paths.
+    pub fn policy(&self) -> ProtectedPathPolicy {
+        let mut policy = ProtectedPathPolicy::default();
+        policy.add_tree(&self.app_data_dir, ProtectionReason::AppData);
+
+        for path in self.user_paths().unwrap_or_default() {
+            policy.add_tree(Path::new(&path), ProtectionReason::UserDefined);
+        }
+
+        policy
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Returns the user-defined protected paths.
+///
+/// # Returns
+///
+/// * `Ok(Vec<String>)` - The paths, in the order they were saved
+/// * `Err(String)` - If the settings are unavailable
+#[tauri::command]
+pub fn get_protected_paths(
+    protected_paths: State<'_, ProtectedPaths>,
+) -> Result<Vec<String>, String> {
+    protected_paths.user_paths()
+}
+
+/// Replaces the user-defined protected paths.
+///
+/// Deletes refuse these paths, everything inside them and every directory
+/// above them, in addition to the built-in protected locations.
+///
+/// # Arguments
+///
+/// * `paths` - Absolute paths to protect
+///
+/// # Returns
+///
+/// * `Ok(())` - The paths were saved
+/// * `Err(String)` - If a path is not absolute or the settings could not be saved
+#[tauri::command]
+pub fn set_protected_paths(
+    paths: Vec<String>,
+    protected_paths: State<'_, ProtectedPaths>,
+) -> Result<(), String> {
+    protected_paths.set_user_paths(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Tests system directories, their contents and their ancestors are protected.
+    #[cfg(unix)]
+    #[test]
+    fn test_system_directories_protected() {
+        let policy = ProtectedPathPolicy::default();
+
+        assert_eq!(
+            policy.check(Path::new("/etc")),
+            Some(ProtectionReason::SystemDirectory)
+        );
+        assert_eq!(
+            policy.check(Path::new("/usr/bin/env")),
+            Some(ProtectionReason::SystemDirectory)
+        );
+        assert_eq!(
+            policy.check(Path::new("/")),
+            Some(ProtectionReason::SystemDirectory)
+        );
+    }
+
+    /// Tests `..` cannot be used to escape into a protected directory.
+    #[cfg(unix)]
+    #[test]
+    fn test_dot_dot_is_resolved() {
+        let dir = tempdir().unwrap();
+        let policy = ProtectedPathPolicy::default();
+
+        let sneaky = dir.path().join("../../etc/hostname");
+        assert_eq!(
+            policy.check(&sneaky),
+            Some(ProtectionReason::SystemDirectory)
+        );
+        assert_eq!(policy.check(&dir.path().join("file.txt")), None);
+    }
+
+    /// Tests symbolic links in parent directories are resolved, but a link itself may be deleted.
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_resolved() {
+        let dir = tempdir().unwrap();
+        let protected = dir.path().join("protected");
+        fs::create_dir(&protected).unwrap();
+        fs::write(protected.join("keep.txt"), "x").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&protected, &link).unwrap();
+
+        let mut policy = ProtectedPathPolicy::default();
+        policy.add_tree(&protected, ProtectionReason::UserDefined);
+
+        assert_eq!(
+            policy.check(&link.join("keep.txt")),
+            Some(ProtectionReason::UserDefined)
+        );
+        // Deleting the link removes the link, not the protected directory
+        assert_eq!(policy.check(&link), None);
+    }
+
+    /// Tests home directories and their parents are protected, but not their contents.
+    #[test]
+    fn test_home_roots_protected() {
+        let dir = tempdir().unwrap();
+        let home = dir.path().join("home").join("me");
+        fs::create_dir_all(&home).unwrap();
+
+        let mut policy = ProtectedPathPolicy::default();
+        policy.add_root(&home, ProtectionReason::HomeDirectory);
+
+        assert_eq!(policy.check(&home), Some(ProtectionReason::HomeDirectory));
+        assert_eq!(
+            policy.check(&dir.path().join("home")),
+            Some(ProtectionReason::HomeDirectory)
+        );
+        assert_eq!(policy.check(&home.join("notes.txt")), None);
+    }
+
+    /// Tests every home directory is protected, not only the current user's.
+    #[cfg(unix)]
+    #[test]
+    fn test_other_home_directories_protected() {
+        let policy = ProtectedPathPolicy::default();
+
+        assert_eq!(
+            policy.check(Path::new("/home/x")),
+            Some(ProtectionReason::HomeDirectory)
+        );
+        assert_eq!(
+            policy.check(Path::new("/home")),
+            Some(ProtectionReason::HomeDirectory)
+        );
+    }
+
+    /// Tests `/root` is protected as a home directory, but not its contents.
+    #[cfg(unix)]
+    #[test]
+    fn test_root_home_protected() {
+        let policy = ProtectedPathPolicy::default();
+
+        assert_eq!(
+            policy.check(Path::new("/root")),
+            Some(ProtectionReason::HomeDirectory)
+        );
+        assert_eq!(policy.check(Path::new("/root/file.txt")), None);
+    }
+
+    /// Tests the direct children of a directory are protected, but not their contents.
+    #[test]
+    fn test_children_protected() {
+        let dir = tempdir().unwrap();
+        let homes = dir.path().join("home");
+        fs::create_dir_all(homes.join("me")).unwrap();
+
+        let mut policy = ProtectedPathPolicy::default();
+        policy.add_children(&homes, ProtectionReason::HomeDirectory);
+
+        assert_eq!(
+            policy.check(&homes.join("me")),
+            Some(ProtectionReason::HomeDirectory)
+        );
+        assert_eq!(
+            policy.check(&homes.join("you")),
+            Some(ProtectionReason::HomeDirectory)
+        );
+        assert_eq!(policy.check(&homes), Some(ProtectionReason::HomeDirectory));
+        assert_eq!(policy.check(&homes.join("me").join("notes.txt")), None);
+    }
+
+    /// Tests the app data directory and user-defined paths come from the settings.
+    #[test]
+    fn test_settings_policy() {
+        let dir = tempdir().unwrap();
+        let data_dir = dir.path().join("data");
+        let photos = dir.path().join("photos");
+        fs::create_dir_all(&photos).unwrap();
+
+        let settings = ProtectedPaths::new(data_dir.clone());
+        settings
+            .set_user_paths(vec![photos.to_string_lossy().to_string()])
+            .unwrap();
+        let policy = settings.policy();
+
+        assert_eq!(
+            policy.check(&data_dir.join("rename-journal.json")),
+            Some(ProtectionReason::AppData)
+        );
+        assert_eq!(
+            policy.check(&photos.join("a.jpg")),
+            Some(ProtectionReason::UserDefined)
+        );
+        assert_eq!(policy.check(&dir.path().join("other.txt")), None);
+
+        // User-defined paths survive a restart
+        assert_eq!(
+            ProtectedPaths::new(data_dir).user_paths().unwrap(),
+            vec![photos.to_string_lossy().to_string()]
+        );
+    }
+
+    /// Tests relative user-defined paths are rejected.
+    #[test]
+    fn test_set_user_paths_rejects_relative() {
+        let dir = tempdir().unwrap();
+        let settings = ProtectedPaths::new(dir.path().to_path_buf());
+
+        assert!(settings
+            .set_user_paths(vec!["relative/path".to_string()])
+            .is_err());
+        assert!(settings.user_paths().unwrap().is_empty());
+    }
+
+    /// Tests the filesystem root counts as a mount point.
+    #[test]
+    fn test_is_mount_point() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().ancestors().last().unwrap();
+
+        assert!(is_mount_point(root));
+        assert!(!is_mount_point(dir.path()));
+    }
+
+    /// Tests a scoped policy refuses paths outside the root, including through `..`.
+    #[test]
+    fn test_policy_within_root() {
+        let dir = tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap().join("root");
+        fs::create_dir(&root).unwrap();
+        let policy = ProtectedPathPolicy::default().within_root(root.clone());
+
+        assert_eq!(policy.check(&root.join("a.txt")), None);
+        assert_eq!(
+            policy.check(&root.join("../a.txt")),
+            Some(ProtectionReason::OutsideRoot)
+        );
+        assert_eq!(policy.check(&root), Some(ProtectionReason::OutsideRoot));
+        assert_eq!(policy.scope(), Some(root.as_path()));
+    }
+
+    /// Tests reasons serialize in camelCase.
+    #[test]
+    fn test_protection_reason_serialization() {
+        assert_eq!(
+            serde_json::to_string(&ProtectionReason::SystemDirectory).unwrap(),
+            "\"systemDirectory\""
+        );
+    }
+}
@@ -0,0 +1,651 @@
+//! Recoverable deletes through an app-managed quarantine.
+//!
+//! `batch_delete` in quarantine mode moves each item into a per-batch
+//! directory under the app data directory instead of removing it, and records
+//! the batch in a JSON manifest. Batches can later be put back with
+//! `restore_deleted` or removed for good with `purge_quarantine`.
+
+use crate::file_move::move_file;
+use crate::journal::{modified_ms, new_batch_id, now_ms};
+use crate::rename_rules::split_extension;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use walkdir::WalkDir;
+
+/// Name of the quarantine directory inside the app data directory
+pub const QUARANTINE_DIR_NAME: &str = "quarantine";
+
+/// File name of the manifest inside the quarantine directory
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+// ==================== Types ====================
+
+/// An item held in quarantine
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuarantineEntry {
+    /// Where the item was before it was deleted
+    pub original_path: String,
+    /// Where the item is kept inside the quarantine
+    pub quarantined_path: String,
+    /// Size in bytes; for directories, the total size of the files inside
+    pub size: u64,
+    /// Modification time (milliseconds since the Unix epoch) when deleted
+    pub modified_ms: Option<u64>,
+    /// Whether the item is a directory
+    pub is_directory: bool,
+}
+
+/// The items deleted by one `batch_delete` call
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuarantineBatch {
+    /// Unique identifier of the batch
+    pub id: String,
+    /// When the batch was deleted (seconds since the Unix epoch)
+    pub timestamp: u64,
+    /// Items still held for this batch
+    pub entries: Vec<QuarantineEntry>,
+}
+
+/// Result of restoring a quarantined batch
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RestoreResult {
+    /// Identifier of the batch
+    pub batch_id: String,
+    /// Restored items as (original path, path restored to); the paths differ
+    /// when the original location was taken
+    pub restored: Vec<(String, String)>,
+    /// Items that stay in quarantine (original path, error message)
+    pub failed: Vec<(String, String)>,
+}
+
+/// Result of purging the quarantine
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PurgeResult {
+    /// Batches that were removed for good
+    pub purged_batches: Vec<String>,
+    /// Total size of the purged items in bytes
+    pub freed_bytes: u64,
+    /// Batches that could not be removed (batch id, error message)
+    pub failed: Vec<(String, String)>,
+}
+
+/// On-disk layout of the manifest file
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Manifest {
+    batches: Vec<QuarantineBatch>,
+}
+
+/// Handle to the quarantine, shared as Tauri managed state.
+///
+/// Manifest reads and writes are serialized through an internal lock.
+#[derive(Debug, Clone)]
+pub struct Quarantine {
+    root: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+/// A batch being filled by `batch_delete`; recorded by `commit`.
+#[derive(Debug)]
+pub(crate) struct PendingBatch {
+    quarantine: Quarantine,
+    id: String,
+    timestamp: u64,
+    entries: Vec<QuarantineEntry>,
+}
+
+// ==================== Quarantine ====================
+
+/// Computes the size of an item, summing file sizes for directories.
+fn item_size(path: &Path, metadata: &fs::Metadata) -> u64 {
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Picks where to restore an item, avoiding anything already at its original path.
+///
+/// Returns the original path if it is free, otherwise `name (restored).ext`,
+/// `name (restored 2).ext` and so on.
+fn restore_target(original: &Path) -> PathBuf {
+    if original.symlink_metadata().is_err() {
+        return original.to_path_buf();
+    }
+
+    let name = original
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (stem, ext) = split_extension(&name);
+
+    (1..)
+        .map(|attempt| {
+            let suffix = if attempt == 1 {
+                " (restored)".to_string()
+            } else {
+                format!(" (restored {})", attempt)
+            };
+            original.with_file_name(format!("{}{}{}", stem, suffix, ext))
+        })
+        .find(|candidate| candidate.symlink_metadata().is_err())
+        .expect("some restore name is free")
+}
+
+impl Quarantine {
+    /// Creates a quarantine rooted at the given directory.
+    ///
+    /// The directory is created when the first item is quarantined.
+    pub fn new(root: PathBuf) -> Self {
+        Quarantine {
+            root,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.root.join(MANIFEST_FILE_NAME)
+    }
+
+    /// Loads the manifest, treating a missing file as empty.
+    fn load(&self) -> Result<Manifest, String> {
+        match fs::read_to_string(self.manifest_path()) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Failed to parse quarantine manifest: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(e) => Err(format!("Failed to read quarantine manifest: {}", e)),
+        }
+    }
+
+    /// Writes the manifest atomically by replacing it with a fully written copy.
+    fn save(&self, manifest: &Manifest) -> Result<(), String> {
+        fs::create_dir_all(&self.root)
+            .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+
+        let contents = serde_json::to_string_pretty(manifest)
+            .map_err(|e| format!("Failed to serialize quarantine manifest: {}", e))?;
+        let path = self.manifest_path();
+        let temp_path = path.with_extension("json.tmp");
+
+        fs::write(&temp_path, contents)
+            .and_then(|_| fs::rename(&temp_path, &path))
+            .map_err(|e| format!("Failed to write quarantine manifest: {}", e))
+    }
+
+    /// Starts a new batch of deletions.
+    pub(crate) fn begin(&self) -> PendingBatch {
+        let now = now_ms();
+        PendingBatch {
+            quarantine: self.clone(),
+            id: new_batch_id(now),
+            timestamp: now / 1000,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns all quarantined batches, oldest first.
+    pub fn batches(&self) -> Result<Vec<QuarantineBatch>, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        Ok(self.load()?.batches)
+    }
+
+    /// Moves the items of a batch back to where they were deleted from.
+    ///
+    /// Missing parent directories are recreated. An item whose original path
+    /// is taken is restored next to it under a `(restored)` name. Items that
+    /// fail stay in quarantine; the batch is dropped once it is empty.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RestoreResult)` - Which items were restored, and where
+    /// * `Err(String)` - If the batch does not exist or the manifest is unavailable
+    pub fn restore(&self, batch_id: &str) -> Result<RestoreResult, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut manifest = self.load()?;
+
+        let index = manifest
+            .batches
+            .iter()
+            .position(|b| b.id == batch_id)
+            .ok_or_else(|| format!("Quarantine batch not found: {}", batch_id))?;
+
+        let mut restored = Vec::new();
+        let mut failed = Vec::new();
+        let mut remaining = Vec::new();
+
+        for entry in manifest.batches[index].entries.drain(..) {
+            match restore_entry(&entry) {
+                Ok(target) => {
+                    restored.push((entry.original_path, target.to_string_lossy().to_string()))
+                }
+                Err(e) => {
+                    failed.push((entry.original_path.clone(), e));
+                    remaining.push(entry);
+                }
+            }
+        }
+
+        if remaining.is_empty() {
+            manifest.batches.remove(index);
+            let _ = fs::remove_dir_all(self.root.join(batch_id));
+        } else {
+            manifest.batches[index].entries = remaining;
+        }
+        self.save(&manifest)?;
+
+        Ok(RestoreResult {
+            batch_id: batch_id.to_string(),
+            restored,
+            failed,
+        })
+    }
+
+    /// Permanently removes quarantined batches.
+    ///
+    /// # Arguments
+    ///
+    /// * `older_than` - Only purge batches deleted at least this many seconds
+    ///   ago; `None` purges everything
+    /// * `now` - The current time in seconds since the Unix epoch
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PurgeResult)` - The purged batches and the space they used
+    /// * `Err(String)` - If the manifest is unavailable
+    pub fn purge(&self, older_than: Option<u64>, now: u64) -> Result<PurgeResult, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut manifest = self.load()?;
+
+        let mut result = PurgeResult {
+            purged_batches: Vec::new(),
+            freed_bytes: 0,
+            failed: Vec::new(),
+        };
+
+        manifest.batches.retain(|batch| {
+            let expired = older_than.is_none_or(|age| batch.timestamp.saturating_add(age) <= now);
+            if !expired {
+                return true;
+            }
+
+            let batch_dir = self.root.join(&batch.id);
+            match fs::remove_dir_all(&batch_dir) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    result.failed.push((batch.id.clone(), e.to_string()));
+                    true
+                }
+                _ => {
+                    result.purged_batches.push(batch.id.clone());
+                    result.freed_bytes += batch.entries.iter().map(|e| e.size).sum::<u64>();
+                    false
+                }
+            }
+        });
+
+        self.save(&manifest)?;
+        Ok(result)
+    }
+}
+
+/// Moves one quarantined item back, returning the path it was restored to.
+fn restore_entry(entry: &QuarantineEntry) -> Result<PathBuf, String> {
+    let quarantined = Path::new(&entry.quarantined_path);
+    if quarantined.symlink_metadata().is_err() {
+        return Err("quarantined copy no longer exists".to_string());
+    }
+
+    let original = Path::new(&entry.original_path);
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to recreate {}: {}", parent.display(), e))?;
+    }
+
+    let target = restore_target(original);
+    move_file(quarantined, &target, |_, _| {}).map_err(|e| e.to_string())?;
+    Ok(target)
+}
+
+impl PendingBatch {
+    /// Moves an item into the quarantine.
+    ///
+    /// Each item gets its own numbered directory inside the batch directory,
+    /// so items with the same name never collide.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PathBuf)` - Where the item is now kept
+    /// * `Err(String)` - The item could not be moved and was left in place
+    pub(crate) fn store(&mut self, path: &Path) -> Result<PathBuf, String> {
+        let metadata = path.symlink_metadata().map_err(|e| e.to_string())?;
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| format!("Cannot quarantine {}", path.display()))?;
+
+        let slot = self
+            .quarantine
+            .root
+            .join(&self.id)
+            .join(self.entries.len().to_string());
+        fs::create_dir_all(&slot)
+            .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+
+        let size = item_size(path, &metadata);
+        let destination = slot.join(file_name);
+        if let Err(e) = move_file(path, &destination, |_, _| {}) {
+            let _ = fs::remove_dir(&slot);
+            return Err(e.to_string());
+        }
+
+        let original_path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        self.entries.push(QuarantineEntry {
+            original_path: original_path.to_string_lossy().to_string(),
+            quarantined_path: destination.to_string_lossy().to_string(),
+            size,
+            modified_ms: modified_ms(&metadata),
+            is_directory: metadata.is_dir(),
+        });
+
+        Ok(destination)
+    }
+
+    /// Records the batch in the manifest.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(String))` - The id of the recorded batch
+    /// * `Ok(None)` - If nothing was quarantined
+    /// * `Err(String)` - If the manifest could not be written; the items stay
+    ///   in the quarantine directory
+    pub(crate) fn commit(self) -> Result<Option<String>, String> {
+        if self.entries.is_empty() {
+            let _ = fs::remove_dir_all(self.quarantine.root.join(&self.id));
+            return Ok(None);
+        }
+
+        let quarantine = &self.quarantine;
+        let _guard = quarantine.lock.lock().map_err(|e| e.to_string())?;
+        let mut manifest = quarantine.load()?;
+        manifest.batches.push(QuarantineBatch {
+            id: self.id.clone(),
+            timestamp: self.timestamp,
+            entries: self.entries,
+        });
+        quarantine.save(&manifest)?;
+
+        Ok(Some(self.id))
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Lists the batches held in the delete quarantine.
+///
+/// # Returns
+///
+/// * `Ok(Vec<QuarantineBatch>)` - All quarantined batches, oldest first
+/// * `Err(String)` - If the manifest could not be read
+#[tauri::command]
+pub fn list_quarantine(quarantine: State<'_, Quarantine>) -> Result<Vec<QuarantineBatch>, String> {
+    quarantine.batches()
+}
+
+/// Restores a quarantined batch to its original locations.
+///
+/// Missing parent directories are recreated, and an item whose original path
+/// is now taken is restored next to it as `name (restored).ext`.
+///
+/// # Arguments
+///
+/// * `batch_id` - The id of the batch, as returned by `list_quarantine`
+///
+/// # Returns
+///
+/// * `Ok(RestoreResult)` - Restored items and items left in quarantine
+/// * `Err(String)` - If the batch does not exist or the manifest is unavailable
+#[tauri::command]
+pub fn restore_deleted(
+    batch_id: String,
+    quarantine: State<'_, Quarantine>,
+) -> Result<RestoreResult, String> {
+    quarantine.restore(&batch_id)
+}
+
+/// Permanently deletes quarantined batches.
+///
+/// # Arguments
+///
+/// * `older_than` - Only purge batches deleted at least this many seconds
+///   ago; omit to purge everything
+///
+/// # Returns
+///
+/// * `Ok(PurgeResult)` - The purged batches and the space freed
+/// * `Err(String)` - If the manifest is unavailable
+#[tauri::command]
+pub fn purge_quarantine(
+    older_than: Option<u64>,
+    quarantine: State<'_, Quarantine>,
+) -> Result<PurgeResult, String> {
+    quarantine.purge(older_than, now_ms() / 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Helper to create a file with the given content, including parent directories.
+    fn write_file(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Helper to quarantine items as one batch.
+    fn quarantine_items(quarantine: &Quarantine, paths: &[&Path]) -> String {
+        let mut batch = quarantine.begin();
+        for path in paths {
+            batch.store(path).unwrap();
+        }
+        batch.commit().unwrap().unwrap()
+    }
+
+    /// Tests quarantined items are moved and recorded with their metadata.
+    #[test]
+    fn test_quarantine_records_batch() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+        let file = dir.path().join("docs/report.txt");
+        let folder = dir.path().join("docs/folder");
+        write_file(&file, "12345");
+        write_file(&folder.join("inner.txt"), "abc");
+
+        let id = quarantine_items(&quarantine, &[&file, &folder]);
+
+        assert!(!file.exists());
+        assert!(!folder.exists());
+
+        let batches = quarantine.batches().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].id, id);
+
+        let entries = &batches[0].entries;
+        assert_eq!(entries[0].original_path, file.to_string_lossy());
+        assert_eq!(entries[0].size, 5);
+        assert!(entries[0].modified_ms.is_some());
+        assert!(!entries[0].is_directory);
+        assert_eq!(
+            fs::read_to_string(&entries[0].quarantined_path).unwrap(),
+            "12345"
+        );
+        assert!(entries[1].is_directory);
+        assert_eq!(entries[1].size, 3);
+    }
+
+    /// Tests an empty batch is not recorded.
+    #[test]
+    fn test_quarantine_empty_batch() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+
+        assert_eq!(quarantine.begin().commit().unwrap(), None);
+        assert!(quarantine.batches().unwrap().is_empty());
+    }
+
+    /// Tests items with the same name are kept apart.
+    #[test]
+    fn test_quarantine_same_names() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+        let first = dir.path().join("a/notes.txt");
+        let second = dir.path().join("b/notes.txt");
+        write_file(&first, "first");
+        write_file(&second, "second");
+
+        let id = quarantine_items(&quarantine, &[&first, &second]);
+        let result = quarantine.restore(&id).unwrap();
+
+        assert_eq!(result.restored.len(), 2);
+        assert_eq!(fs::read_to_string(&first).unwrap(), "first");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "second");
+    }
+
+    /// Tests restoring a batch puts items back and removes the batch.
+    #[test]
+    fn test_restore_deleted() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+        let file = dir.path().join("photo.jpg");
+        write_file(&file, "pixels");
+
+        let id = quarantine_items(&quarantine, &[&file]);
+        let result = quarantine.restore(&id).unwrap();
+
+        let path = file.to_string_lossy().to_string();
+        assert_eq!(result.restored, vec![(path.clone(), path)]);
+        assert!(result.failed.is_empty());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "pixels");
+        assert!(quarantine.batches().unwrap().is_empty());
+        assert!(!dir.path().join("quarantine").join(&id).exists());
+    }
+
+    /// Tests restoring recreates a parent directory that no longer exists.
+    #[test]
+    fn test_restore_recreates_parent() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+        let file = dir.path().join("gone/deeper/file.txt");
+        write_file(&file, "data");
+
+        let id = quarantine_items(&quarantine, &[&file]);
+        fs::remove_dir_all(dir.path().join("gone")).unwrap();
+
+        quarantine.restore(&id).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "data");
+    }
+
+    /// Tests restoring next to a file that took the original name.
+    #[test]
+    fn test_restore_name_conflict() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+        let file = dir.path().join("report.txt");
+        write_file(&file, "old");
+
+        let id = quarantine_items(&quarantine, &[&file]);
+        write_file(&file, "new");
+        write_file(&dir.path().join("report (restored).txt"), "other");
+
+        let result = quarantine.restore(&id).unwrap();
+
+        let expected = dir.path().join("report (restored 2).txt");
+        assert_eq!(result.restored[0].1, expected.to_string_lossy());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new");
+        assert_eq!(fs::read_to_string(&expected).unwrap(), "old");
+    }
+
+    /// Tests items whose quarantined copy vanished stay in the manifest.
+    #[test]
+    fn test_restore_missing_copy() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+        let kept = dir.path().join("kept.txt");
+        let lost = dir.path().join("lost.txt");
+        write_file(&kept, "kept");
+        write_file(&lost, "lost");
+
+        let id = quarantine_items(&quarantine, &[&kept, &lost]);
+        let lost_copy = quarantine.batches().unwrap()[0].entries[1]
+            .quarantined_path
+            .clone();
+        fs::remove_file(lost_copy).unwrap();
+
+        let result = quarantine.restore(&id).unwrap();
+
+        assert_eq!(result.restored.len(), 1);
+        assert_eq!(result.failed.len(), 1);
+        assert!(kept.exists());
+        assert_eq!(quarantine.batches().unwrap()[0].entries.len(), 1);
+    }
+
+    /// Tests restoring an unknown batch fails.
+    #[test]
+    fn test_restore_unknown_batch() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+
+        assert!(quarantine
+            .restore("missing")
+            .unwrap_err()
+            .contains("not found"));
+    }
+
+    /// Tests purging respects the age limit and removes quarantined files.
+    #[test]
+    fn test_purge_quarantine() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+        let file = dir.path().join("file.txt");
+        write_file(&file, "1234");
+
+        let id = quarantine_items(&quarantine, &[&file]);
+        let timestamp = quarantine.batches().unwrap()[0].timestamp;
+
+        // Not old enough yet
+        let result = quarantine.purge(Some(3600), timestamp + 60).unwrap();
+        assert!(result.purged_batches.is_empty());
+        assert_eq!(quarantine.batches().unwrap().len(), 1);
+
+        let result = quarantine.purge(Some(3600), timestamp + 3600).unwrap();
+        assert_eq!(result.purged_batches, vec![id.clone()]);
+        assert_eq!(result.freed_bytes, 4);
+        assert!(quarantine.batches().unwrap().is_empty());
+        assert!(!dir.path().join("quarantine").join(&id).exists());
+    }
+
+    /// Tests purging without an age limit removes every batch.
+    #[test]
+    fn test_purge_quarantine_all() {
+        let dir = tempdir().unwrap();
+        let quarantine = Quarantine::new(dir.path().join("quarantine"));
+        let first = dir.path().join("a.txt");
+        let second = dir.path().join("b.txt");
+        write_file(&first, "a");
+        write_file(&second, "b");
+
+        quarantine_items(&quarantine, &[&first]);
+        quarantine_items(&quarantine, &[&second]);
+
+        let result = quarantine.purge(None, 0).unwrap();
+        assert_eq!(result.purged_batches.len(), 2);
+        assert!(quarantine.batches().unwrap().is_empty());
+    }
+}
@@ -6,12 +6,24 @@
 //! This module supports both synchronous commands (for backward compatibility)
 //! and streaming commands with progress updates via Tauri Channels.
 
+use crate::approved_roots::{is_within_root, ApprovedRoots};
+use crate::content_search::ContentHit;
+use crate::operations::{CancellationToken, OperationRegistry, CANCELLED_ERROR};
+use crate::protected_paths::{ProtectedPathPolicy, ProtectedPaths, ProtectionReason};
+use crate::quarantine::{PendingBatch, Quarantine};
+use crate::rename_rules::char_offset;
+use crate::search_expression::{Candidate, FilterExpression, SearchExpression};
+use crate::search_filters::{SearchFilters, TimeKind};
+use crate::trash::move_to_trash;
+use globset::{GlobBuilder, GlobMatcher};
 use rayon::prelude::*;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::ipc::Channel;
+use tauri::State;
 use walkdir::WalkDir;
 
 // ==================== Types ====================
@@ -45,6 +57,13 @@ pub enum SearchProgress {
         /// Total number of matches found
         matches_found: usize,
     },
+    /// Search was cancelled before it finished
+    Cancelled {
+        /// Number of files found before cancellation
+        files_found: usize,
+        /// Number of matches found before cancellation
+        matches_found: usize,
+    },
 }
 
 /// Progress events for batch delete operations.
@@ -75,18 +94,99 @@ pub enum DeleteProgress {
         /// Number of failed deletions
         failed: usize,
     },
+    /// Deletion was cancelled; the remaining files were left untouched
+    Cancelled {
+        /// Number of files deleted before cancellation
+        successful: usize,
+        /// Number of failed deletions before cancellation
+        failed: usize,
+        /// Total number of files requested
+        total: usize,
+    },
 }
 
 /// Pattern matching mode for file search
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum PatternType {
     /// Simple substring matching
+    #[default]
     Simple,
     /// Match by file extension (comma-separated list)
     Extension,
     /// Regular expression matching
     Regex,
+    /// Shell glob matching (`*`, `?`, `[abc]`, `{a,b}`, `**`)
+    Glob,
+}
+
+/// What a search pattern is matched against
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchTarget {
+    /// The file name
+    #[default]
+    Name,
+    /// The path relative to the search's base directory
+    RelativePath,
+    /// The full path
+    FullPath,
+}
+
+impl MatchTarget {
+    /// Picks the target for a search.
+    ///
+    /// Without an explicit target, a glob containing `/` is matched against
+    /// the relative path and any other pattern against the file name.
+    pub(crate) fn resolve(
+        target: Option<MatchTarget>,
+        pattern_type: &PatternType,
+        pattern: &str,
+    ) -> Self {
+        target.unwrap_or(
+            if *pattern_type == PatternType::Glob && pattern.contains('/') {
+                MatchTarget::RelativePath
+            } else {
+                MatchTarget::Name
+            },
+        )
+    }
+
+    /// Returns the text a pattern is matched against, using `/` as the
+    /// separator on every platform.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file
+    /// * `base_path` - The directory the search started in
+    /// * `name` - The file name
+    pub(crate) fn text(self, path: &Path, base_path: &Path, name: &str) -> String {
+        let path = match self {
+            MatchTarget::Name => return name.to_string(),
+            MatchTarget::RelativePath => path.strip_prefix(base_path).unwrap_or(path),
+            MatchTarget::FullPath => path,
+        };
+
+        let text = path.to_string_lossy();
+        if std::path::MAIN_SEPARATOR == '/' {
+            text.to_string()
+        } else {
+            text.replace(std::path::MAIN_SEPARATOR, "/")
+        }
+    }
+}
+
+/// What happens to deleted files
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteMode {
+    /// Remove files from disk; this cannot be undone
+    #[default]
+    Permanent,
+    /// Move files to the desktop trash so they can be restored
+    Trash,
+    /// Move files to the app's quarantine so the batch can be restored
+    Quarantine,
 }
 
 /// Result of a file match operation
@@ -96,12 +196,47 @@ pub struct FileMatchResult {
     pub path: String,
     /// File name only
     pub name: String,
-    /// Character ranges where the pattern matched (start, end)
+    /// Character ranges where the pattern matched (start, end), within the
+    /// text given by `match_target`
     pub match_ranges: Vec<(usize, usize)>,
+    /// What the pattern was matched against
+    pub match_target: MatchTarget,
     /// File size in bytes
     pub size: u64,
     /// Whether this is a directory
     pub is_directory: bool,
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+    /// Creation time in milliseconds since the Unix epoch, if recorded
+    pub created_ms: Option<u64>,
+    /// Last access time in milliseconds since the Unix epoch, if recorded
+    pub accessed_ms: Option<u64>,
+    /// The first matching lines, for content search matches
+    pub content_hits: Vec<ContentHit>,
+}
+
+impl FileMatchResult {
+    /// Builds a match from a path, its pattern matches and its metadata.
+    pub(crate) fn new(
+        path: &Path,
+        name: String,
+        match_ranges: Vec<(usize, usize)>,
+        match_target: MatchTarget,
+        metadata: &fs::Metadata,
+    ) -> Self {
+        FileMatchResult {
+            path: path.to_string_lossy().to_string(),
+            name,
+            match_ranges,
+            match_target,
+            size: metadata.len(),
+            is_directory: metadata.is_dir(),
+            modified_ms: TimeKind::Modified.read(metadata),
+            created_ms: TimeKind::Created.read(metadata),
+            accessed_ms: TimeKind::Accessed.read(metadata),
+            content_hits: Vec::new(),
+        }
+    }
 }
 
 /// Result of a batch delete operation
@@ -113,6 +248,475 @@ pub struct DeleteResult {
     pub failed: Vec<(String, String)>,
     /// Empty directories that were cleaned up
     pub deleted_dirs: Vec<String>,
+    /// Where each deleted item was moved to, as (original path, new path);
+    /// empty for permanent deletion
+    pub destinations: Vec<(String, String)>,
+    /// Id of the quarantine batch holding the deleted items, in quarantine mode
+    pub batch_id: Option<String>,
+    /// Why the quarantine batch could not be recorded, if it could not; the
+    /// items stay in the quarantine directory but cannot be restored
+    pub quarantine_error: Option<String>,
+    /// Whether this is a dry-run report; nothing was touched on disk
+    pub dry_run: bool,
+    /// For each directory in a dry run, how many entries it contains
+    /// (files and subdirectories at any depth) that would be removed with it
+    pub directory_entries: Vec<(String, usize)>,
+    /// Paths refused by the protected-path policy, with the reason; they are
+    /// also listed in `failed`
+    pub protected: Vec<(String, ProtectionReason)>,
+    /// The approved root the operation was restricted to
+    pub root: Option<String>,
+}
+
+// ==================== Deletion ====================
+
+/// Formats the `failed` message for a path refused by the protected-path policy.
+fn protected_message(reason: ProtectionReason) -> String {
+    format!("Refusing to delete protected path ({})", reason.describe())
+}
+
+/// Deletes a single file or directory.
+///
+/// # Arguments
+///
+/// * `path` - The item to delete
+/// * `mode` - Whether to remove the item or move it somewhere recoverable
+/// * `quarantine` - The batch receiving quarantined items, if available
+///
+/// # Returns
+///
+/// * `Ok(Some(String))` - The item was moved to this path
+/// * `Ok(None)` - The item was removed from disk
+/// * `Err(String)` - The item could not be deleted
+fn delete_path(
+    path: &Path,
+    mode: DeleteMode,
+    quarantine: Option<&mut PendingBatch>,
+) -> Result<Option<String>, String> {
+    let destination = match mode {
+        DeleteMode::Permanent => {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+            return result.map(|_| None).map_err(|e| e.to_string());
+        }
+        DeleteMode::Trash => move_to_trash(path)?,
+        DeleteMode::Quarantine => quarantine
+            .ok_or_else(|| "The quarantine is not available".to_string())?
+            .store(path)?,
+    };
+
+    Ok(Some(destination.to_string_lossy().to_string()))
+}
+
+/// Sorts directories deepest first, so nested empty directories go before their parents.
+fn deepest_first(parent_dirs: HashSet<String>) -> Vec<String> {
+    let mut dirs: Vec<_> = parent_dirs.into_iter().collect();
+    dirs.sort_by(|a, b| {
+        b.matches(std::path::MAIN_SEPARATOR)
+            .count()
+            .cmp(&a.matches(std::path::MAIN_SEPARATOR).count())
+    });
+    dirs
+}
+
+/// Checks whether the empty directory cleanup may remove a directory.
+///
+/// The cleanup base itself and anything outside it are never removed, nor
+/// are directories the protected-path policy refuses.
+fn may_clean_up(path: &Path, cleanup_base: Option<&Path>, policy: &ProtectedPathPolicy) -> bool {
+    cleanup_base.is_none_or(|base| is_within_root(path, base)) && policy.check(path).is_none()
+}
+
+/// Resolves the directory the empty directory cleanup may not climb above.
+///
+/// # Arguments
+///
+/// * `base_path` - The search's base path, if the caller passed one
+/// * `policy` - The protected-path policy, restricted to the approved root
+///
+/// # Returns
+///
+/// * `Ok(Option<PathBuf>)` - The canonical base path, or the approved root
+///   when no base path was passed
+/// * `Err(String)` - If the base path cannot be resolved or is outside the
+///   approved root
+fn cleanup_base(
+    base_path: Option<&str>,
+    policy: &ProtectedPathPolicy,
+) -> Result<Option<PathBuf>, String> {
+    let Some(base_path) = base_path else {
+        return Ok(policy.scope().map(Path::to_path_buf));
+    };
+
+    let base =
+        fs::canonicalize(base_path).map_err(|e| format!("Cannot resolve {}: {}", base_path, e))?;
+    if let Some(root) = policy.scope() {
+        if !base.starts_with(root) {
+            return Err(format!(
+                "{} is outside the approved root {}",
+                base_path,
+                root.display()
+            ));
+        }
+    }
+
+    Ok(Some(base))
+}
+
+/// Removes the given directories if they are empty, deepest first.
+///
+/// With a `cleanup_base`, each removed directory's parent is checked in
+/// turn, walking upward until a directory is not empty or the cleanup base
+/// is reached; the base itself is never removed. Without one, only the given
+/// directories are checked.
+///
+/// # Returns
+///
+/// The directories that were removed.
+fn remove_empty_dirs(
+    parent_dirs: HashSet<String>,
+    cleanup_base: Option<&Path>,
+    policy: &ProtectedPathPolicy,
+) -> Vec<String> {
+    let mut deleted_dirs = Vec::new();
+
+    for dir in deepest_first(parent_dirs) {
+        let mut current = Some(PathBuf::from(dir));
+
+        while let Some(path) = current {
+            if !may_clean_up(&path, cleanup_base, policy) || !path.is_dir() {
+                break;
+            }
+            let Ok(mut entries) = fs::read_dir(&path) else {
+                break;
+            };
+            // Stop at the first directory that is not empty
+            if entries.next().is_some() || fs::remove_dir(&path).is_err() {
+                break;
+            }
+
+            deleted_dirs.push(path.to_string_lossy().to_string());
+            current = cleanup_base.and(path.parent().map(Path::to_path_buf));
+        }
+    }
+
+    deleted_dirs
+}
+
+/// Deletes a batch of files, optionally cleaning up emptied parent directories.
+///
+/// Shared by `batch_delete` and `batch_delete_with_progress`. In quarantine
+/// mode the deleted items are recorded as one quarantine batch. A manifest
+/// failure does not fail the batch, since the items have already been moved
+/// at that point; callers should check `quarantine_error`, which says why the
+/// batch could not be recorded while `batch_id` stays empty.
+///
+/// Paths refused by `policy` are not touched and are reported in both
+/// `failed` and `protected`; protected parent directories are never removed
+/// by the empty directory cleanup, which never climbs above `cleanup_base`.
+///
+/// Once `cancel` is set, the remaining files are left in place and no empty
+/// directories are removed.
+///
+/// # Arguments
+///
+/// * `files` - List of file paths to delete
+/// * `delete_empty_dirs` - Whether to remove parent directories that become empty
+/// * `cleanup_base` - The directory the cleanup stops at; without one, only
+///   direct parents are removed
+/// * `mode` - What happens to deleted items
+/// * `policy` - The protected-path policy
+/// * `quarantine` - The quarantine used in quarantine mode
+/// * `cancel` - Token checked before each file
+/// * `on_deleted` - Called with the 0-based index and path of each processed file
+///
+/// # Returns
+///
+/// The `DeleteResult`; fewer files than requested were processed if cancelled.
+#[allow(clippy::too_many_arguments)]
+fn run_batch_delete<F>(
+    files: Vec<String>,
+    delete_empty_dirs: bool,
+    cleanup_base: Option<&Path>,
+    mode: DeleteMode,
+    policy: &ProtectedPathPolicy,
+    quarantine: Option<&Quarantine>,
+    cancel: &CancellationToken,
+    mut on_deleted: F,
+) -> DeleteResult
+where
+    F: FnMut(usize, &str),
+{
+    let mut successful = Vec::new();
+    let mut failed = Vec::new();
+    let mut destinations = Vec::new();
+    let mut protected = Vec::new();
+    let mut parent_dirs: HashSet<String> = HashSet::new();
+    let mut pending = match mode {
+        DeleteMode::Quarantine => quarantine.map(Quarantine::begin),
+        _ => None,
+    };
+    let mut cancelled = false;
+
+    for (index, file_path) in files.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            cancelled = true;
+            break;
+        }
+
+        let path = Path::new(&file_path);
+
+        if let Some(reason) = policy.check(path) {
+            on_deleted(index, &file_path);
+            failed.push((file_path.clone(), protected_message(reason)));
+            protected.push((file_path, reason));
+            continue;
+        }
+
+        // Track parent directory for potential cleanup
+        if delete_empty_dirs {
+            if let Some(parent) = path.parent() {
+                parent_dirs.insert(parent.to_string_lossy().to_string());
+            }
+        }
+
+        // Attempt deletion
+        let result = delete_path(path, mode, pending.as_mut());
+        on_deleted(index, &file_path);
+
+        match result {
+            Ok(destination) => {
+                if let Some(destination) = destination {
+                    destinations.push((file_path.clone(), destination));
+                }
+                successful.push(file_path);
+            }
+            Err(e) => failed.push((file_path, e)),
+        }
+    }
+
+    let (batch_id, quarantine_error) = match pending.map(|batch| batch.commit()) {
+        Some(Ok(batch_id)) => (batch_id, None),
+        Some(Err(e)) => (
+            None,
+            Some(format!(
+                "The quarantined items could not be recorded and cannot be restored: {}",
+                e
+            )),
+        ),
+        None => (None, None),
+    };
+
+    // Clean up empty directories if requested
+    let deleted_dirs = if delete_empty_dirs && !cancelled {
+        remove_empty_dirs(parent_dirs, cleanup_base, policy)
+    } else {
+        Vec::new()
+    };
+
+    DeleteResult {
+        successful,
+        failed,
+        deleted_dirs,
+        destinations,
+        batch_id,
+        quarantine_error,
+        dry_run: false,
+        directory_entries: Vec::new(),
+        protected,
+        root: policy
+            .scope()
+            .map(|root| root.to_string_lossy().to_string()),
+    }
+}
+
+// ==================== Dry Run ====================
+
+/// Checks whether the current user may create and remove entries in a directory.
+#[cfg(unix)]
+fn can_modify_dir(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is a valid NUL-terminated string that outlives the call
+    unsafe { libc::access(path.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+}
+
+/// Checks whether the current user may create and remove entries in a directory.
+#[cfg(not(unix))]
+fn can_modify_dir(dir: &Path) -> bool {
+    fs::metadata(dir).is_ok_and(|m| !m.permissions().readonly())
+}
+
+/// Runs the checks a deletion would hit, without deleting anything.
+///
+/// # Returns
+///
+/// * `Ok(Some(usize))` - A directory that would be removed with this many entries
+/// * `Ok(None)` - A file or symbolic link that would be removed
+/// * `Err(String)` - Why the deletion would fail
+fn check_deletion(path: &Path) -> Result<Option<usize>, String> {
+    let metadata = path.symlink_metadata().map_err(|e| e.to_string())?;
+
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if !can_modify_dir(parent) {
+        return Err(format!(
+            "Permission denied: cannot remove entries from {}",
+            parent.display()
+        ));
+    }
+
+    if !metadata.is_dir() {
+        return Ok(None);
+    }
+
+    // remove_dir_all must empty every directory in the tree
+    let mut entries = 0;
+    for entry in WalkDir::new(path).min_depth(1) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        entries += 1;
+        if entry.file_type().is_dir() && !can_modify_dir(entry.path()) {
+            return Err(format!(
+                "Permission denied: cannot remove entries from {}",
+                entry.path().display()
+            ));
+        }
+    }
+    if !can_modify_dir(path) && entries > 0 {
+        return Err(format!(
+            "Permission denied: cannot remove entries from {}",
+            path.display()
+        ));
+    }
+
+    Ok(Some(entries))
+}
+
+/// Predicts which parent directories the empty-directory cleanup would remove.
+///
+/// Mirrors `remove_empty_dirs`: a directory is removed if, once `removed` are
+/// gone, it has no entries left, counting directories removed before it. With
+/// a `cleanup_base`, the prediction walks upward the same way.
+fn predict_empty_dirs(
+    parent_dirs: HashSet<String>,
+    mut removed: HashSet<PathBuf>,
+    cleanup_base: Option<&Path>,
+    policy: &ProtectedPathPolicy,
+) -> Vec<String> {
+    let mut deleted_dirs = Vec::new();
+
+    for dir in deepest_first(parent_dirs) {
+        let mut current = Some(PathBuf::from(dir));
+
+        while let Some(path) = current {
+            if removed.contains(&path)
+                || !may_clean_up(&path, cleanup_base, policy)
+                || !path.is_dir()
+                || !can_modify_dir(path.parent().unwrap_or(&path))
+            {
+                break;
+            }
+
+            let Ok(mut entries) = fs::read_dir(&path) else {
+                break;
+            };
+            let would_be_empty =
+                entries.all(|entry| entry.is_ok_and(|entry| removed.contains(&entry.path())));
+            if !would_be_empty {
+                break;
+            }
+
+            deleted_dirs.push(path.to_string_lossy().to_string());
+            current = cleanup_base.and(path.parent().map(Path::to_path_buf));
+            removed.insert(path);
+        }
+    }
+
+    deleted_dirs
+}
+
+/// Reports what `batch_delete` would do, without touching the disk.
+///
+/// # Arguments
+///
+/// * `files` - List of file paths to check
+/// * `delete_empty_dirs` - Whether to predict the empty directory cleanup
+/// * `cleanup_base` - The directory the cleanup stops at
+/// * `policy` - The protected-path policy
+///
+/// # Returns
+///
+/// A `DeleteResult` with `dry_run` set: the paths that would be deleted, the
+/// paths that would fail with the reason, the directories the cleanup would
+/// remove, and how many entries each directory would take with it.
+fn plan_batch_delete(
+    files: Vec<String>,
+    delete_empty_dirs: bool,
+    cleanup_base: Option<&Path>,
+    policy: &ProtectedPathPolicy,
+) -> DeleteResult {
+    let mut successful = Vec::new();
+    let mut failed = Vec::new();
+    let mut protected = Vec::new();
+    let mut directory_entries = Vec::new();
+    let mut parent_dirs: HashSet<String> = HashSet::new();
+    let mut removed: HashSet<PathBuf> = HashSet::new();
+
+    for file_path in files {
+        let path = Path::new(&file_path);
+
+        if let Some(reason) = policy.check(path) {
+            failed.push((file_path.clone(), protected_message(reason)));
+            protected.push((file_path, reason));
+            continue;
+        }
+
+        if delete_empty_dirs {
+            if let Some(parent) = path.parent() {
+                parent_dirs.insert(parent.to_string_lossy().to_string());
+            }
+        }
+
+        match check_deletion(path) {
+            Ok(entries) => {
+                if let Some(entries) = entries {
+                    directory_entries.push((file_path.clone(), entries));
+                }
+                removed.insert(path.to_path_buf());
+                successful.push(file_path);
+            }
+            Err(e) => failed.push((file_path, e)),
+        }
+    }
+
+    let deleted_dirs = if delete_empty_dirs {
+        predict_empty_dirs(parent_dirs, removed, cleanup_base, policy)
+    } else {
+        Vec::new()
+    };
+
+    DeleteResult {
+        successful,
+        failed,
+        deleted_dirs,
+        destinations: Vec::new(),
+        batch_id: None,
+        quarantine_error: None,
+        dry_run: true,
+        directory_entries,
+        protected,
+        root: policy
+            .scope()
+            .map(|root| root.to_string_lossy().to_string()),
+    }
 }
 
 // ==================== Pattern Matching Functions ====================
@@ -129,7 +733,8 @@ pub struct DeleteResult {
 ///
 /// # Returns
 ///
-/// * `Some(Vec<(usize, usize)>)` - Vector of (start, end) positions of matches
+/// * `Some(Vec<(usize, usize)>)` - Vector of (start, end) character positions
+///   of matches
 /// * `None` - If no matches found
 fn match_simple(name: &str, pattern: &str, case_sensitive: bool) -> Option<Vec<(usize, usize)>> {
     let search_name = if case_sensitive {
@@ -147,8 +752,16 @@ fn match_simple(name: &str, pattern: &str, case_sensitive: bool) -> Option<Vec<(
     let mut start = 0;
     while let Some(pos) = search_name[start..].find(&search_pattern) {
         let abs_pos = start + pos;
-        matches.push((abs_pos, abs_pos + pattern.len()));
-        start = abs_pos + 1;
+        matches.push((
+            char_offset(&search_name, abs_pos),
+            char_offset(&search_name, abs_pos + search_pattern.len()),
+        ));
+        // Step over one whole character so overlapping matches are found
+        start = abs_pos
+            + search_name[abs_pos..]
+                .chars()
+                .next()
+                .map_or(1, char::len_utf8);
     }
 
     if matches.is_empty() {
@@ -197,86 +810,260 @@ fn match_extension(
         };
 
         if name_check.ends_with(&ext_check) {
-            let start = name.len() - ext_pattern.len();
-            return Some(vec![(start, name.len())]);
+            let end = name.chars().count();
+            return Some(vec![(end.saturating_sub(ext_pattern.chars().count()), end)]);
         }
     }
     None
 }
 
-/// Match files using regex.
-///
-/// Finds all occurrences of the regex pattern within the filename.
+/// Compiles a regex.
 ///
 /// # Arguments
 ///
-/// * `name` - The filename to search in
-/// * `pattern` - The regex pattern to match
+/// * `pattern` - The regex pattern
 /// * `case_sensitive` - Whether the match should be case-sensitive
 ///
 /// # Returns
 ///
-/// * `Ok(Some(Vec<(usize, usize)>))` - Vector of (start, end) positions of matches
-/// * `Ok(None)` - If no matches found
+/// * `Ok(Regex)` - The compiled regex
 /// * `Err(String)` - If the regex pattern is invalid
-fn match_regex(
-    name: &str,
-    pattern: &str,
-    case_sensitive: bool,
-) -> Result<Option<Vec<(usize, usize)>>, String> {
-    use regex::Regex;
-
-    let regex_pattern = if case_sensitive {
+pub(crate) fn compile_regex(pattern: &str, case_sensitive: bool) -> Result<Regex, String> {
+    if case_sensitive {
         Regex::new(pattern)
     } else {
         Regex::new(&format!("(?i){}", pattern))
     }
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| e.to_string())
+}
 
-    let matches: Vec<(usize, usize)> = regex_pattern
+/// Match files using a compiled regex.
+///
+/// Finds all occurrences of the regex within the filename.
+///
+/// # Arguments
+///
+/// * `name` - The filename to search in
+/// * `regex` - The compiled regex
+///
+/// # Returns
+///
+/// * `Some(Vec<(usize, usize)>)` - Vector of (start, end) character positions
+///   of matches
+/// * `None` - If no matches found
+fn match_regex(name: &str, regex: &Regex) -> Option<Vec<(usize, usize)>> {
+    let matches: Vec<(usize, usize)> = regex
         .find_iter(name)
-        .map(|m| (m.start(), m.end()))
+        .map(|m| (char_offset(name, m.start()), char_offset(name, m.end())))
         .collect();
 
     if matches.is_empty() {
-        Ok(None)
+        None
     } else {
-        Ok(Some(matches))
+        Some(matches)
+    }
+}
+
+/// Compiles a shell glob.
+///
+/// `*` and `?` do not cross `/`, while `**` matches any number of directories.
+///
+/// # Arguments
+///
+/// * `pattern` - The glob pattern
+/// * `case_sensitive` - Whether the match should be case-sensitive
+///
+/// # Returns
+///
+/// * `Ok(GlobMatcher)` - The compiled glob
+/// * `Err(String)` - If the glob is invalid
+fn compile_glob(pattern: &str, case_sensitive: bool) -> Result<GlobMatcher, String> {
+    Ok(GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| e.to_string())?
+        .compile_matcher())
+}
+
+/// Match a name or path against a compiled glob.
+///
+/// A glob describes the whole text, so a match covers all of it.
+///
+/// # Arguments
+///
+/// * `text` - The name or path, with `/` separators
+/// * `glob` - The compiled glob
+///
+/// # Returns
+///
+/// * `Some(Vec<(usize, usize)>)` - Single range covering the text
+/// * `None` - If the glob does not match
+fn match_glob(text: &str, glob: &GlobMatcher) -> Option<Vec<(usize, usize)>> {
+    glob.is_match(text).then(|| vec![(0, text.chars().count())])
+}
+
+/// A search pattern compiled once and shared by every matching thread
+#[derive(Debug, Clone)]
+pub(crate) enum PatternMatcher {
+    Simple {
+        pattern: String,
+        case_sensitive: bool,
+    },
+    Extension {
+        extensions: String,
+        case_sensitive: bool,
+    },
+    Regex(Regex),
+    Glob(GlobMatcher),
+}
+
+impl PatternMatcher {
+    /// Compiles a pattern.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PatternMatcher)` - The compiled pattern
+    /// * `Err(String)` - If the pattern is empty or is an invalid regex or glob
+    pub(crate) fn compile(
+        pattern: &str,
+        pattern_type: &PatternType,
+        case_sensitive: bool,
+    ) -> Result<Self, String> {
+        if pattern.trim().is_empty() {
+            return Err("Pattern cannot be empty".to_string());
+        }
+
+        Ok(match pattern_type {
+            PatternType::Simple => PatternMatcher::Simple {
+                pattern: pattern.to_string(),
+                case_sensitive,
+            },
+            PatternType::Extension => PatternMatcher::Extension {
+                extensions: pattern.to_string(),
+                case_sensitive,
+            },
+            PatternType::Regex => PatternMatcher::Regex(compile_regex(pattern, case_sensitive)?),
+            PatternType::Glob => PatternMatcher::Glob(compile_glob(pattern, case_sensitive)?),
+        })
+    }
+
+    /// Finds the pattern in a name or path.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Vec<(usize, usize)>)` - The (start, end) character positions of
+    ///   the matches
+    /// * `None` - If the pattern does not match
+    pub(crate) fn find(&self, text: &str) -> Option<Vec<(usize, usize)>> {
+        match self {
+            PatternMatcher::Simple {
+                pattern,
+                case_sensitive,
+            } => match_simple(text, pattern, *case_sensitive),
+            PatternMatcher::Extension {
+                extensions,
+                case_sensitive,
+            } => match_extension(text, extensions, *case_sensitive),
+            PatternMatcher::Regex(regex) => match_regex(text, regex),
+            PatternMatcher::Glob(glob) => match_glob(text, glob),
+        }
     }
 }
 
+/// Compiles what a search matches. The pattern, the metadata filters and the
+/// filter expression must all match.
+///
+/// # Returns
+///
+/// * `Ok(SearchExpression)` - The compiled search
+/// * `Err(String)` - If there is neither a pattern nor an expression, or
+///   anything fails to compile
+fn compile_search(
+    pattern: Option<String>,
+    pattern_type: Option<PatternType>,
+    case_sensitive: bool,
+    match_target: Option<MatchTarget>,
+    filters: Option<SearchFilters>,
+    expression: Option<FilterExpression>,
+) -> Result<SearchExpression, String> {
+    if pattern.is_none() && expression.is_none() {
+        return Err("A search needs a pattern or a filter expression".to_string());
+    }
+
+    let mut parts = Vec::new();
+    if let Some(pattern) = pattern {
+        let pattern = FilterExpression::Pattern {
+            pattern,
+            pattern_type: pattern_type.unwrap_or_default(),
+            case_sensitive,
+            match_target,
+        };
+        parts.push(pattern.compile()?);
+    }
+    parts.push(filters.unwrap_or_default().compile()?.into());
+    if let Some(expression) = expression {
+        parts.push(expression.compile()?);
+    }
+
+    Ok(SearchExpression::all(parts))
+}
+
 // ==================== Tauri Commands ====================
 
-/// Searches for files matching a pattern in a directory.
+/// Searches for files matching a pattern or filter expression in a directory.
 ///
-/// Supports three pattern matching modes:
+/// Supports four pattern matching modes:
 /// - Simple: Substring matching
 /// - Extension: Match by file extension (comma-separated list)
 /// - Regex: Regular expression matching
+/// - Glob: Shell glob matching
+///
+/// The pattern is matched against the file name, the path relative to
+/// `base_path` or the full path. Without an explicit target, a glob
+/// containing `/` is matched against the relative path and any other
+/// pattern against the file name.
+///
+/// A filter expression combines patterns, size, time and entry type tests
+/// with `and`, `or` and `not`. When a pattern, filters and an expression are
+/// all given, a file must match each of them.
 ///
 /// # Arguments
 ///
 /// * `base_path` - The directory to search in
-/// * `pattern` - The pattern to match against filenames
-/// * `pattern_type` - The type of pattern matching to use
+/// * `pattern` - The pattern to match
+/// * `pattern_type` - The type of pattern matching to use (default simple)
 /// * `include_subdirs` - Whether to search subdirectories
-/// * `case_sensitive` - Whether the search should be case-sensitive
+/// * `case_sensitive` - Whether the pattern should be case-sensitive
+/// * `match_target` - What the pattern is matched against
+/// * `filters` - Metadata filters such as size bounds
+/// * `expression` - A filter expression
 ///
 /// # Returns
 ///
 /// * `Ok(Vec<FileMatchResult>)` - List of matching files with match details
-/// * `Err(String)` - Error message if search fails
+/// * `Err(String)` - Error message if search fails, there is neither a
+///   pattern nor an expression, or a pattern or filter is invalid
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn search_files_by_pattern(
     base_path: String,
-    pattern: String,
-    pattern_type: PatternType,
+    pattern: Option<String>,
+    pattern_type: Option<PatternType>,
     include_subdirs: bool,
     case_sensitive: bool,
+    match_target: Option<MatchTarget>,
+    filters: Option<SearchFilters>,
+    expression: Option<FilterExpression>,
 ) -> Result<Vec<FileMatchResult>, String> {
-    if pattern.trim().is_empty() {
-        return Err("Pattern cannot be empty".to_string());
-    }
+    let search = compile_search(
+        pattern,
+        pattern_type,
+        case_sensitive,
+        match_target,
+        filters,
+        expression,
+    )?;
 
     let mut results = Vec::new();
 
@@ -294,34 +1081,27 @@ pub fn search_files_by_pattern(
             continue;
         }
 
-        let name = match path.file_name() {
-            Some(n) => n.to_string_lossy().to_string(),
-            None => continue,
-        };
-
-        let match_ranges = match &pattern_type {
-            PatternType::Simple => match_simple(&name, &pattern, case_sensitive),
-            PatternType::Extension => match_extension(&name, &pattern, case_sensitive),
-            PatternType::Regex => match_regex(&name, &pattern, case_sensitive)?,
+        let Some(candidate) = Candidate::new(path, Path::new(&base_path)) else {
+            continue;
         };
 
-        if let Some(ranges) = match_ranges {
-            let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
-
-            results.push(FileMatchResult {
-                path: path.to_string_lossy().to_string(),
-                name,
-                match_ranges: ranges,
-                size: metadata.len(),
-                is_directory: metadata.is_dir(),
-            });
+        if search.matches(&candidate) {
+            let (target, ranges) = search.highlight(&candidate);
+            let metadata = candidate.metadata()?;
+            results.push(FileMatchResult::new(
+                path,
+                candidate.name().to_string(),
+                ranges,
+                target,
+                metadata,
+            ));
         }
     }
 
     Ok(results)
 }
 
-/// Searches for files matching a pattern with progress streaming.
+/// Searches for files matching a pattern or filter expression with progress streaming.
 ///
 /// This is the streaming variant of `search_files_by_pattern` that sends
 /// progress updates via a Tauri Channel. Use this for large directories
@@ -334,28 +1114,48 @@ pub fn search_files_by_pattern(
 /// # Arguments
 ///
 /// * `base_path` - The directory to search in
-/// * `pattern` - The pattern to match against filenames
-/// * `pattern_type` - The type of pattern matching to use
+/// * `pattern` - The pattern to match
+/// * `pattern_type` - The type of pattern matching to use (default simple)
 /// * `include_subdirs` - Whether to search subdirectories
-/// * `case_sensitive` - Whether the search should be case-sensitive
+/// * `case_sensitive` - Whether the pattern should be case-sensitive
+/// * `match_target` - What the pattern is matched against
+/// * `filters` - Metadata filters such as size bounds, checked while matching
+/// * `expression` - A filter expression, evaluated while matching
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `operations` - The operation registry managed by Tauri
 /// * `on_progress` - Channel to send progress events
 ///
 /// # Returns
 ///
 /// * `Ok(Vec<FileMatchResult>)` - List of matching files with match details
-/// * `Err(String)` - Error message if search fails
+/// * `Err(String)` - Error message if search fails, there is neither a
+///   pattern nor an expression, a pattern or filter is invalid, or the
+///   search is cancelled
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn search_files_with_progress(
     base_path: String,
-    pattern: String,
-    pattern_type: PatternType,
+    pattern: Option<String>,
+    pattern_type: Option<PatternType>,
     include_subdirs: bool,
     case_sensitive: bool,
+    match_target: Option<MatchTarget>,
+    filters: Option<SearchFilters>,
+    expression: Option<FilterExpression>,
+    operation_id: Option<u64>,
+    operations: State<'_, OperationRegistry>,
     on_progress: Channel<SearchProgress>,
 ) -> Result<Vec<FileMatchResult>, String> {
-    if pattern.trim().is_empty() {
-        return Err("Pattern cannot be empty".to_string());
-    }
+    let operation = operations.begin(operation_id);
+
+    let search = compile_search(
+        pattern,
+        pattern_type,
+        case_sensitive,
+        match_target,
+        filters,
+        expression,
+    )?;
 
     // Run the heavy work in a blocking thread to keep the main thread responsive
     tokio::task::spawn_blocking(move || {
@@ -376,6 +1176,14 @@ pub async fn search_files_with_progress(
         };
 
         for entry in walker.into_iter().filter_map(|e| e.ok()) {
+            if operation.is_cancelled() {
+                let _ = on_progress.send(SearchProgress::Cancelled {
+                    files_found: all_files.len(),
+                    matches_found: 0,
+                });
+                return Err(CANCELLED_ERROR.to_string());
+            }
+
             let path = entry.path();
 
             // Skip the base directory itself
@@ -384,7 +1192,7 @@ pub async fn search_files_with_progress(
             }
 
             // Send scanning progress periodically
-            if all_files.len() % progress_interval == 0 {
+            if all_files.len().is_multiple_of(progress_interval) {
                 if let Some(parent) = path.parent() {
                     let current_dir = parent.to_string_lossy().to_string();
                     if current_dir != last_progress_dir {
@@ -405,63 +1213,42 @@ pub async fn search_files_with_progress(
             total_files: all_files.len(),
         });
 
-        // Phase 2: Pattern matching with Rayon parallelization
-        // Pre-compile regex if needed for thread-safe sharing
-        let compiled_regex = if pattern_type == PatternType::Regex {
-            let regex_pattern = if case_sensitive {
-                regex::Regex::new(&pattern)
-            } else {
-                regex::Regex::new(&format!("(?i){}", pattern))
-            }
-            .map_err(|e| e.to_string())?;
-            Some(regex_pattern)
-        } else {
-            None
-        };
-
+        // Phase 2: Pattern matching with Rayon parallelization; the compiled
+        // search is shared read-only between threads
         let results: Vec<FileMatchResult> = all_files
             .par_iter()
             .filter_map(|entry| {
+                // Remaining files are skipped once cancelled
+                if operation.is_cancelled() {
+                    return None;
+                }
+
                 let path = entry.path();
-                let name = path.file_name()?.to_string_lossy().to_string();
-
-                let match_ranges = match &pattern_type {
-                    PatternType::Simple => match_simple(&name, &pattern, case_sensitive),
-                    PatternType::Extension => match_extension(&name, &pattern, case_sensitive),
-                    PatternType::Regex => {
-                        // Use pre-compiled regex for thread safety
-                        if let Some(ref regex) = compiled_regex {
-                            let matches: Vec<(usize, usize)> = regex
-                                .find_iter(&name)
-                                .map(|m| (m.start(), m.end()))
-                                .collect();
-                            if matches.is_empty() {
-                                None
-                            } else {
-                                Some(matches)
-                            }
-                        } else {
-                            None
-                        }
-                    }
-                };
-
-                if let Some(ranges) = match_ranges {
-                    let metadata = fs::metadata(path).ok()?;
-
-                    Some(FileMatchResult {
-                        path: path.to_string_lossy().to_string(),
-                        name,
-                        match_ranges: ranges,
-                        size: metadata.len(),
-                        is_directory: metadata.is_dir(),
-                    })
-                } else {
-                    None
+                let candidate = Candidate::new(path, Path::new(&base_path))?;
+                if !search.matches(&candidate) {
+                    return None;
                 }
+
+                let (target, ranges) = search.highlight(&candidate);
+                let metadata = candidate.metadata().ok()?;
+                Some(FileMatchResult::new(
+                    path,
+                    candidate.name().to_string(),
+                    ranges,
+                    target,
+                    metadata,
+                ))
             })
             .collect();
 
+        if operation.is_cancelled() {
+            let _ = on_progress.send(SearchProgress::Cancelled {
+                files_found: all_files.len(),
+                matches_found: results.len(),
+            });
+            return Err(CANCELLED_ERROR.to_string());
+        }
+
         // Send completed event
         let _ = on_progress.send(SearchProgress::Completed {
             matches_found: results.len(),
@@ -475,75 +1262,67 @@ pub async fn search_files_with_progress(
 
 /// Deletes multiple files and optionally cleans up empty directories.
 ///
+/// With `dry_run`, runs the same checks without touching the disk: whether
+/// each path exists, whether its parent directory allows removing entries,
+/// how many entries each directory would take with it, and which parent
+/// directories the cleanup would remove.
+///
 /// # Arguments
 ///
 /// * `files` - List of file paths to delete
-/// * `delete_empty_dirs` - Whether to remove parent directories that become empty
+/// * `delete_empty_dirs` - Whether to remove parent directories that become
+///   empty, walking upward until a directory is not empty or `base_path` is reached
+/// * `base_path` - The search's base path, which the cleanup never removes or
+///   climbs above (default `root`)
+/// * `mode` - Whether to delete permanently (default), move to the trash, or
+///   move to the quarantine
+/// * `dry_run` - Only report what would happen, without touching the disk
+/// * `root` - Root from `choose_operation_root`; paths outside it are refused
+/// * `quarantine` - The delete quarantine managed by Tauri
+/// * `protected_paths` - The protected-path settings managed by Tauri
+/// * `roots` - The approved roots managed by Tauri
 ///
 /// # Returns
 ///
-/// * `Ok(DeleteResult)` - Result containing successful/failed deletions and cleaned dirs
-/// * `Err(String)` - Error message if operation completely fails
+/// * `Ok(DeleteResult)` - Result containing successful/failed deletions, cleaned dirs,
+///   where moved items went and the quarantine batch id
+/// * `Err(String)` - If `root` is not an approved root, or `base_path` is
+///   outside it
 #[tauri::command]
-pub fn batch_delete(files: Vec<String>, delete_empty_dirs: bool) -> Result<DeleteResult, String> {
-    let mut successful = Vec::new();
-    let mut failed = Vec::new();
-    let mut deleted_dirs = Vec::new();
-    let mut parent_dirs: HashSet<String> = HashSet::new();
-
-    for file_path in files {
-        let path = Path::new(&file_path);
-
-        // Track parent directory for potential cleanup
-        if delete_empty_dirs {
-            if let Some(parent) = path.parent() {
-                parent_dirs.insert(parent.to_string_lossy().to_string());
-            }
-        }
-
-        // Attempt deletion
-        let result = if path.is_dir() {
-            fs::remove_dir_all(path)
-        } else {
-            fs::remove_file(path)
-        };
-
-        match result {
-            Ok(_) => successful.push(file_path),
-            Err(e) => failed.push((file_path, e.to_string())),
-        }
-    }
-
-    // Clean up empty directories if requested
-    if delete_empty_dirs {
-        // Sort by depth (deepest first) to handle nested empty dirs
-        let mut dirs: Vec<_> = parent_dirs.into_iter().collect();
-        dirs.sort_by(|a, b| {
-            b.matches(std::path::MAIN_SEPARATOR)
-                .count()
-                .cmp(&a.matches(std::path::MAIN_SEPARATOR).count())
-        });
-
-        for dir in dirs {
-            let path = Path::new(&dir);
-            if path.exists() && path.is_dir() {
-                if let Ok(mut entries) = fs::read_dir(path) {
-                    if entries.next().is_none() {
-                        // Directory is empty
-                        if fs::remove_dir(path).is_ok() {
-                            deleted_dirs.push(dir);
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(DeleteResult {
-        successful,
-        failed,
-        deleted_dirs,
-    })
+#[allow(clippy::too_many_arguments)]
+pub fn batch_delete(
+    files: Vec<String>,
+    delete_empty_dirs: bool,
+    base_path: Option<String>,
+    mode: Option<DeleteMode>,
+    dry_run: Option<bool>,
+    root: String,
+    quarantine: State<'_, Quarantine>,
+    protected_paths: State<'_, ProtectedPaths>,
+    roots: State<'_, ApprovedRoots>,
+) -> Result<DeleteResult, String> {
+    let policy = protected_paths.policy().within_root(roots.resolve(&root)?);
+    let cleanup_base = cleanup_base(base_path.as_deref(), &policy)?;
+
+    if dry_run.unwrap_or(false) {
+        return Ok(plan_batch_delete(
+            files,
+            delete_empty_dirs,
+            cleanup_base.as_deref(),
+            &policy,
+        ));
+    }
+
+    Ok(run_batch_delete(
+        files,
+        delete_empty_dirs,
+        cleanup_base.as_deref(),
+        mode.unwrap_or_default(),
+        &policy,
+        Some(&quarantine),
+        &CancellationToken::default(),
+        |_, _| {},
+    ))
 }
 
 /// Deletes multiple files with progress streaming.
@@ -555,22 +1334,54 @@ pub fn batch_delete(files: Vec<String>, delete_empty_dirs: bool) -> Result<Delet
 /// main Tauri thread to remain responsive and deliver progress events
 /// to the frontend in real-time.
 ///
+/// When started with an `operation_id`, the deletion can be stopped with
+/// `cancel_operation`. Files not yet reached are left in place, no empty
+/// directories are removed, and the result lists what was deleted so far.
+///
 /// # Arguments
 ///
 /// * `files` - List of file paths to delete
-/// * `delete_empty_dirs` - Whether to remove parent directories that become empty
+/// * `delete_empty_dirs` - Whether to remove parent directories that become
+///   empty, walking upward until a directory is not empty or `base_path` is reached
+/// * `base_path` - The search's base path, which the cleanup never removes or
+///   climbs above (default `root`)
+/// * `mode` - Whether to delete permanently (default), move to the trash, or
+///   move to the quarantine
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `root` - Root from `choose_operation_root`; paths outside it are refused
+/// * `quarantine` - The delete quarantine managed by Tauri
+/// * `protected_paths` - The protected-path settings managed by Tauri
+/// * `roots` - The approved roots managed by Tauri
+/// * `operations` - The operation registry managed by Tauri
 /// * `on_progress` - Channel to send progress events
 ///
 /// # Returns
 ///
-/// * `Ok(DeleteResult)` - Result containing successful/failed deletions and cleaned dirs
-/// * `Err(String)` - Error message if operation completely fails
+/// * `Ok(DeleteResult)` - Result containing successful/failed deletions, cleaned dirs,
+///   where moved items went and the quarantine batch id
+/// * `Err(String)` - If `root` is not an approved root, or `base_path` is
+///   outside it
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn batch_delete_with_progress(
     files: Vec<String>,
     delete_empty_dirs: bool,
+    base_path: Option<String>,
+    mode: Option<DeleteMode>,
+    operation_id: Option<u64>,
+    root: String,
+    quarantine: State<'_, Quarantine>,
+    protected_paths: State<'_, ProtectedPaths>,
+    roots: State<'_, ApprovedRoots>,
+    operations: State<'_, OperationRegistry>,
     on_progress: Channel<DeleteProgress>,
 ) -> Result<DeleteResult, String> {
+    let policy = protected_paths.policy().within_root(roots.resolve(&root)?);
+    let cleanup_base = cleanup_base(base_path.as_deref(), &policy)?;
+    let operation = operations.begin(operation_id);
+    let mode = mode.unwrap_or_default();
+    let quarantine = quarantine.inner().clone();
+
     // Run the heavy work in a blocking thread to keep the main thread responsive
     tokio::task::spawn_blocking(move || {
         let total = files.len();
@@ -578,77 +1389,39 @@ pub async fn batch_delete_with_progress(
         // Send started event
         let _ = on_progress.send(DeleteProgress::Started { total_files: total });
 
-        let mut successful = Vec::new();
-        let mut failed = Vec::new();
-        let mut deleted_dirs = Vec::new();
-        let mut parent_dirs: HashSet<String> = HashSet::new();
-
-        for (index, file_path) in files.into_iter().enumerate() {
-            let path = Path::new(&file_path);
-
-            // Track parent directory for potential cleanup
-            if delete_empty_dirs {
-                if let Some(parent) = path.parent() {
-                    parent_dirs.insert(parent.to_string_lossy().to_string());
-                }
-            }
+        let result = run_batch_delete(
+            files,
+            delete_empty_dirs,
+            cleanup_base.as_deref(),
+            mode,
+            &policy,
+            Some(&quarantine),
+            operation.token(),
+            |index, path| {
+                // Send progress update
+                let _ = on_progress.send(DeleteProgress::Progress {
+                    current: index + 1,
+                    total,
+                    current_path: path.to_string(),
+                });
+            },
+        );
 
-            // Attempt deletion
-            let result = if path.is_dir() {
-                fs::remove_dir_all(path)
-            } else {
-                fs::remove_file(path)
-            };
+        let successful = result.successful.len();
+        let failed = result.failed.len();
 
-            // Send progress update
-            let _ = on_progress.send(DeleteProgress::Progress {
-                current: index + 1,
+        if successful + failed < total {
+            let _ = on_progress.send(DeleteProgress::Cancelled {
+                successful,
+                failed,
                 total,
-                current_path: file_path.clone(),
             });
-
-            match result {
-                Ok(_) => successful.push(file_path),
-                Err(e) => failed.push((file_path, e.to_string())),
-            }
-        }
-
-        // Clean up empty directories if requested
-        if delete_empty_dirs {
-            // Sort by depth (deepest first) to handle nested empty dirs
-            let mut dirs: Vec<_> = parent_dirs.into_iter().collect();
-            dirs.sort_by(|a, b| {
-                b.matches(std::path::MAIN_SEPARATOR)
-                    .count()
-                    .cmp(&a.matches(std::path::MAIN_SEPARATOR).count())
-            });
-
-            for dir in dirs {
-                let path = Path::new(&dir);
-                if path.exists() && path.is_dir() {
-                    if let Ok(mut entries) = fs::read_dir(path) {
-                        if entries.next().is_none() {
-                            // Directory is empty
-                            if fs::remove_dir(path).is_ok() {
-                                deleted_dirs.push(dir);
-                            }
-                        }
-                    }
-                }
-            }
+        } else {
+            // Send completed event
+            let _ = on_progress.send(DeleteProgress::Completed { successful, failed });
         }
 
-        // Send completed event
-        let _ = on_progress.send(DeleteProgress::Completed {
-            successful: successful.len(),
-            failed: failed.len(),
-        });
-
-        Ok(DeleteResult {
-            successful,
-            failed,
-            deleted_dirs,
-        })
+        Ok(result)
     })
     .await
     .map_err(|e| format!("Task failed: {}", e))?
@@ -657,10 +1430,39 @@ pub async fn batch_delete_with_progress(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json;
     use std::fs::File;
     use tempfile::tempdir;
 
+    /// Runs a batch delete the way the `batch_delete` command does, without a quarantine.
+    fn batch_delete(
+        files: Vec<String>,
+        delete_empty_dirs: bool,
+        mode: Option<DeleteMode>,
+    ) -> Result<DeleteResult, String> {
+        Ok(run_batch_delete(
+            files,
+            delete_empty_dirs,
+            None,
+            mode.unwrap_or_default(),
+            &ProtectedPathPolicy::default(),
+            None,
+            &CancellationToken::default(),
+            |_, _| {},
+        ))
+    }
+
+    /// Compiles and matches a regex the way a search does.
+    fn match_regex(
+        name: &str,
+        pattern: &str,
+        case_sensitive: bool,
+    ) -> Result<Option<Vec<(usize, usize)>>, String> {
+        Ok(super::match_regex(
+            name,
+            &compile_regex(pattern, case_sensitive)?,
+        ))
+    }
+
     // ==================== Pattern Matching Tests ====================
 
     /// Tests simple substring matching (case insensitive).
@@ -776,20 +1578,122 @@ mod tests {
         assert_eq!(result.unwrap(), None);
     }
 
-    /// Tests regex matching with multiple matches.
+    /// Tests regex matching with multiple matches.
+    #[test]
+    fn test_match_regex_multiple_matches() {
+        let result = match_regex("abc123def456.txt", r"\d+", false);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), Some(vec![(3, 6), (9, 12)]));
+    }
+
+    /// Tests regex returns None when no match.
+    #[test]
+    fn test_match_regex_no_match() {
+        let result = match_regex("abcdef.txt", r"\d+", false);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), None);
+    }
+
+    /// Tests name globs with wildcards, classes and alternatives.
+    #[test]
+    fn test_match_glob_name() {
+        let matches = |pattern: &str, name: &str| {
+            let glob = compile_glob(pattern, false).unwrap();
+            match_glob(name, &glob)
+        };
+
+        assert_eq!(matches("*.log", "app.log"), Some(vec![(0, 7)]));
+        assert_eq!(matches("file?.txt", "file1.txt"), Some(vec![(0, 9)]));
+        assert_eq!(matches("file[ab].txt", "fileb.txt"), Some(vec![(0, 9)]));
+        assert_eq!(matches("*.{jpg,png}", "photo.PNG"), Some(vec![(0, 9)]));
+        assert_eq!(matches("*.log", "app.log.gz"), None);
+        assert_eq!(matches("file[ab].txt", "filec.txt"), None);
+    }
+
+    /// Tests globs match paths, with `**` crossing directories.
+    #[test]
+    fn test_match_glob_relative_path() {
+        let glob = compile_glob("**/cache/*.tmp", true).unwrap();
+        assert_eq!(match_glob("build/cache/a.tmp", &glob), Some(vec![(0, 17)]));
+        assert_eq!(match_glob("cache/a.tmp", &glob), Some(vec![(0, 11)]));
+
+        // `*` does not cross directories
+        let glob = compile_glob("build/*.tmp", true).unwrap();
+        assert_eq!(match_glob("build/cache/a.tmp", &glob), None);
+
+        // Case sensitivity applies to globs
+        let glob = compile_glob("BUILD/**", true).unwrap();
+        assert_eq!(match_glob("build/cache/a.tmp", &glob), None);
+    }
+
+    /// Tests match ranges count characters, not bytes, in multibyte names.
+    #[test]
+    fn test_match_ranges_use_character_offsets() {
+        assert_eq!(
+            match_simple("Ürlaub_über.txt", "ÜBER", false),
+            Some(vec![(7, 11)])
+        );
+        assert_eq!(
+            match_simple("ééé", "é", true),
+            Some(vec![(0, 1), (1, 2), (2, 3)])
+        );
+        assert_eq!(
+            match_extension("café.tXt", "txt", false),
+            Some(vec![(4, 8)])
+        );
+
+        assert_eq!(
+            match_regex("日本_2024.jpg", r"\d+", false).unwrap(),
+            Some(vec![(3, 7)])
+        );
+
+        let glob = compile_glob("*.jpg", false).unwrap();
+        assert_eq!(match_glob("日本.jpg", &glob), Some(vec![(0, 6)]));
+    }
+
+    /// Tests the text each match target selects, with `/` separators.
+    #[test]
+    fn test_match_target_text() {
+        let base = Path::new("/data");
+        let path = base.join("build").join("cache").join("a.tmp");
+
+        assert_eq!(MatchTarget::Name.text(&path, base, "a.tmp"), "a.tmp");
+        assert_eq!(
+            MatchTarget::RelativePath.text(&path, base, "a.tmp"),
+            "build/cache/a.tmp"
+        );
+        assert_eq!(
+            MatchTarget::FullPath.text(&path, base, "a.tmp"),
+            path.to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/")
+        );
+    }
+
+    /// Tests only globs containing `/` default to the relative path.
     #[test]
-    fn test_match_regex_multiple_matches() {
-        let result = match_regex("abc123def456.txt", r"\d+", false);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), Some(vec![(3, 6), (9, 12)]));
+    fn test_match_target_resolve() {
+        assert_eq!(
+            MatchTarget::resolve(None, &PatternType::Glob, "build/*.o"),
+            MatchTarget::RelativePath
+        );
+        assert_eq!(
+            MatchTarget::resolve(None, &PatternType::Glob, "*.o"),
+            MatchTarget::Name
+        );
+        assert_eq!(
+            MatchTarget::resolve(None, &PatternType::Regex, r"build/.*\.o"),
+            MatchTarget::Name
+        );
+        assert_eq!(
+            MatchTarget::resolve(Some(MatchTarget::FullPath), &PatternType::Glob, "build/*.o"),
+            MatchTarget::FullPath
+        );
     }
 
-    /// Tests regex returns None when no match.
+    /// Tests an invalid glob is reported as an error.
     #[test]
-    fn test_match_regex_no_match() {
-        let result = match_regex("abcdef.txt", r"\d+", false);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), None);
+    fn test_compile_glob_invalid() {
+        assert!(compile_glob("[abc", false).is_err());
     }
 
     // ==================== search_files_by_pattern Tests ====================
@@ -818,10 +1722,13 @@ mod tests {
         let dir = setup_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            "file".to_string(),
-            PatternType::Simple,
+            Some("file".to_string()),
+            Some(PatternType::Simple),
             false,
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -835,10 +1742,13 @@ mod tests {
         let dir = setup_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            ".txt".to_string(),
-            PatternType::Extension,
+            Some(".txt".to_string()),
+            Some(PatternType::Extension),
             true, // include subdirs
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -852,10 +1762,13 @@ mod tests {
         let dir = setup_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            ".txt".to_string(),
-            PatternType::Extension,
+            Some(".txt".to_string()),
+            Some(PatternType::Extension),
             false, // exclude subdirs
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -869,10 +1782,13 @@ mod tests {
         let dir = setup_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            r"file\d".to_string(),
-            PatternType::Regex,
+            Some(r"file\d".to_string()),
+            Some(PatternType::Regex),
             false,
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -885,41 +1801,245 @@ mod tests {
         let dir = setup_test_directory();
         let result = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            "".to_string(),
-            PatternType::Simple,
+            Some("".to_string()),
+            Some(PatternType::Simple),
             false,
             false,
+            None,
+            None,
+            None,
         );
 
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Pattern cannot be empty");
     }
 
+    /// Tests searching with a glob across subdirectories.
+    #[test]
+    fn test_search_files_glob_pattern() {
+        let dir = setup_test_directory();
+        let search = |pattern: &str| {
+            search_files_by_pattern(
+                dir.path().to_string_lossy().to_string(),
+                Some(pattern.to_string()),
+                Some(PatternType::Glob),
+                true,
+                false,
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        // file1.txt, file2.txt, nested.txt
+        assert_eq!(search("*.txt").len(), 3);
+
+        let results = search("subdir/**/*.txt");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "nested.txt");
+        // The match covers the relative path "subdir/nested.txt"
+        assert_eq!(results[0].match_target, MatchTarget::RelativePath);
+        assert_eq!(results[0].match_ranges, vec![(0, 17)]);
+    }
+
+    /// Tests patterns matched against the relative and full path.
+    #[test]
+    fn test_search_files_match_target() {
+        let dir = setup_test_directory();
+        let search = |pattern: &str, match_target: MatchTarget| {
+            search_files_by_pattern(
+                dir.path().to_string_lossy().to_string(),
+                Some(pattern.to_string()),
+                Some(PatternType::Regex),
+                true,
+                false,
+                Some(match_target),
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        // Directory names are only seen in paths
+        assert!(search(r"^subdir/.*\.txt$", MatchTarget::Name).is_empty());
+        let results = search(r"^subdir/.*\.txt$", MatchTarget::RelativePath);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "nested.txt");
+        assert_eq!(results[0].match_ranges, vec![(0, 17)]);
+
+        // Ranges are positions in the full path
+        let results = search("nested", MatchTarget::FullPath);
+        assert_eq!(results.len(), 1);
+        let (start, end) = results[0].match_ranges[0];
+        assert_eq!(&results[0].path[start..end], "nested");
+    }
+
+    /// Tests a filter expression alone, and combined with a pattern.
+    #[test]
+    fn test_search_files_expression() {
+        let dir = setup_test_directory();
+        let search = |pattern: Option<&str>, expression: &str| {
+            search_files_by_pattern(
+                dir.path().to_string_lossy().to_string(),
+                pattern.map(str::to_string),
+                Some(PatternType::Simple),
+                true,
+                false,
+                None,
+                None,
+                Some(serde_json::from_str(expression).unwrap()),
+            )
+            .unwrap()
+        };
+        let not_in_subdir = r#"{
+            "type": "and",
+            "children": [
+                { "type": "pattern", "pattern": "*.txt", "patternType": "glob" },
+                { "type": "not", "child":
+                    { "type": "pattern", "pattern": "subdir/**", "patternType": "glob" } }
+            ]
+        }"#;
+
+        // file1.txt and file2.txt, highlighted by the first pattern
+        let results = search(None, not_in_subdir);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.match_ranges == vec![(0, 9)]));
+
+        // The pattern must match as well, and supplies the highlight
+        let results = search(Some("2"), not_in_subdir);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "file2.txt");
+        assert_eq!(results[0].match_ranges, vec![(4, 5)]);
+    }
+
+    /// Tests a search without a pattern or expression is refused.
+    #[test]
+    fn test_search_files_requires_pattern_or_expression() {
+        let dir = setup_test_directory();
+        let result = search_files_by_pattern(
+            dir.path().to_string_lossy().to_string(),
+            None,
+            None,
+            false,
+            false,
+            None,
+            None,
+            None,
+        );
+
+        assert_eq!(
+            result.unwrap_err(),
+            "A search needs a pattern or a filter expression"
+        );
+    }
+
     /// Tests searching with invalid regex returns error.
     #[test]
     fn test_search_files_invalid_regex() {
         let dir = setup_test_directory();
         let result = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            "[invalid".to_string(),
-            PatternType::Regex,
+            Some("[invalid".to_string()),
+            Some(PatternType::Regex),
             false,
             false,
+            None,
+            None,
+            None,
         );
 
         assert!(result.is_err());
     }
 
+    /// Tests size bounds filter matches and invalid sizes fail the search.
+    #[test]
+    fn test_search_files_size_filter() {
+        let dir = setup_test_directory();
+        fs::write(dir.path().join("big.bin"), vec![0u8; 2048]).unwrap();
+        fs::write(dir.path().join("small.bin"), vec![0u8; 10]).unwrap();
+        let search = |min_size: Option<&str>, max_size: Option<&str>| {
+            search_files_by_pattern(
+                dir.path().to_string_lossy().to_string(),
+                Some(".bin".to_string()),
+                Some(PatternType::Extension),
+                false,
+                false,
+                None,
+                Some(SearchFilters {
+                    min_size: min_size.map(str::to_string),
+                    max_size: max_size.map(str::to_string),
+                    ..Default::default()
+                }),
+                None,
+            )
+        };
+
+        let results = search(Some("1 KiB"), None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "big.bin");
+
+        let results = search(None, Some("1KB")).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "small.bin");
+
+        assert!(search(Some("lots"), None).is_err());
+    }
+
+    /// Tests modification time bounds filter search matches.
+    #[test]
+    fn test_search_files_time_filter() {
+        use crate::search_filters::{TimeBound, TimeRange};
+
+        let dir = setup_test_directory();
+        let file1 = dir.path().join("file1.txt");
+        let week = std::time::Duration::from_secs(7 * 24 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(&file1)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - week)
+            .unwrap();
+
+        let results = search_files_by_pattern(
+            dir.path().to_string_lossy().to_string(),
+            Some("file".to_string()),
+            Some(PatternType::Simple),
+            false,
+            false,
+            None,
+            Some(SearchFilters {
+                modified: Some(TimeRange {
+                    before: Some(TimeBound::Ago {
+                        duration: "3d".to_string(),
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "file1.txt");
+        let age = crate::journal::now_ms() - results[0].modified_ms.unwrap();
+        assert!(age >= week.as_millis() as u64);
+    }
+
     /// Tests FileMatchResult contains correct metadata.
     #[test]
     fn test_search_files_returns_metadata() {
         let dir = setup_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            "file1".to_string(),
-            PatternType::Simple,
+            Some("file1".to_string()),
+            Some(PatternType::Simple),
             false,
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -929,6 +2049,7 @@ mod tests {
         assert!(result.path.ends_with("file1.txt"));
         assert!(!result.is_directory);
         assert!(!result.match_ranges.is_empty());
+        assert!(result.modified_ms.is_some());
     }
 
     // ==================== batch_delete Tests ====================
@@ -939,7 +2060,8 @@ mod tests {
         let dir = setup_test_directory();
         let file_path = dir.path().join("temp.tmp");
 
-        let result = batch_delete(vec![file_path.to_string_lossy().to_string()], false).unwrap();
+        let result =
+            batch_delete(vec![file_path.to_string_lossy().to_string()], false, None).unwrap();
 
         assert_eq!(result.successful.len(), 1);
         assert!(result.failed.is_empty());
@@ -959,6 +2081,7 @@ mod tests {
                 file2.to_string_lossy().to_string(),
             ],
             false,
+            None,
         )
         .unwrap();
 
@@ -978,6 +2101,7 @@ mod tests {
         let result = batch_delete(
             vec![nested_file.to_string_lossy().to_string()],
             true, // delete empty dirs
+            None,
         )
         .unwrap();
 
@@ -991,7 +2115,8 @@ mod tests {
     /// Tests deletion of nonexistent file returns failure.
     #[test]
     fn test_batch_delete_nonexistent_file() {
-        let result = batch_delete(vec!["/nonexistent/path/file.txt".to_string()], false).unwrap();
+        let result =
+            batch_delete(vec!["/nonexistent/path/file.txt".to_string()], false, None).unwrap();
 
         assert!(result.successful.is_empty());
         assert_eq!(result.failed.len(), 1);
@@ -1009,6 +2134,7 @@ mod tests {
                 "/nonexistent/file.txt".to_string(),
             ],
             false,
+            None,
         )
         .unwrap();
 
@@ -1023,7 +2149,7 @@ mod tests {
         let dir = setup_test_directory();
         let subdir = dir.path().join("subdir");
 
-        let result = batch_delete(vec![subdir.to_string_lossy().to_string()], false).unwrap();
+        let result = batch_delete(vec![subdir.to_string_lossy().to_string()], false, None).unwrap();
 
         assert_eq!(result.successful.len(), 1);
         assert!(!subdir.exists());
@@ -1032,13 +2158,448 @@ mod tests {
     /// Tests deletion with empty file list.
     #[test]
     fn test_batch_delete_empty_list() {
-        let result = batch_delete(vec![], false).unwrap();
+        let result = batch_delete(vec![], false, None).unwrap();
 
         assert!(result.successful.is_empty());
         assert!(result.failed.is_empty());
         assert!(result.deleted_dirs.is_empty());
     }
 
+    /// Tests permanent deletion reports no destinations.
+    #[test]
+    fn test_batch_delete_permanent_has_no_destinations() {
+        let dir = setup_test_directory();
+        let file_path = dir.path().join("file1.txt");
+
+        let result = batch_delete(
+            vec![file_path.to_string_lossy().to_string()],
+            false,
+            Some(DeleteMode::Permanent),
+        )
+        .unwrap();
+
+        assert_eq!(result.successful.len(), 1);
+        assert!(result.destinations.is_empty());
+    }
+
+    /// Tests trash mode moves files into the home trash and records where they went.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_batch_delete_to_trash() {
+        let dir = setup_test_directory();
+        let data_home = tempdir().expect("Failed to create temp dir");
+        std::env::set_var("XDG_DATA_HOME", data_home.path());
+        let file_path = dir.path().join("file1.txt");
+
+        let result = batch_delete(
+            vec![file_path.to_string_lossy().to_string()],
+            false,
+            Some(DeleteMode::Trash),
+        )
+        .unwrap();
+
+        let trashed = data_home.path().join("Trash/files/file1.txt");
+        assert!(!file_path.exists());
+        assert!(trashed.exists());
+        assert!(data_home
+            .path()
+            .join("Trash/info/file1.txt.trashinfo")
+            .exists());
+        assert_eq!(
+            result.destinations,
+            vec![(
+                file_path.to_string_lossy().to_string(),
+                trashed.to_string_lossy().to_string()
+            )]
+        );
+    }
+
+    /// Tests quarantine mode moves files into one restorable batch.
+    #[test]
+    fn test_batch_delete_to_quarantine() {
+        let dir = setup_test_directory();
+        let quarantine = Quarantine::new(dir.path().join(".quarantine"));
+        let file1 = dir.path().join("file1.txt");
+        let nested = dir.path().join("subdir").join("nested.txt");
+
+        let result = run_batch_delete(
+            vec![
+                file1.to_string_lossy().to_string(),
+                nested.to_string_lossy().to_string(),
+            ],
+            true,
+            None,
+            DeleteMode::Quarantine,
+            &ProtectedPathPolicy::default(),
+            Some(&quarantine),
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert_eq!(result.successful.len(), 2);
+        assert_eq!(result.destinations.len(), 2);
+        assert!(!file1.exists());
+        assert!(!dir.path().join("subdir").exists());
+
+        let batch_id = result.batch_id.unwrap();
+        assert_eq!(quarantine.batches().unwrap()[0].entries.len(), 2);
+
+        quarantine.restore(&batch_id).unwrap();
+        assert!(file1.exists());
+        assert!(nested.exists());
+    }
+
+    /// Tests a quarantine batch that cannot be recorded is reported in the result.
+    #[test]
+    fn test_batch_delete_reports_quarantine_failure() {
+        let dir = setup_test_directory();
+        let quarantine = Quarantine::new(dir.path().join(".quarantine"));
+        let file1 = dir.path().join("file1.txt");
+        // The batch cannot be added to an unreadable manifest
+        fs::create_dir(dir.path().join(".quarantine")).unwrap();
+        fs::write(dir.path().join(".quarantine").join("manifest.json"), "{").unwrap();
+
+        let result = run_batch_delete(
+            vec![file1.to_string_lossy().to_string()],
+            false,
+            None,
+            DeleteMode::Quarantine,
+            &ProtectedPathPolicy::default(),
+            Some(&quarantine),
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert_eq!(result.successful.len(), 1);
+        assert_eq!(result.batch_id, None);
+        assert!(result
+            .quarantine_error
+            .unwrap()
+            .contains("cannot be restored"));
+        assert!(!file1.exists());
+    }
+
+    /// Tests quarantine mode fails per file when no quarantine is available.
+    #[test]
+    fn test_batch_delete_quarantine_unavailable() {
+        let dir = setup_test_directory();
+        let file_path = dir.path().join("file1.txt");
+
+        let result = batch_delete(
+            vec![file_path.to_string_lossy().to_string()],
+            false,
+            Some(DeleteMode::Quarantine),
+        )
+        .unwrap();
+
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.batch_id, None);
+        assert!(file_path.exists());
+    }
+
+    /// Tests a cancelled batch leaves the remaining files and empty directories alone.
+    #[test]
+    fn test_batch_delete_cancelled() {
+        let dir = setup_test_directory();
+        let nested = dir.path().join("subdir").join("nested.txt");
+        let file1 = dir.path().join("file1.txt");
+        let cancel = CancellationToken::default();
+
+        let result = run_batch_delete(
+            vec![
+                nested.to_string_lossy().to_string(),
+                file1.to_string_lossy().to_string(),
+            ],
+            true,
+            None,
+            DeleteMode::Permanent,
+            &ProtectedPathPolicy::default(),
+            None,
+            &cancel,
+            |_, _| cancel.cancel(),
+        );
+
+        assert_eq!(result.successful.len(), 1);
+        assert!(!nested.exists());
+        assert!(file1.exists());
+        // Cleanup is skipped once cancelled
+        assert!(result.deleted_dirs.is_empty());
+        assert!(dir.path().join("subdir").exists());
+    }
+
+    /// Tests a dry run reports what would happen without touching the disk.
+    #[test]
+    fn test_batch_delete_dry_run() {
+        let dir = setup_test_directory();
+        let file1 = dir.path().join("file1.txt");
+        let subdir = dir.path().join("subdir");
+        fs::create_dir(subdir.join("inner")).unwrap();
+        File::create(subdir.join("inner").join("deep.txt")).unwrap();
+
+        let result = plan_batch_delete(
+            vec![
+                file1.to_string_lossy().to_string(),
+                subdir.to_string_lossy().to_string(),
+                "/nonexistent/file.txt".to_string(),
+            ],
+            false,
+            None,
+            &ProtectedPathPolicy::default(),
+        );
+
+        assert!(result.dry_run);
+        assert_eq!(result.successful.len(), 2);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].0, "/nonexistent/file.txt");
+        // nested.txt, inner/ and inner/deep.txt
+        assert_eq!(
+            result.directory_entries,
+            vec![(subdir.to_string_lossy().to_string(), 3)]
+        );
+        assert!(file1.exists());
+        assert!(subdir.join("inner").join("deep.txt").exists());
+    }
+
+    /// Tests a dry run predicts which parent directories the cleanup would remove.
+    #[test]
+    fn test_batch_delete_dry_run_predicts_empty_dirs() {
+        let dir = setup_test_directory();
+        let subdir = dir.path().join("subdir");
+        let nested = subdir.join("nested.txt");
+
+        let result = plan_batch_delete(
+            vec![nested.to_string_lossy().to_string()],
+            true,
+            None,
+            &ProtectedPathPolicy::default(),
+        );
+        assert_eq!(
+            result.deleted_dirs,
+            vec![subdir.to_string_lossy().to_string()]
+        );
+        assert!(nested.exists());
+
+        // A directory that keeps other entries is not cleaned up
+        File::create(subdir.join("other.txt")).unwrap();
+        let result = plan_batch_delete(
+            vec![nested.to_string_lossy().to_string()],
+            true,
+            None,
+            &ProtectedPathPolicy::default(),
+        );
+        assert!(result.deleted_dirs.is_empty());
+
+        // The prediction matches the real cleanup
+        fs::remove_file(subdir.join("other.txt")).unwrap();
+        let planned = plan_batch_delete(
+            vec![nested.to_string_lossy().to_string()],
+            true,
+            None,
+            &ProtectedPathPolicy::default(),
+        );
+        let actual = batch_delete(vec![nested.to_string_lossy().to_string()], true, None).unwrap();
+        assert_eq!(planned.successful, actual.successful);
+        assert_eq!(planned.deleted_dirs, actual.deleted_dirs);
+        assert!(!actual.dry_run);
+    }
+
+    /// Tests protected paths are refused with a reason, in real and dry runs.
+    #[test]
+    fn test_batch_delete_protected_paths() {
+        let dir = setup_test_directory();
+        let subdir = dir.path().join("subdir");
+        let nested = subdir.join("nested.txt");
+        let file1 = dir.path().join("file1.txt");
+        let mut policy = ProtectedPathPolicy::default();
+        policy.add_tree(&subdir, ProtectionReason::UserDefined);
+
+        let files = vec![
+            file1.to_string_lossy().to_string(),
+            // Reaches the protected directory through `..`
+            dir.path()
+                .join("subdir/../subdir/nested.txt")
+                .to_string_lossy()
+                .to_string(),
+        ];
+
+        let planned = plan_batch_delete(files.clone(), true, None, &policy);
+        assert_eq!(planned.successful, vec![files[0].clone()]);
+        assert_eq!(
+            planned.protected,
+            vec![(files[1].clone(), ProtectionReason::UserDefined)]
+        );
+
+        let result = run_batch_delete(
+            files.clone(),
+            true,
+            None,
+            DeleteMode::Permanent,
+            &policy,
+            None,
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+        assert_eq!(result.successful, vec![files[0].clone()]);
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.failed[0].1.contains("user-protected path"));
+        assert_eq!(
+            result.protected,
+            vec![(files[1].clone(), ProtectionReason::UserDefined)]
+        );
+        assert!(!file1.exists());
+        assert!(nested.exists());
+    }
+
+    /// Tests a scoped delete refuses paths outside the root and never removes the root.
+    #[test]
+    fn test_batch_delete_within_root() {
+        let dir = setup_test_directory();
+        let root = fs::canonicalize(dir.path()).unwrap().join("subdir");
+        let nested = root.join("nested.txt");
+        let outside = dir.path().join("file1.txt");
+        let policy = ProtectedPathPolicy::default().within_root(root.clone());
+
+        let result = run_batch_delete(
+            vec![
+                nested.to_string_lossy().to_string(),
+                root.join("../file1.txt").to_string_lossy().to_string(),
+            ],
+            true,
+            None,
+            DeleteMode::Permanent,
+            &policy,
+            None,
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert_eq!(result.successful.len(), 1);
+        assert_eq!(result.protected[0].1, ProtectionReason::OutsideRoot);
+        assert_eq!(result.root, Some(root.to_string_lossy().to_string()));
+        assert!(!nested.exists());
+        assert!(outside.exists());
+        // The emptied root is not cleaned up
+        assert!(result.deleted_dirs.is_empty());
+        assert!(root.exists());
+    }
+
+    /// Tests the cleanup walks up through emptied ancestors and stops at the base path.
+    #[test]
+    fn test_batch_delete_cleans_up_ancestors() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let base = fs::canonicalize(dir.path()).unwrap().join("base");
+        let deepest = base.join("a").join("b").join("c");
+        let file = deepest.join("file.txt");
+        fs::create_dir_all(&deepest).unwrap();
+        File::create(&file).unwrap();
+        let files = vec![file.to_string_lossy().to_string()];
+        let policy =
+            ProtectedPathPolicy::default().within_root(base.parent().unwrap().to_path_buf());
+        let expected = vec![
+            deepest.to_string_lossy().to_string(),
+            base.join("a/b").to_string_lossy().to_string(),
+            base.join("a").to_string_lossy().to_string(),
+        ];
+
+        let planned = plan_batch_delete(files.clone(), true, Some(&base), &policy);
+        assert_eq!(planned.deleted_dirs, expected);
+
+        let result = run_batch_delete(
+            files,
+            true,
+            Some(&base),
+            DeleteMode::Permanent,
+            &policy,
+            None,
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert_eq!(result.deleted_dirs, expected);
+        // The emptied base path is kept
+        assert!(base.exists());
+    }
+
+    /// Tests the upward cleanup stops at the first directory that is not empty.
+    #[test]
+    fn test_batch_delete_cleanup_stops_at_non_empty_dir() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let base = fs::canonicalize(dir.path()).unwrap();
+        let kept = base.join("a");
+        let nested = kept.join("b").join("c");
+        let file = nested.join("file.txt");
+        fs::create_dir_all(&nested).unwrap();
+        File::create(&file).unwrap();
+        File::create(kept.join("keep.txt")).unwrap();
+
+        let result = run_batch_delete(
+            vec![file.to_string_lossy().to_string()],
+            true,
+            Some(&base),
+            DeleteMode::Permanent,
+            &ProtectedPathPolicy::default(),
+            None,
+            &CancellationToken::default(),
+            |_, _| {},
+        );
+
+        assert_eq!(
+            result.deleted_dirs,
+            vec![
+                nested.to_string_lossy().to_string(),
+                kept.join("b").to_string_lossy().to_string(),
+            ]
+        );
+        assert!(kept.exists());
+    }
+
+    /// Tests the cleanup base must be inside the approved root.
+    #[test]
+    fn test_cleanup_base() {
+        let dir = setup_test_directory();
+        let root = fs::canonicalize(dir.path()).unwrap().join("subdir");
+        let policy = ProtectedPathPolicy::default().within_root(root.clone());
+
+        assert_eq!(cleanup_base(None, &policy), Ok(Some(root.clone())));
+        assert_eq!(
+            cleanup_base(Some(&root.to_string_lossy()), &policy),
+            Ok(Some(root.clone()))
+        );
+        assert!(cleanup_base(Some(&dir.path().to_string_lossy()), &policy).is_err());
+        assert_eq!(
+            cleanup_base(None, &ProtectedPathPolicy::default()),
+            Ok(None)
+        );
+    }
+
+    /// Tests system directories are refused by the built-in policy.
+    #[cfg(unix)]
+    #[test]
+    fn test_batch_delete_refuses_system_dirs() {
+        let result = batch_delete(vec!["/etc".to_string(), "/".to_string()], false, None).unwrap();
+
+        assert!(result.successful.is_empty());
+        assert_eq!(
+            result.protected,
+            vec![
+                ("/etc".to_string(), ProtectionReason::SystemDirectory),
+                ("/".to_string(), ProtectionReason::SystemDirectory),
+            ]
+        );
+        assert!(Path::new("/etc").exists());
+    }
+
+    /// Tests DeleteMode uses lowercase names.
+    #[test]
+    fn test_delete_mode_serialization() {
+        assert_eq!(
+            serde_json::to_string(&DeleteMode::Trash).unwrap(),
+            "\"trash\""
+        );
+        let mode: DeleteMode = serde_json::from_str("\"permanent\"").unwrap();
+        assert_eq!(mode, DeleteMode::Permanent);
+    }
+
     // ==================== Progress Types Serialization Tests ====================
 
     /// Tests SearchProgress::Started serialization.
@@ -1120,6 +2681,34 @@ mod tests {
         assert!(json.contains("\"failed\":2"));
     }
 
+    /// Tests SearchProgress::Cancelled serialization.
+    #[test]
+    fn test_search_progress_cancelled_serialization() {
+        let progress = SearchProgress::Cancelled {
+            files_found: 400,
+            matches_found: 12,
+        };
+        let json = serde_json::to_string(&progress).unwrap();
+        assert!(json.contains("\"type\":\"cancelled\""));
+        assert!(json.contains("\"filesFound\":400"));
+        assert!(json.contains("\"matchesFound\":12"));
+    }
+
+    /// Tests DeleteProgress::Cancelled serialization.
+    #[test]
+    fn test_delete_progress_cancelled_serialization() {
+        let progress = DeleteProgress::Cancelled {
+            successful: 4,
+            failed: 1,
+            total: 20,
+        };
+        let json = serde_json::to_string(&progress).unwrap();
+        assert!(json.contains("\"type\":\"cancelled\""));
+        assert!(json.contains("\"successful\":4"));
+        assert!(json.contains("\"failed\":1"));
+        assert!(json.contains("\"total\":20"));
+    }
+
     // ==================== Progress Types Round-Trip Tests ====================
 
     /// Tests SearchProgress round-trip serialization/deserialization.
@@ -1443,10 +3032,13 @@ mod tests {
         let dir = setup_large_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            "file".to_string(),
-            PatternType::Simple,
+            Some("file".to_string()),
+            Some(PatternType::Simple),
             true,
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -1461,10 +3053,13 @@ mod tests {
         let dir = setup_large_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            ".txt".to_string(),
-            PatternType::Extension,
+            Some(".txt".to_string()),
+            Some(PatternType::Extension),
             true,
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -1479,10 +3074,13 @@ mod tests {
         let dir = setup_large_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            r"file\d{4}".to_string(),
-            PatternType::Regex,
+            Some(r"file\d{4}".to_string()),
+            Some(PatternType::Regex),
             true,
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -1496,10 +3094,13 @@ mod tests {
         let dir = setup_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            "file".to_string(),
-            PatternType::Simple,
+            Some("file".to_string()),
+            Some(PatternType::Simple),
             false,
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
@@ -1518,10 +3119,13 @@ mod tests {
         let dir = setup_large_test_directory();
         let results = search_files_by_pattern(
             dir.path().to_string_lossy().to_string(),
-            "nonexistent_pattern_xyz".to_string(),
-            PatternType::Simple,
+            Some("nonexistent_pattern_xyz".to_string()),
+            Some(PatternType::Simple),
             true,
             false,
+            None,
+            None,
+            None,
         )
         .unwrap();
 
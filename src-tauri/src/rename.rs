@@ -6,10 +6,16 @@
 //! This module supports both synchronous commands (for backward compatibility)
 //! and streaming commands with progress updates via Tauri Channels.
 
+use crate::approved_roots::{ensure_within_root, ApprovedRoots};
+use crate::file_move::move_file;
+use crate::journal::RenameJournal;
+use crate::operations::{CancellationToken, OperationRegistry, CANCELLED_ERROR};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::ipc::Channel;
+use tauri::State;
 use walkdir::WalkDir;
 
 // ==================== Progress Types ====================
@@ -38,6 +44,11 @@ pub enum ListProgress {
         /// Total number of files found
         total_files: usize,
     },
+    /// Listing was cancelled before it finished
+    Cancelled {
+        /// Number of files found before cancellation
+        files_found: usize,
+    },
 }
 
 /// Progress events for batch rename operations.
@@ -61,6 +72,15 @@ pub enum RenameProgress {
         /// Path of file just processed
         current_path: String,
     },
+    /// Byte progress while a file is copied to another filesystem
+    Copying {
+        /// Destination path of the file being copied
+        current_path: String,
+        /// Bytes copied so far
+        bytes_copied: u64,
+        /// Size of the file
+        total_bytes: u64,
+    },
     /// Renaming completed
     Completed {
         /// Number of successfully renamed files
@@ -68,6 +88,810 @@ pub enum RenameProgress {
         /// Number of failed renames
         failed: usize,
     },
+    /// Renaming was cancelled; the remaining files were left untouched
+    Cancelled {
+        /// Number of files renamed before cancellation
+        successful: usize,
+        /// Number of failed renames before cancellation
+        failed: usize,
+        /// Total number of files in the batch
+        total: usize,
+    },
+}
+
+/// Result of a batch rename
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchRenameResult {
+    /// New paths of the renamed files
+    pub renamed: Vec<String>,
+    /// Id of the journal batch recording the renames, for `undo_rename_batch`
+    pub batch_id: Option<String>,
+    /// Why the renames could not be recorded in the journal; the batch
+    /// cannot be undone when this is set
+    pub journal_error: Option<String>,
+}
+
+// ==================== Validation Types ====================
+
+/// How a rename batch treats targets that already exist on disk.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OverwritePolicy {
+    /// Refuse to run the batch if any target already exists
+    #[default]
+    Refuse,
+    /// Replace existing targets that are not part of the batch
+    Overwrite,
+}
+
+/// Result of validating a rename plan before it runs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RenameValidation {
+    /// Whether the plan passed every check
+    pub is_valid: bool,
+    /// Targets requested by more than one source (target, sources)
+    pub duplicate_targets: Vec<(String, Vec<String>)>,
+    /// Targets that already exist and are not moved away by the batch (source, target)
+    pub existing_targets: Vec<(String, String)>,
+    /// Source paths that do not exist
+    pub missing_sources: Vec<String>,
+    /// Targets whose parent directory does not exist (source, target)
+    pub missing_directories: Vec<(String, String)>,
+    /// Targets with a file name that cannot be used (target, reason)
+    pub invalid_names: Vec<(String, String)>,
+}
+
+impl RenameValidation {
+    /// Lists the problems that block the plan under the given overwrite policy.
+    ///
+    /// Existing targets only block the plan when the policy is `Refuse`.
+    fn blocking_errors(&self, policy: OverwritePolicy) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for source in &self.missing_sources {
+            errors.push(format!(
+                "Failed to rename {}: source does not exist",
+                source
+            ));
+        }
+        for (target, sources) in &self.duplicate_targets {
+            errors.push(format!(
+                "Failed to rename {}: {} sources would be renamed to {}",
+                sources.join(", "),
+                sources.len(),
+                target
+            ));
+        }
+        if policy == OverwritePolicy::Refuse {
+            for (source, target) in &self.existing_targets {
+                errors.push(format!(
+                    "Failed to rename {}: target {} already exists",
+                    source, target
+                ));
+            }
+        }
+        for (source, target) in &self.missing_directories {
+            errors.push(format!(
+                "Failed to rename {}: directory of {} does not exist",
+                source, target
+            ));
+        }
+        for (target, reason) in &self.invalid_names {
+            errors.push(format!("Failed to rename to {}: {}", target, reason));
+        }
+
+        errors
+    }
+}
+
+// ==================== Validation ====================
+
+/// Checks whether a file name can be used as a rename target.
+///
+/// Control characters and empty or relative names (`.`, `..`) are always
+/// rejected. When `windows_rules` is set, the characters, reserved device
+/// names and trailing dots/spaces that Windows refuses are rejected as well.
+///
+/// # Arguments
+///
+/// * `name` - The file name (last path component) to check
+/// * `windows_rules` - Whether to apply Windows file naming restrictions
+///
+/// # Returns
+///
+/// * `Some(String)` - The reason the name is invalid
+/// * `None` - If the name is valid
+fn invalid_name_reason(name: &str, windows_rules: bool) -> Option<String> {
+    if name.is_empty() || name == "." || name == ".." {
+        return Some("file name is empty".to_string());
+    }
+
+    if let Some(c) = name.chars().find(|c| c.is_control()) {
+        return Some(format!(
+            "file name contains control character U+{:04X}",
+            c as u32
+        ));
+    }
+
+    if windows_rules {
+        if let Some(c) = name.chars().find(|c| r#"<>:"/\|?*"#.contains(*c)) {
+            return Some(format!("file name contains invalid character '{}'", c));
+        }
+
+        if name.ends_with('.') || name.ends_with(' ') {
+            return Some("file name ends with a dot or space".to_string());
+        }
+
+        const RESERVED: [&str; 22] = [
+            "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7",
+            "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+        ];
+        let stem = name.split('.').next().unwrap_or(name);
+        if RESERVED.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+            return Some(format!("'{}' is a reserved file name", stem));
+        }
+    }
+
+    None
+}
+
+/// Validates a batch of renames against the file system without changing it.
+///
+/// A target that exists on disk is only reported when it is not itself a
+/// source in the batch, since the batch moves those out of the way first.
+///
+/// # Arguments
+///
+/// * `files` - The rename pairs to validate
+///
+/// # Returns
+///
+/// A `RenameValidation` report listing every problem found.
+fn check_rename_plan(files: &[(String, String)]) -> RenameValidation {
+    let sources: HashSet<&Path> = files
+        .iter()
+        .map(|(old, _)| Path::new(old.as_str()))
+        .collect();
+
+    let mut report = RenameValidation::default();
+    let mut targets: HashMap<&Path, Vec<String>> = HashMap::new();
+    let mut target_order: Vec<&Path> = Vec::new();
+
+    for (old_path, new_path) in files {
+        let source = Path::new(old_path);
+        let target = Path::new(new_path);
+
+        if source.symlink_metadata().is_err() {
+            report.missing_sources.push(old_path.clone());
+        }
+
+        let entry = targets.entry(target).or_default();
+        if entry.is_empty() {
+            target_order.push(target);
+        }
+        entry.push(old_path.clone());
+
+        if target != source
+            && !sources.contains(target)
+            && target.symlink_metadata().is_ok()
+            && !is_same_file(source, target)
+        {
+            report
+                .existing_targets
+                .push((old_path.clone(), new_path.clone()));
+        }
+
+        match target.file_name() {
+            Some(name) => {
+                if let Some(reason) = invalid_name_reason(&name.to_string_lossy(), cfg!(windows)) {
+                    report.invalid_names.push((new_path.clone(), reason));
+                }
+            }
+            None => report
+                .invalid_names
+                .push((new_path.clone(), "file name is empty".to_string())),
+        }
+
+        let parent_exists = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.is_dir(),
+            _ => true,
+        };
+        if !parent_exists {
+            report
+                .missing_directories
+                .push((old_path.clone(), new_path.clone()));
+        }
+    }
+
+    for target in target_order {
+        let sources = &targets[target];
+        if sources.len() > 1 {
+            report
+                .duplicate_targets
+                .push((target.to_string_lossy().to_string(), sources.clone()));
+        }
+    }
+
+    report.is_valid = report.duplicate_targets.is_empty()
+        && report.existing_targets.is_empty()
+        && report.missing_sources.is_empty()
+        && report.missing_directories.is_empty()
+        && report.invalid_names.is_empty();
+
+    report
+}
+
+// ==================== Rename Planning ====================
+
+/// A single filesystem move in an executable rename plan.
+///
+/// Each variant carries the index of the (old, new) pair it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RenameStep {
+    /// Move the source straight to its final name
+    Direct(usize),
+    /// Park the source under a temporary name to break a cycle
+    ToTemp(usize),
+    /// Move a parked file from its temporary name to its final name
+    FromTemp(usize),
+}
+
+/// An ordered set of moves that applies a batch of renames safely.
+///
+/// `blockers[i]` is the index of the pair whose *source* is the *target* of
+/// pair `i`; pair `i` may only land once that source has been moved away.
+#[derive(Debug, Clone, PartialEq)]
+struct RenamePlan {
+    steps: Vec<RenameStep>,
+    blockers: Vec<Option<usize>>,
+}
+
+/// Builds an execution order for a batch of (old_path, new_path) pairs.
+///
+/// The pairs form a dependency graph where pair `i` waits for pair `j` when
+/// `new_i == old_j`. Chains are resolved by renaming the last link first, and
+/// every cycle (swaps, rotations) is broken by parking one of its members
+/// under a temporary name, unwinding the rest of the cycle, and finally moving
+/// the parked file into place.
+///
+/// # Arguments
+///
+/// * `files` - The rename pairs in the order they were requested
+///
+/// # Returns
+///
+/// A `RenamePlan` whose steps, executed in order, never overwrite a file that
+/// another pair still needs to move.
+fn plan_renames(files: &[(String, String)]) -> RenamePlan {
+    let count = files.len();
+
+    // Map each source path to the first pair that moves it
+    let mut source_index: HashMap<&Path, usize> = HashMap::new();
+    for (index, (old_path, _)) in files.iter().enumerate() {
+        source_index.entry(Path::new(old_path)).or_insert(index);
+    }
+
+    let blockers: Vec<Option<usize>> = files
+        .iter()
+        .enumerate()
+        .map(|(index, (_, new_path))| {
+            source_index
+                .get(Path::new(new_path))
+                .copied()
+                .filter(|&blocker| blocker != index)
+        })
+        .collect();
+
+    let mut waiters: Vec<Vec<usize>> = vec![Vec::new(); count];
+    for (index, blocker) in blockers.iter().enumerate() {
+        if let Some(blocker) = blocker {
+            waiters[*blocker].push(index);
+        }
+    }
+
+    let mut steps = Vec::with_capacity(count);
+    let mut done = vec![false; count];
+    let mut parked = vec![false; count];
+    let mut ready: VecDeque<usize> = (0..count).filter(|&i| blockers[i].is_none()).collect();
+
+    loop {
+        while let Some(index) = ready.pop_front() {
+            done[index] = true;
+            if parked[index] {
+                // Waiters were released when this file was parked
+                steps.push(RenameStep::FromTemp(index));
+            } else {
+                steps.push(RenameStep::Direct(index));
+                ready.extend(waiters[index].iter().copied());
+            }
+        }
+
+        // Everything left is either on a cycle or waiting on one
+        let Some(start) = (0..count).find(|&i| !done[i] && !parked[i]) else {
+            break;
+        };
+
+        // Follow the blockers until a node repeats; that node is on the cycle
+        let mut seen = vec![false; count];
+        let mut current = start;
+        while !seen[current] {
+            seen[current] = true;
+            current = match blockers[current] {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        parked[current] = true;
+        steps.push(RenameStep::ToTemp(current));
+        ready.extend(waiters[current].iter().copied());
+    }
+
+    RenamePlan { steps, blockers }
+}
+
+/// Checks whether two paths refer to the same file on disk.
+///
+/// On case-insensitive filesystems `Photo.JPG` and `photo.jpg` resolve to the
+/// same file, so the target of a case-only rename appears to already exist.
+#[cfg(unix)]
+pub(crate) fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (a.symlink_metadata(), b.symlink_metadata()) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/// Checks whether two paths refer to the same file on disk.
+///
+/// Without inode numbers, an existing target that differs from the source
+/// only by case is assumed to be the source itself.
+#[cfg(not(unix))]
+pub(crate) fn is_same_file(a: &Path, b: &Path) -> bool {
+    b.symlink_metadata().is_ok() && is_case_only_change(a, b)
+}
+
+/// Checks whether a rename only changes the letter case of the file name.
+fn is_case_only_change(old_path: &Path, new_path: &Path) -> bool {
+    old_path != new_path
+        && old_path.parent() == new_path.parent()
+        && match (old_path.file_name(), new_path.file_name()) {
+            (Some(old_name), Some(new_name)) => {
+                old_name.to_string_lossy().to_lowercase()
+                    == new_name.to_string_lossy().to_lowercase()
+            }
+            _ => false,
+        }
+}
+
+/// Checks whether a rename must hop through a temporary name.
+///
+/// `fs::rename` between two names of the same file is a no-op (POSIX) or an
+/// error, which is what a case-only rename looks like on case-insensitive
+/// mounts such as vfat, exFAT or SMB shares.
+fn needs_temp_hop(old_path: &Path, new_path: &Path) -> bool {
+    is_case_only_change(old_path, new_path)
+        || (old_path != new_path && is_same_file(old_path, new_path))
+}
+
+/// Renames a file through a temporary name next to it.
+///
+/// If the second move fails, the file is moved back to its original name. If
+/// the target is another hard link to the same file, the second move is a
+/// no-op and the temporary link is removed so only the target name remains.
+fn rename_via_temp(old_path: &Path, new_path: &Path, index: usize) -> Result<(), String> {
+    let temp_path = temp_rename_path(old_path, index);
+
+    if temp_path.symlink_metadata().is_ok() {
+        return Err(format!(
+            "Failed to rename {}: temporary path {} already exists",
+            old_path.display(),
+            temp_path.display()
+        ));
+    }
+
+    fs::rename(old_path, &temp_path)
+        .map_err(|e| format!("Failed to rename {}: {}", old_path.display(), e))?;
+
+    fs::rename(&temp_path, new_path).map_err(|e| {
+        let restored = fs::rename(&temp_path, old_path).is_ok();
+        format!(
+            "Failed to rename {}: {}{}",
+            old_path.display(),
+            e,
+            if restored {
+                String::new()
+            } else {
+                format!(" (file left at temporary path {})", temp_path.display())
+            }
+        )
+    })?;
+
+    if is_same_file(&temp_path, new_path) {
+        fs::remove_file(&temp_path).map_err(|e| {
+            format!(
+                "Failed to rename {}: could not remove temporary path {}: {}",
+                old_path.display(),
+                temp_path.display(),
+                e
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Builds the temporary name used to park a file while a cycle is unwound.
+///
+/// The temporary file lives next to the original so the move stays on the
+/// same filesystem.
+fn temp_rename_path(old_path: &Path, index: usize) -> PathBuf {
+    let file_name = old_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_name = format!(".{}.{}-{}.renaming", file_name, std::process::id(), index);
+
+    match old_path.parent() {
+        Some(parent) => parent.join(temp_name),
+        None => PathBuf::from(temp_name),
+    }
+}
+
+/// Progress reported while a batch of renames executes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum RenameEvent {
+    /// The plan passed validation and the renames are about to start
+    Started,
+    /// The pair with this index has either landed or failed
+    Finished(usize),
+    /// The pair with this index is being copied to another filesystem
+    Copying {
+        /// Index of the pair being copied
+        index: usize,
+        /// Bytes copied so far
+        bytes_copied: u64,
+        /// Size of the file
+        total_bytes: u64,
+    },
+}
+
+/// Executes a batch of renames following the plan from `plan_renames`.
+///
+/// A pair whose target is still occupied because the pair it depends on
+/// failed is skipped with an error instead of overwriting that file.
+///
+/// Once `cancel` is set, the remaining pairs are skipped without an event.
+/// A cycle whose first file has already been parked is always finished, so
+/// cancellation never leaves a file under its temporary name.
+///
+/// # Arguments
+///
+/// * `files` - The rename pairs in the order they were requested
+/// * `cancel` - Token checked before each pair starts moving
+/// * `on_event` - Called when a pair has either landed or failed, and with
+///   byte progress while a pair is copied across filesystems
+///
+/// # Returns
+///
+/// One outcome per input pair, in input order.
+pub(crate) fn execute_renames<F>(
+    files: &[(String, String)],
+    cancel: &CancellationToken,
+    mut on_event: F,
+) -> Vec<Result<(), String>>
+where
+    F: FnMut(RenameEvent),
+{
+    let plan = plan_renames(files);
+    let count = files.len();
+
+    let mut outcomes: Vec<Option<Result<(), String>>> = vec![None; count];
+    let mut vacated = vec![false; count];
+    let mut temp_paths: Vec<Option<PathBuf>> = vec![None; count];
+    let mut parked_count = 0;
+
+    // Decide before anything moves, while both names can still be compared on disk
+    let temp_hops: Vec<bool> = files
+        .iter()
+        .map(|(old_path, new_path)| needs_temp_hop(Path::new(old_path), Path::new(new_path)))
+        .collect();
+
+    for step in plan.steps {
+        if let RenameStep::Direct(index) | RenameStep::ToTemp(index) = step {
+            if parked_count == 0 && cancel.is_cancelled() {
+                outcomes[index] = Some(Err(format!(
+                    "Skipped {}: rename was cancelled",
+                    files[index].0
+                )));
+                continue;
+            }
+        }
+
+        match step {
+            RenameStep::ToTemp(index) => {
+                let old_path = Path::new(&files[index].0);
+                let temp_path = temp_rename_path(old_path, index);
+
+                let result = if temp_path.symlink_metadata().is_ok() {
+                    Err(format!(
+                        "Failed to rename {}: temporary path {} already exists",
+                        files[index].0,
+                        temp_path.display()
+                    ))
+                } else {
+                    fs::rename(old_path, &temp_path)
+                        .map_err(|e| format!("Failed to rename {}: {}", files[index].0, e))
+                };
+
+                match result {
+                    Ok(_) => {
+                        vacated[index] = true;
+                        temp_paths[index] = Some(temp_path);
+                        parked_count += 1;
+                    }
+                    Err(e) => {
+                        outcomes[index] = Some(Err(e));
+                        on_event(RenameEvent::Finished(index));
+                    }
+                }
+            }
+            RenameStep::Direct(index) | RenameStep::FromTemp(index) => {
+                // Parking already failed for this pair
+                if outcomes[index].is_some() {
+                    continue;
+                }
+
+                let (old_path, new_path) = &files[index];
+
+                if let Some(blocker) = plan.blockers[index] {
+                    if !vacated[blocker] {
+                        outcomes[index] = Some(Err(format!(
+                            "Failed to rename {}: target is still occupied because {} could not be renamed",
+                            old_path, files[blocker].0
+                        )));
+                        on_event(RenameEvent::Finished(index));
+                        continue;
+                    }
+                }
+
+                let mut on_bytes = |bytes_copied, total_bytes| {
+                    on_event(RenameEvent::Copying {
+                        index,
+                        bytes_copied,
+                        total_bytes,
+                    })
+                };
+
+                let result = match &temp_paths[index] {
+                    Some(temp_path) => move_file(temp_path, Path::new(new_path), &mut on_bytes)
+                        .map_err(|e| {
+                            format!(
+                                "Failed to rename {}: {} (file left at temporary path {})",
+                                old_path,
+                                e,
+                                temp_path.display()
+                            )
+                        }),
+                    None if temp_hops[index] => {
+                        rename_via_temp(Path::new(old_path), Path::new(new_path), index)
+                    }
+                    None => move_file(Path::new(old_path), Path::new(new_path), &mut on_bytes)
+                        .map_err(|e| format!("Failed to rename {}: {}", old_path, e)),
+                };
+
+                if result.is_ok() && step == RenameStep::Direct(index) {
+                    vacated[index] = true;
+                }
+                if step == RenameStep::FromTemp(index) {
+                    parked_count -= 1;
+                }
+
+                outcomes[index] = Some(result);
+                on_event(RenameEvent::Finished(index));
+            }
+        }
+    }
+
+    outcomes
+        .into_iter()
+        .map(|outcome| outcome.unwrap_or_else(|| Err("Rename was not executed".to_string())))
+        .collect()
+}
+
+/// What `run_batch_rename` did.
+#[derive(Debug)]
+struct RenameRun {
+    /// One outcome per input pair, in input order
+    outcomes: Vec<Result<(), String>>,
+    /// The id of the journal batch, or why the renames could not be recorded
+    journal: Result<Option<String>, String>,
+}
+
+impl RenameRun {
+    /// Describes a journal failure as an error line.
+    fn journal_error(&self) -> Option<String> {
+        self.journal.as_ref().err().map(|e| {
+            format!(
+                "The renames could not be recorded and cannot be undone: {}",
+                e
+            )
+        })
+    }
+}
+
+/// Splits per-pair outcomes into the command result shape.
+///
+/// Returns the new paths of all pairs and the journal batch if every rename
+/// succeeded, otherwise a newline-separated list of the errors, including a
+/// journal failure.
+fn collect_rename_outcomes(
+    files: &[(String, String)],
+    run: RenameRun,
+) -> Result<BatchRenameResult, String> {
+    let journal_error = run.journal_error();
+    let mut renamed = Vec::new();
+    let mut errors = Vec::new();
+
+    for ((_, new_path), outcome) in files.iter().zip(run.outcomes) {
+        match outcome {
+            Ok(_) => renamed.push(new_path.clone()),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(BatchRenameResult {
+            renamed,
+            batch_id: run.journal.unwrap_or(None),
+            journal_error,
+        })
+    } else {
+        errors.extend(journal_error);
+        Err(errors.join("\n"))
+    }
+}
+
+/// Validates, executes and journals a batch of renames.
+///
+/// Shared by `batch_rename` and `batch_rename_with_progress`. Pairs that were
+/// renamed successfully are recorded in `journal` so the batch can be undone;
+/// a journal failure is returned with the outcomes rather than failing the
+/// batch, since the files have already been renamed at that point.
+///
+/// # Arguments
+///
+/// * `files` - The rename pairs in the order they were requested
+/// * `policy` - How to treat targets that already exist
+/// * `root` - The approved root every old and new path must be inside, if any
+/// * `journal` - Where to record the batch, if anywhere
+/// * `cancel` - Token checked before each pair starts moving
+/// * `on_event` - Called once the plan passed validation, when a pair has
+///   either landed or failed, and with byte progress while a pair is copied
+///   across filesystems
+///
+/// # Returns
+///
+/// * `Ok(RenameRun)` - One outcome per input pair, in input order, and the
+///   journal batch
+/// * `Err(String)` - If a path is outside the root or the plan failed
+///   validation, and nothing was renamed
+fn run_batch_rename<F>(
+    files: &[(String, String)],
+    policy: OverwritePolicy,
+    root: Option<&Path>,
+    journal: Option<&RenameJournal>,
+    cancel: &CancellationToken,
+    mut on_event: F,
+) -> Result<RenameRun, String>
+where
+    F: FnMut(RenameEvent),
+{
+    if let Some(root) = root {
+        ensure_within_root(
+            files
+                .iter()
+                .flat_map(|(old, new)| [old.as_str(), new.as_str()]),
+            root,
+        )?;
+    }
+    ensure_plan_is_valid(files, policy)?;
+
+    on_event(RenameEvent::Started);
+    let outcomes = execute_renames(files, cancel, &mut on_event);
+
+    let journal = match journal {
+        Some(journal) => {
+            let renamed: Vec<(String, String)> = files
+                .iter()
+                .zip(&outcomes)
+                .filter(|(_, outcome)| outcome.is_ok())
+                .map(|(pair, _)| pair.clone())
+                .collect();
+            journal.record(&renamed, root)
+        }
+        None => Ok(None),
+    };
+
+    Ok(RenameRun { outcomes, journal })
+}
+
+/// Validates a rename plan and turns blocking problems into a command error.
+fn ensure_plan_is_valid(files: &[(String, String)], policy: OverwritePolicy) -> Result<(), String> {
+    let errors = check_rename_plan(files).blocking_errors(policy);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Validates a rename plan without touching the file system.
+///
+/// Reports duplicate targets, targets that already exist on disk, missing
+/// sources, targets in directories that do not exist, and target names with
+/// invalid characters. `batch_rename` runs the same checks before renaming.
+///
+/// # Arguments
+///
+/// * `files` - A vector of (old_path, new_path) tuples
+///
+/// # Returns
+///
+/// A `RenameValidation` report; `is_valid` is true when no problems were found.
+///
+/// # Example
+///
+/// ```ignore
+/// let report = validate_rename_plan(vec![
+///     ("/path/to/a.txt".to_string(), "/path/to/b.txt".to_string()),
+/// ]);
+/// if !report.is_valid {
+///     println!("{} targets already exist", report.existing_targets.len());
+/// }
+/// ```
+#[tauri::command]
+pub fn validate_rename_plan(files: Vec<(String, String)>) -> RenameValidation {
+    check_rename_plan(&files)
+}
+
+/// Renames a batch inside an approved root and records it in the journal.
+///
+/// Shared by `batch_rename` and `rename_with_pipeline`, so both validate,
+/// order and journal renames the same way.
+///
+/// # Arguments
+///
+/// * `files` - The rename pairs in the order they were requested
+/// * `policy` - How to treat targets that already exist
+/// * `root` - The approved root every old and new path must be inside
+/// * `journal` - Where to record the batch
+///
+/// # Returns
+///
+/// * `Ok(BatchRenameResult)` - The new paths, and the journal batch id or why
+///   the batch could not be recorded
+/// * `Err(String)` - A newline-separated string of all errors that occurred
+pub(crate) fn rename_within_root(
+    files: &[(String, String)],
+    policy: OverwritePolicy,
+    root: &Path,
+    journal: &RenameJournal,
+) -> Result<BatchRenameResult, String> {
+    let run = run_batch_rename(
+        files,
+        policy,
+        Some(root),
+        Some(journal),
+        &CancellationToken::default(),
+        |_| {},
+    )?;
+    collect_rename_outcomes(files, run)
 }
 
 /// Renames multiple files in a single batch operation.
@@ -76,15 +900,30 @@ pub enum RenameProgress {
 /// to rename each file. Returns all successfully renamed files or an error
 /// containing all failures.
 ///
+/// Pairs may depend on each other: swaps (`a -> b`, `b -> a`), rotations and
+/// chains (`a -> b`, `b -> c`) are ordered and routed through temporary names
+/// so no file in the batch is overwritten by another.
+///
+/// The plan is validated first (see `validate_rename_plan`) and nothing is
+/// renamed if it fails. Targets that already exist outside the batch are
+/// only replaced when `overwrite_policy` is `Overwrite`. Successful renames
+/// are recorded in the rename journal so the batch can be undone.
+///
 /// # Arguments
 ///
 /// * `files` - A vector of tuples where each tuple contains:
 ///   - `old_path`: The current path of the file
 ///   - `new_path`: The desired new path for the file
+/// * `overwrite_policy` - How to treat existing targets (defaults to `Refuse`)
+/// * `root` - Root from `choose_operation_root`; every old and new path must be
+///   inside it
+/// * `journal` - The rename journal managed by Tauri
+/// * `roots` - The approved roots managed by Tauri
 ///
 /// # Returns
 ///
-/// * `Ok(Vec<String>)` - A vector of successfully renamed file paths (new paths)
+/// * `Ok(BatchRenameResult)` - The new paths, and the journal batch id or why
+///   the batch could not be recorded
 /// * `Err(String)` - A newline-separated string of all errors that occurred
 ///
 /// # Example
@@ -94,25 +933,23 @@ pub enum RenameProgress {
 ///     ("/path/to/old1.txt".to_string(), "/path/to/new1.txt".to_string()),
 ///     ("/path/to/old2.txt".to_string(), "/path/to/new2.txt".to_string()),
 /// ];
-/// let result = batch_rename(files);
+/// let result = batch_rename(files, None, "/path/to".to_string(), journal, roots);
 /// ```
 #[tauri::command]
-pub fn batch_rename(files: Vec<(String, String)>) -> Result<Vec<String>, String> {
-    let mut renamed_files = Vec::new();
-    let mut errors = Vec::new();
-
-    for (old_path, new_path) in files {
-        match fs::rename(&old_path, &new_path) {
-            Ok(_) => renamed_files.push(new_path),
-            Err(e) => errors.push(format!("Failed to rename {}: {}", old_path, e)),
-        }
-    }
-
-    if errors.is_empty() {
-        Ok(renamed_files)
-    } else {
-        Err(errors.join("\n"))
-    }
+pub fn batch_rename(
+    files: Vec<(String, String)>,
+    overwrite_policy: Option<OverwritePolicy>,
+    root: String,
+    journal: State<'_, RenameJournal>,
+    roots: State<'_, ApprovedRoots>,
+) -> Result<BatchRenameResult, String> {
+    let root = roots.resolve(&root)?;
+    rename_within_root(
+        &files,
+        overwrite_policy.unwrap_or_default(),
+        &root,
+        &journal,
+    )
 }
 
 /// Renames multiple files with progress streaming.
@@ -124,57 +961,116 @@ pub fn batch_rename(files: Vec<(String, String)>) -> Result<Vec<String>, String>
 /// main Tauri thread to remain responsive and deliver progress events
 /// to the frontend in real-time.
 ///
+/// When started with an `operation_id`, the batch can be stopped with
+/// `cancel_operation`. Files renamed before cancellation stay renamed and are
+/// journaled as usual; a `Cancelled` event reports how many were processed.
+///
 /// # Arguments
 ///
 /// * `files` - A vector of tuples where each tuple contains:
 ///   - `old_path`: The current path of the file
 ///   - `new_path`: The desired new path for the file
+/// * `overwrite_policy` - How to treat existing targets (defaults to `Refuse`)
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `root` - Root from `choose_operation_root`; every old and new path must be
+///   inside it
+/// * `journal` - The rename journal managed by Tauri
+/// * `roots` - The approved roots managed by Tauri
+/// * `operations` - The operation registry managed by Tauri
 /// * `on_progress` - Channel to send progress events
 ///
 /// # Returns
 ///
-/// * `Ok(Vec<String>)` - A vector of successfully renamed file paths (new paths)
-/// * `Err(String)` - A newline-separated string of all errors that occurred
+/// * `Ok(BatchRenameResult)` - The new paths, and the journal batch id or why
+///   the batch could not be recorded
+/// * `Err(String)` - A newline-separated string of all errors that occurred,
+///   or a cancellation message
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn batch_rename_with_progress(
     files: Vec<(String, String)>,
+    overwrite_policy: Option<OverwritePolicy>,
+    operation_id: Option<u64>,
+    root: String,
+    journal: State<'_, RenameJournal>,
+    roots: State<'_, ApprovedRoots>,
+    operations: State<'_, OperationRegistry>,
     on_progress: Channel<RenameProgress>,
-) -> Result<Vec<String>, String> {
+) -> Result<BatchRenameResult, String> {
+    let root = roots.resolve(&root)?;
+    let operation = operations.begin(operation_id);
+    let policy = overwrite_policy.unwrap_or_default();
+    let journal = journal.inner().clone();
+
     // Run the heavy work in a blocking thread to keep the main thread responsive
     tokio::task::spawn_blocking(move || {
         let total = files.len();
-
-        // Send started event
-        let _ = on_progress.send(RenameProgress::Started { total_files: total });
-
-        let mut renamed_files = Vec::new();
-        let mut errors = Vec::new();
-
-        for (index, (old_path, new_path)) in files.into_iter().enumerate() {
-            match fs::rename(&old_path, &new_path) {
-                Ok(_) => renamed_files.push(new_path.clone()),
-                Err(e) => errors.push(format!("Failed to rename {}: {}", old_path, e)),
-            }
-
-            // Send progress update
-            let _ = on_progress.send(RenameProgress::Progress {
-                current: index + 1,
+        let mut current = 0;
+        let run = run_batch_rename(
+            &files,
+            policy,
+            Some(&root),
+            Some(&journal),
+            operation.token(),
+            |event| match event {
+                RenameEvent::Started => {
+                    // Sent once the plan passed validation
+                    let _ = on_progress.send(RenameProgress::Started { total_files: total });
+                }
+                RenameEvent::Finished(index) => {
+                    current += 1;
+
+                    // Send progress update
+                    let _ = on_progress.send(RenameProgress::Progress {
+                        current,
+                        total,
+                        current_path: files[index].1.clone(),
+                    });
+                }
+                RenameEvent::Copying {
+                    index,
+                    bytes_copied,
+                    total_bytes,
+                } => {
+                    // Send byte progress for cross-filesystem copies
+                    let _ = on_progress.send(RenameProgress::Copying {
+                        current_path: files[index].1.clone(),
+                        bytes_copied,
+                        total_bytes,
+                    });
+                }
+            },
+        )?;
+
+        let failed = run
+            .outcomes
+            .iter()
+            .filter(|outcome| outcome.is_err())
+            .count();
+
+        // Skipped pairs never produce a progress event
+        if current < total {
+            let failed = failed - (total - current);
+            let _ = on_progress.send(RenameProgress::Cancelled {
+                successful: current - failed,
+                failed,
                 total,
-                current_path: new_path,
             });
+            let mut errors = vec![format!(
+                "Rename cancelled after {} of {} files",
+                current, total
+            )];
+            errors.extend(run.journal_error());
+            return Err(errors.join("\n"));
         }
 
         // Send completed event
         let _ = on_progress.send(RenameProgress::Completed {
-            successful: renamed_files.len(),
-            failed: errors.len(),
+            successful: total - failed,
+            failed,
         });
 
-        if errors.is_empty() {
-            Ok(renamed_files)
-        } else {
-            Err(errors.join("\n"))
-        }
+        collect_rename_outcomes(&files, run)
     })
     .await
     .map_err(|e| format!("Task failed: {}", e))?
@@ -241,21 +1137,29 @@ pub fn list_files_recursively(dir_path: String) -> Result<Vec<String>, String> {
 /// main Tauri thread to remain responsive and deliver progress events
 /// to the frontend in real-time.
 ///
+/// When started with an `operation_id`, the listing can be stopped with
+/// `cancel_operation`; it then sends a `Cancelled` event and fails.
+///
 /// # Arguments
 ///
 /// * `dir_path` - The path to the directory to scan
+/// * `operation_id` - Id from `register_operation`, to allow cancellation
+/// * `operations` - The operation registry managed by Tauri
 /// * `on_progress` - Channel to send progress events
 ///
 /// # Returns
 ///
 /// * `Ok(Vec<String>)` - A vector of file paths found in the directory tree
 /// * `Err(String)` - An error message if the path doesn't exist, isn't a directory,
-///   or if there was an error reading the directory
+///   if there was an error reading the directory, or if the listing was cancelled
 #[tauri::command]
 pub async fn list_files_with_progress(
     dir_path: String,
+    operation_id: Option<u64>,
+    operations: State<'_, OperationRegistry>,
     on_progress: Channel<ListProgress>,
 ) -> Result<Vec<String>, String> {
+    let operation = operations.begin(operation_id);
     let path = Path::new(&dir_path);
 
     if !path.exists() {
@@ -285,6 +1189,13 @@ pub async fn list_files_with_progress(
             .filter_map(|e| e.ok())
             .filter(|e| e.path().is_file())
         {
+            if operation.is_cancelled() {
+                let _ = on_progress.send(ListProgress::Cancelled {
+                    files_found: files.len(),
+                });
+                return Err(CANCELLED_ERROR.to_string());
+            }
+
             if let Some(path_str) = entry.path().to_str() {
                 files.push(path_str.to_string());
 
@@ -352,11 +1263,27 @@ fn collect_files_recursive(dir: &Path, files: &mut Vec<String>) -> std::io::Resu
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json;
     use std::fs::File;
     use std::io::Write;
     use tempfile::tempdir;
 
+    /// Runs a batch rename the way the `batch_rename` command does, minus the
+    /// approved root and the journal.
+    fn run_rename_for_test(
+        files: Vec<(String, String)>,
+        overwrite_policy: Option<OverwritePolicy>,
+    ) -> Result<Vec<String>, String> {
+        let run = run_batch_rename(
+            &files,
+            overwrite_policy.unwrap_or_default(),
+            None,
+            None,
+            &CancellationToken::default(),
+            |_| {},
+        )?;
+        collect_rename_outcomes(&files, run).map(|result| result.renamed)
+    }
+
     // ==================== batch_rename tests ====================
 
     /// Tests successful batch renaming of multiple files.
@@ -393,7 +1320,7 @@ mod tests {
         ];
 
         // Execute batch rename
-        let result = batch_rename(files);
+        let result = run_rename_for_test(files, None);
 
         // Verify success
         assert!(result.is_ok());
@@ -412,7 +1339,7 @@ mod tests {
     /// Tests batch rename with an empty file list.
     #[test]
     fn test_batch_rename_empty_list() {
-        let result = batch_rename(vec![]);
+        let result = run_rename_for_test(vec![], None);
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
     }
@@ -425,13 +1352,13 @@ mod tests {
             "/nonexistent/path/renamed.txt".to_string(),
         )];
 
-        let result = batch_rename(files);
+        let result = run_rename_for_test(files, None);
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(error.contains("Failed to rename"));
     }
 
-    /// Tests batch rename with partial failures (some files succeed, some fail).
+    /// Tests that a batch with a missing source is refused before anything is renamed.
     #[test]
     fn test_batch_rename_partial_failure() {
         let temp_dir = tempdir().expect("Failed to create temp dir");
@@ -456,19 +1383,20 @@ mod tests {
             ),
         ];
 
-        let result = batch_rename(files);
+        let result = run_rename_for_test(files, None);
 
-        // Should return error because one file failed
+        // Should return error because one source is missing
         assert!(result.is_err());
         let error = result.unwrap_err();
         assert!(error.contains("Failed to rename"));
+        assert!(error.contains("source does not exist"));
 
-        // The real file should still have been renamed
-        assert!(file_new.exists());
-        assert!(!file_old.exists());
+        // Validation runs first, so the real file is left untouched
+        assert!(!file_new.exists());
+        assert!(file_old.exists());
     }
 
-    /// Tests renaming a file to a path that already exists.
+    /// Tests renaming a file to a path that already exists with the overwrite policy.
     #[test]
     fn test_batch_rename_overwrites_existing() {
         let temp_dir = tempdir().expect("Failed to create temp dir");
@@ -491,7 +1419,7 @@ mod tests {
             target.to_string_lossy().to_string(),
         )];
 
-        let result = batch_rename(files);
+        let result = run_rename_for_test(files, Some(OverwritePolicy::Overwrite));
 
         // On most systems, rename will overwrite the target
         assert!(result.is_ok());
@@ -503,6 +1431,36 @@ mod tests {
         assert_eq!(content, "source content");
     }
 
+    /// Tests that an existing target is refused without an overwrite policy.
+    #[test]
+    fn test_batch_rename_refuses_existing_target() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+
+        let source = temp_dir.path().join("source.txt");
+        let target = temp_dir.path().join("target.txt");
+        File::create(&source)
+            .unwrap()
+            .write_all(b"source content")
+            .unwrap();
+        File::create(&target)
+            .unwrap()
+            .write_all(b"target content")
+            .unwrap();
+
+        let result = run_rename_for_test(
+            vec![(
+                source.to_string_lossy().to_string(),
+                target.to_string_lossy().to_string(),
+            )],
+            Some(OverwritePolicy::Refuse),
+        );
+
+        let error = result.unwrap_err();
+        assert!(error.contains("already exists"));
+        assert!(source.exists());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "target content");
+    }
+
     // ==================== list_files_recursively tests ====================
 
     /// Tests listing files in a directory with nested subdirectories.
@@ -721,6 +1679,45 @@ mod tests {
         assert!(json.contains("\"failed\":2"));
     }
 
+    /// Tests RenameProgress::Copying serialization.
+    #[test]
+    fn test_rename_progress_copying_serialization() {
+        let progress = RenameProgress::Copying {
+            current_path: "/mnt/usb/video.mp4".to_string(),
+            bytes_copied: 1024,
+            total_bytes: 4096,
+        };
+        let json = serde_json::to_string(&progress).unwrap();
+        assert!(json.contains("\"type\":\"copying\""));
+        assert!(json.contains("\"currentPath\":\"/mnt/usb/video.mp4\""));
+        assert!(json.contains("\"bytesCopied\":1024"));
+        assert!(json.contains("\"totalBytes\":4096"));
+    }
+
+    /// Tests RenameProgress::Cancelled serialization.
+    #[test]
+    fn test_rename_progress_cancelled_serialization() {
+        let progress = RenameProgress::Cancelled {
+            successful: 3,
+            failed: 1,
+            total: 10,
+        };
+        let json = serde_json::to_string(&progress).unwrap();
+        assert!(json.contains("\"type\":\"cancelled\""));
+        assert!(json.contains("\"successful\":3"));
+        assert!(json.contains("\"failed\":1"));
+        assert!(json.contains("\"total\":10"));
+    }
+
+    /// Tests ListProgress::Cancelled serialization.
+    #[test]
+    fn test_list_progress_cancelled_serialization() {
+        let progress = ListProgress::Cancelled { files_found: 250 };
+        let json = serde_json::to_string(&progress).unwrap();
+        assert!(json.contains("\"type\":\"cancelled\""));
+        assert!(json.contains("\"filesFound\":250"));
+    }
+
     // ==================== Progress Types Round-Trip Tests ====================
 
     /// Tests ListProgress round-trip serialization/deserialization.
@@ -1107,7 +2104,7 @@ mod tests {
             ));
         }
 
-        let result = batch_rename(rename_pairs);
+        let result = run_rename_for_test(rename_pairs, None);
 
         assert!(result.is_ok());
         let renamed = result.unwrap();
@@ -1132,10 +2129,13 @@ mod tests {
         let mut file = File::create(&old_path).unwrap();
         file.write_all(content.as_bytes()).unwrap();
 
-        let result = batch_rename(vec![(
-            old_path.to_string_lossy().to_string(),
-            new_path.to_string_lossy().to_string(),
-        )]);
+        let result = run_rename_for_test(
+            vec![(
+                old_path.to_string_lossy().to_string(),
+                new_path.to_string_lossy().to_string(),
+            )],
+            None,
+        );
 
         assert!(result.is_ok());
 
@@ -1154,14 +2154,666 @@ mod tests {
 
         File::create(&old_path).unwrap();
 
-        let result = batch_rename(vec![(
-            old_path.to_string_lossy().to_string(),
-            new_path.to_string_lossy().to_string(),
-        )]);
+        let result = run_rename_for_test(
+            vec![(
+                old_path.to_string_lossy().to_string(),
+                new_path.to_string_lossy().to_string(),
+            )],
+            None,
+        );
 
         assert!(result.is_ok());
         assert!(new_path.exists());
         assert!(!old_path.exists());
     }
+
+    // ==================== Rename Planning Tests ====================
+
+    /// Helper to build rename pairs from string slices.
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect()
+    }
+
+    /// Helper to create files with their own name as content.
+    fn create_named_files(dir: &Path, names: &[&str]) {
+        for name in names {
+            File::create(dir.join(name))
+                .unwrap()
+                .write_all(name.as_bytes())
+                .unwrap();
+        }
+    }
+
+    /// Helper to build a rename pair inside a directory.
+    fn dir_pair(dir: &Path, old: &str, new: &str) -> (String, String) {
+        (
+            dir.join(old).to_string_lossy().to_string(),
+            dir.join(new).to_string_lossy().to_string(),
+        )
+    }
+
+    /// Tests that independent renames are planned directly in input order.
+    #[test]
+    fn test_plan_renames_independent() {
+        let plan = plan_renames(&pairs(&[("/a", "/x"), ("/b", "/y")]));
+        assert_eq!(
+            plan.steps,
+            vec![RenameStep::Direct(0), RenameStep::Direct(1)]
+        );
+        assert_eq!(plan.blockers, vec![None, None]);
+    }
+
+    /// Tests that a chain renames its last link first.
+    #[test]
+    fn test_plan_renames_chain() {
+        // a -> b must wait until b -> c has freed b
+        let plan = plan_renames(&pairs(&[("/a", "/b"), ("/b", "/c")]));
+        assert_eq!(
+            plan.steps,
+            vec![RenameStep::Direct(1), RenameStep::Direct(0)]
+        );
+        assert_eq!(plan.blockers, vec![Some(1), None]);
+    }
+
+    /// Tests that a swap is broken with a temporary name.
+    #[test]
+    fn test_plan_renames_swap() {
+        let plan = plan_renames(&pairs(&[("/a", "/b"), ("/b", "/a")]));
+        assert_eq!(
+            plan.steps,
+            vec![
+                RenameStep::ToTemp(0),
+                RenameStep::Direct(1),
+                RenameStep::FromTemp(0),
+            ]
+        );
+    }
+
+    /// Tests that a rename onto itself is a single direct step.
+    #[test]
+    fn test_plan_renames_identity() {
+        let plan = plan_renames(&pairs(&[("/a", "/a")]));
+        assert_eq!(plan.steps, vec![RenameStep::Direct(0)]);
+        assert_eq!(plan.blockers, vec![None]);
+    }
+
+    /// Tests that a chain feeding into a cycle is only resolved after the cycle.
+    #[test]
+    fn test_plan_renames_chain_into_cycle() {
+        // x -> a waits on the a <-> b swap
+        let plan = plan_renames(&pairs(&[("/x", "/a"), ("/a", "/b"), ("/b", "/a2")]));
+        // No cycle here: b -> a2 first, then a -> b, then x -> a
+        assert_eq!(
+            plan.steps,
+            vec![
+                RenameStep::Direct(2),
+                RenameStep::Direct(1),
+                RenameStep::Direct(0),
+            ]
+        );
+
+        let plan = plan_renames(&pairs(&[("/x", "/a"), ("/a", "/b"), ("/b", "/a")]));
+        // Every pair gets exactly one final step
+        let finals = plan
+            .steps
+            .iter()
+            .filter(|s| !matches!(s, RenameStep::ToTemp(_)))
+            .count();
+        assert_eq!(finals, 3);
+        assert_eq!(
+            plan.steps
+                .iter()
+                .filter(|s| matches!(s, RenameStep::ToTemp(_)))
+                .count(),
+            1
+        );
+    }
+
+    /// Tests swapping two files.
+    #[test]
+    fn test_batch_rename_swap() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a.txt", "b.txt"]);
+
+        let result = run_rename_for_test(
+            vec![
+                dir_pair(dir.path(), "a.txt", "b.txt"),
+                dir_pair(dir.path(), "b.txt", "a.txt"),
+            ],
+            None,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "b.txt"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("b.txt")).unwrap(),
+            "a.txt"
+        );
+        // No temporary files are left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    /// Tests rotating names across a 3-cycle.
+    #[test]
+    fn test_batch_rename_three_cycle() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a", "b", "c"]);
+
+        let result = run_rename_for_test(
+            vec![
+                dir_pair(dir.path(), "a", "b"),
+                dir_pair(dir.path(), "b", "c"),
+                dir_pair(dir.path(), "c", "a"),
+            ],
+            None,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(dir.path().join("b")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.path().join("c")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(dir.path().join("a")).unwrap(), "c");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    /// Tests a chain mixed with independent renames and a swap.
+    #[test]
+    fn test_batch_rename_chain_with_independent() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["1", "2", "3", "solo", "x", "y"]);
+
+        // Requested in an order that would clobber files if run sequentially
+        let result = run_rename_for_test(
+            vec![
+                dir_pair(dir.path(), "1", "2"),
+                dir_pair(dir.path(), "solo", "alone"),
+                dir_pair(dir.path(), "2", "3"),
+                dir_pair(dir.path(), "x", "y"),
+                dir_pair(dir.path(), "3", "4"),
+                dir_pair(dir.path(), "y", "x"),
+            ],
+            None,
+        );
+
+        let renamed = result.unwrap();
+        // Results are reported in input order
+        assert!(renamed[0].ends_with("2"));
+        assert!(renamed[1].ends_with("alone"));
+
+        assert_eq!(fs::read_to_string(dir.path().join("2")).unwrap(), "1");
+        assert_eq!(fs::read_to_string(dir.path().join("3")).unwrap(), "2");
+        assert_eq!(fs::read_to_string(dir.path().join("4")).unwrap(), "3");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("alone")).unwrap(),
+            "solo"
+        );
+        assert_eq!(fs::read_to_string(dir.path().join("x")).unwrap(), "y");
+        assert_eq!(fs::read_to_string(dir.path().join("y")).unwrap(), "x");
+        assert!(!dir.path().join("1").exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 6);
+    }
+
+    /// Tests that a failed link keeps its dependents from overwriting it.
+    #[test]
+    fn test_batch_rename_chain_failure_protects_dependents() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a", "b"]);
+
+        // b cannot move into a missing directory, so a must not overwrite b.
+        // Validation would refuse this plan, so drive the executor directly.
+        let outcomes = execute_renames(
+            &[
+                dir_pair(dir.path(), "a", "b"),
+                dir_pair(dir.path(), "b", "missing/b"),
+            ],
+            &CancellationToken::default(),
+            |_| {},
+        );
+
+        assert!(outcomes[1].is_err());
+        assert!(outcomes[0]
+            .as_ref()
+            .unwrap_err()
+            .contains("could not be renamed"));
+        assert_eq!(fs::read_to_string(dir.path().join("a")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.path().join("b")).unwrap(), "b");
+    }
+
+    // ==================== Cancellation Tests ====================
+
+    /// Tests a cancelled batch skips the remaining pairs without touching them.
+    #[test]
+    fn test_execute_renames_cancelled_mid_batch() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a", "b", "c"]);
+        let cancel = CancellationToken::default();
+
+        let mut events = 0;
+        let outcomes = execute_renames(
+            &[
+                dir_pair(dir.path(), "a", "x"),
+                dir_pair(dir.path(), "b", "y"),
+                dir_pair(dir.path(), "c", "z"),
+            ],
+            &cancel,
+            |_| {
+                events += 1;
+                cancel.cancel();
+            },
+        );
+
+        assert_eq!(events, 1);
+        assert!(outcomes[0].is_ok());
+        assert!(outcomes[1].as_ref().unwrap_err().contains("cancelled"));
+        assert!(outcomes[2].as_ref().unwrap_err().contains("cancelled"));
+        assert_eq!(dir_names(dir.path()), vec!["b", "c", "x"]);
+    }
+
+    /// Tests a cycle that has started moving is finished despite cancellation.
+    #[test]
+    fn test_execute_renames_cancel_finishes_cycle() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a", "b"]);
+        let cancel = CancellationToken::default();
+
+        let outcomes = execute_renames(
+            &[
+                dir_pair(dir.path(), "a", "b"),
+                dir_pair(dir.path(), "b", "a"),
+            ],
+            &cancel,
+            |_| cancel.cancel(),
+        );
+
+        assert!(outcomes.iter().all(|outcome| outcome.is_ok()));
+        assert_eq!(fs::read_to_string(dir.path().join("a")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(dir.path().join("b")).unwrap(), "a");
+        // No file is left under a temporary name
+        assert_eq!(dir_names(dir.path()), vec!["a", "b"]);
+    }
+
+    /// Tests a journal failure is reported with the renames instead of being dropped.
+    #[test]
+    fn test_batch_rename_reports_journal_failure() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("a.txt");
+        let new = dir.path().join("b.txt");
+        let blocker = dir.path().join("blocker");
+        File::create(&old).unwrap();
+        File::create(&blocker).unwrap();
+        // The journal cannot be written below a regular file
+        let journal = RenameJournal::new(blocker.join("journal.json"));
+        let files = vec![(
+            old.to_string_lossy().to_string(),
+            new.to_string_lossy().to_string(),
+        )];
+
+        let run = run_batch_rename(
+            &files,
+            OverwritePolicy::Refuse,
+            None,
+            Some(&journal),
+            &CancellationToken::default(),
+            |_| {},
+        )
+        .unwrap();
+        let result = collect_rename_outcomes(&files, run).unwrap();
+
+        assert_eq!(result.renamed, vec![new.to_string_lossy().to_string()]);
+        assert_eq!(result.batch_id, None);
+        assert!(result.journal_error.unwrap().contains("cannot be undone"));
+        assert!(new.exists());
+    }
+
+    /// Tests a journaled batch returns its batch id and can be undone.
+    #[test]
+    fn test_batch_rename_is_journaled() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("a.txt");
+        let new = dir.path().join("b.txt");
+        File::create(&old).unwrap();
+        let journal = RenameJournal::new(dir.path().join("journal.json"));
+        let files = vec![(
+            old.to_string_lossy().to_string(),
+            new.to_string_lossy().to_string(),
+        )];
+
+        let run = run_batch_rename(
+            &files,
+            OverwritePolicy::Refuse,
+            None,
+            Some(&journal),
+            &CancellationToken::default(),
+            |_| {},
+        )
+        .unwrap();
+        let result = collect_rename_outcomes(&files, run).unwrap();
+
+        let batches = journal.batches().unwrap();
+        assert_eq!(result.batch_id, Some(batches[0].id.clone()));
+        assert_eq!(result.journal_error, None);
+        assert_eq!(batches[0].entries[0].new_path, files[0].1);
+
+        let undo = journal.undo(result.batch_id.as_deref()).unwrap();
+        assert_eq!(undo.restored, vec![files[0].0.clone()]);
+        assert!(old.exists());
+        assert!(!new.exists());
+    }
+
+    /// Tests a batch reaching outside its root renames nothing, and the root is journaled.
+    #[test]
+    fn test_run_batch_rename_within_root() {
+        let dir = tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap().join("root");
+        fs::create_dir(&root).unwrap();
+        let old = root.join("a.txt");
+        File::create(&old).unwrap();
+        let journal = RenameJournal::new(dir.path().join("journal.json"));
+        let path = |p: &Path| p.to_string_lossy().to_string();
+
+        // The target escapes the root through `..`
+        let escaping = vec![(path(&old), path(&root.join("../a.txt")))];
+        let result = run_batch_rename(
+            &escaping,
+            OverwritePolicy::Refuse,
+            Some(&root),
+            Some(&journal),
+            &CancellationToken::default(),
+            |_| {},
+        );
+        assert!(result.unwrap_err().contains("is outside the approved root"));
+        assert!(old.exists());
+
+        let inside = vec![(path(&old), path(&root.join("b.txt")))];
+        run_batch_rename(
+            &inside,
+            OverwritePolicy::Refuse,
+            Some(&root),
+            Some(&journal),
+            &CancellationToken::default(),
+            |_| {},
+        )
+        .unwrap();
+        assert!(root.join("b.txt").exists());
+        assert_eq!(journal.batches().unwrap()[0].root, Some(path(&root)));
+    }
+
+    /// Tests a batch cancelled before it starts renames nothing.
+    #[test]
+    fn test_execute_renames_cancelled_before_start() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a", "b"]);
+        let cancel = CancellationToken::default();
+        cancel.cancel();
+
+        let outcomes = execute_renames(
+            &[
+                dir_pair(dir.path(), "a", "b"),
+                dir_pair(dir.path(), "b", "a"),
+            ],
+            &cancel,
+            |_| panic!("no pair should finish"),
+        );
+
+        assert!(outcomes.iter().all(|outcome| outcome.is_err()));
+        assert_eq!(fs::read_to_string(dir.path().join("a")).unwrap(), "a");
+        assert_eq!(dir_names(dir.path()), vec!["a", "b"]);
+    }
+
+    // ==================== Rename Validation Tests ====================
+
+    /// Tests that a clean plan validates.
+    #[test]
+    fn test_validate_rename_plan_valid() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a.txt", "b.txt"]);
+
+        let report = validate_rename_plan(vec![
+            dir_pair(dir.path(), "a.txt", "b.txt"),
+            dir_pair(dir.path(), "b.txt", "c.txt"),
+        ]);
+
+        // b.txt exists but is moved away by the batch itself
+        assert!(report.is_valid);
+        assert!(report.existing_targets.is_empty());
+    }
+
+    /// Tests that duplicate targets are grouped with all their sources.
+    #[test]
+    fn test_validate_rename_plan_duplicate_targets() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a", "b", "c"]);
+
+        let report = validate_rename_plan(vec![
+            dir_pair(dir.path(), "a", "same"),
+            dir_pair(dir.path(), "b", "same"),
+            dir_pair(dir.path(), "c", "other"),
+        ]);
+
+        assert!(!report.is_valid);
+        assert_eq!(report.duplicate_targets.len(), 1);
+        let (target, sources) = &report.duplicate_targets[0];
+        assert!(target.ends_with("same"));
+        assert_eq!(sources.len(), 2);
+    }
+
+    /// Tests that targets existing outside the batch are reported.
+    #[test]
+    fn test_validate_rename_plan_existing_target() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a", "taken"]);
+
+        let report = validate_rename_plan(vec![dir_pair(dir.path(), "a", "taken")]);
+
+        assert!(!report.is_valid);
+        assert_eq!(report.existing_targets.len(), 1);
+        assert!(report.existing_targets[0].1.ends_with("taken"));
+    }
+
+    /// Tests that missing sources and missing target directories are reported.
+    #[test]
+    fn test_validate_rename_plan_missing_paths() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a"]);
+
+        let report = validate_rename_plan(vec![
+            dir_pair(dir.path(), "ghost", "b"),
+            dir_pair(dir.path(), "a", "no/such/dir/a"),
+        ]);
+
+        assert!(!report.is_valid);
+        assert_eq!(report.missing_sources.len(), 1);
+        assert!(report.missing_sources[0].ends_with("ghost"));
+        assert_eq!(report.missing_directories.len(), 1);
+        assert!(report.missing_directories[0].1.ends_with("no/such/dir/a"));
+    }
+
+    /// Tests that names with control characters are reported.
+    #[test]
+    fn test_validate_rename_plan_invalid_names() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["a"]);
+
+        let report = validate_rename_plan(vec![dir_pair(dir.path(), "a", "bad\tname")]);
+
+        assert!(!report.is_valid);
+        assert_eq!(report.invalid_names.len(), 1);
+        assert!(report.invalid_names[0].1.contains("control character"));
+    }
+
+    /// Tests the file name rules, including the Windows-only ones.
+    #[test]
+    fn test_invalid_name_reason() {
+        assert_eq!(invalid_name_reason("photo.jpg", false), None);
+        assert_eq!(invalid_name_reason("photo.jpg", true), None);
+        assert!(invalid_name_reason("", false).is_some());
+        assert!(invalid_name_reason("..", false).is_some());
+        assert!(invalid_name_reason("a\0b", false).is_some());
+
+        // Allowed on Unix, rejected by Windows
+        assert_eq!(invalid_name_reason("a:b?.txt", false), None);
+        assert!(invalid_name_reason("a:b?.txt", true).is_some());
+        assert!(invalid_name_reason("trailing.", true).is_some());
+        assert!(invalid_name_reason("con.txt", true).is_some());
+        assert_eq!(invalid_name_reason("console.txt", true), None);
+    }
+
+    /// Tests that the overwrite policy only relaxes existing-target checks.
+    #[test]
+    fn test_rename_validation_blocking_errors() {
+        let report = RenameValidation {
+            existing_targets: vec![("/a".to_string(), "/b".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(report.blocking_errors(OverwritePolicy::Refuse).len(), 1);
+        assert!(report
+            .blocking_errors(OverwritePolicy::Overwrite)
+            .is_empty());
+
+        let report = RenameValidation {
+            existing_targets: vec![("/a".to_string(), "/b".to_string())],
+            missing_sources: vec!["/c".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(report.blocking_errors(OverwritePolicy::Overwrite).len(), 1);
+    }
+
+    /// Tests OverwritePolicy serialization and its default.
+    #[test]
+    fn test_overwrite_policy_serialization() {
+        assert_eq!(OverwritePolicy::default(), OverwritePolicy::Refuse);
+        let json = serde_json::to_string(&OverwritePolicy::Overwrite).unwrap();
+        assert_eq!(json, "\"overwrite\"");
+        let de: OverwritePolicy = serde_json::from_str("\"refuse\"").unwrap();
+        assert_eq!(de, OverwritePolicy::Refuse);
+    }
+
+    // ==================== Case-Only Rename Tests ====================
+
+    /// Helper to read the inode number of a file.
+    #[cfg(unix)]
+    fn inode(path: &Path) -> u64 {
+        use std::os::unix::fs::MetadataExt;
+        fs::symlink_metadata(path).unwrap().ino()
+    }
+
+    /// Helper to list the file names in a directory, sorted.
+    fn dir_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Tests detection of renames that only change letter case.
+    #[test]
+    fn test_is_case_only_change() {
+        assert!(is_case_only_change(
+            Path::new("/d/Photo.JPG"),
+            Path::new("/d/photo.jpg")
+        ));
+        assert!(!is_case_only_change(
+            Path::new("/d/photo.jpg"),
+            Path::new("/d/photo.jpg")
+        ));
+        assert!(!is_case_only_change(
+            Path::new("/d/Photo.JPG"),
+            Path::new("/e/photo.jpg")
+        ));
+        assert!(!is_case_only_change(
+            Path::new("/d/Photo.JPG"),
+            Path::new("/d/photo2.jpg")
+        ));
+    }
+
+    /// Tests a case-only rename keeps the same file under the new name.
+    #[cfg(unix)]
+    #[test]
+    fn test_batch_rename_case_only() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let source = dir.path().join("Photo.JPG");
+        File::create(&source).unwrap().write_all(b"pixels").unwrap();
+        let original_inode = inode(&source);
+
+        let result =
+            run_rename_for_test(vec![dir_pair(dir.path(), "Photo.JPG", "photo.jpg")], None);
+
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(dir_names(dir.path()), vec!["photo.jpg"]);
+        assert_eq!(inode(&dir.path().join("photo.jpg")), original_inode);
+        assert_eq!(fs::read(dir.path().join("photo.jpg")).unwrap(), b"pixels");
+    }
+
+    /// Tests case-only renames on a simulated case-insensitive filesystem.
+    ///
+    /// A hard link stands in for the case-insensitive lookup: both names
+    /// resolve to the same inode, and a direct `fs::rename` between them is a
+    /// silent no-op, just like on vfat or exFAT.
+    #[cfg(unix)]
+    #[test]
+    fn test_batch_rename_case_only_simulated_case_insensitive() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let source = dir.path().join("Photo.JPG");
+        let target = dir.path().join("photo.jpg");
+        File::create(&source).unwrap();
+        fs::hard_link(&source, &target).unwrap();
+        let original_inode = inode(&source);
+
+        // A direct rename does nothing, which is the bug being worked around
+        fs::rename(&source, &target).unwrap();
+        assert_eq!(dir_names(dir.path()), vec!["Photo.JPG", "photo.jpg"]);
+
+        let files = vec![dir_pair(dir.path(), "Photo.JPG", "photo.jpg")];
+        assert!(check_rename_plan(&files).is_valid);
+
+        let result = run_rename_for_test(files, None);
+
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(dir_names(dir.path()), vec!["photo.jpg"]);
+        assert_eq!(inode(&target), original_inode);
+    }
+
+    /// Tests case-only renames still refuse to replace a different file.
+    #[cfg(unix)]
+    #[test]
+    fn test_batch_rename_case_only_refuses_different_file() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["Photo.JPG", "photo.jpg"]);
+
+        let result =
+            run_rename_for_test(vec![dir_pair(dir.path(), "Photo.JPG", "photo.jpg")], None);
+
+        assert!(result.unwrap_err().contains("already exists"));
+        assert_eq!(dir_names(dir.path()), vec!["Photo.JPG", "photo.jpg"]);
+    }
+
+    /// Tests case-only renames mixed with a swap in the same batch.
+    #[test]
+    fn test_batch_rename_case_only_with_swap() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        create_named_files(dir.path(), &["A.txt", "b", "c"]);
+        fs::write(dir.path().join("b"), "b").unwrap();
+        fs::write(dir.path().join("c"), "c").unwrap();
+
+        let result = run_rename_for_test(
+            vec![
+                dir_pair(dir.path(), "A.txt", "a.txt"),
+                dir_pair(dir.path(), "b", "c"),
+                dir_pair(dir.path(), "c", "b"),
+            ],
+            None,
+        );
+
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(dir_names(dir.path()), vec!["a.txt", "b", "c"]);
+        assert_eq!(fs::read_to_string(dir.path().join("b")).unwrap(), "c");
+    }
 }
 
@@ -0,0 +1,822 @@
+//! Rename pipelines.
+//!
+//! A pipeline is an ordered list of rename steps (replace, regex replace,
+//! insert, remove, case change, trim, numbering, extension change) applied to
+//! each file name in turn. Pipelines are plain serializable data so the
+//! frontend can build, save and send them, and the preview reports the name
+//! after every step to make a misbehaving step easy to spot. Executing a
+//! pipeline renames files to exactly the names the preview computed.
+
+use crate::approved_roots::ApprovedRoots;
+use crate::journal::RenameJournal;
+use crate::rename::{rename_within_root, BatchRenameResult, OverwritePolicy};
+use crate::rename_rules::{
+    apply_numbering, compute_diff, split_extension, DiffSegment, NumberingOptions, RenameEngine,
+    RenameOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+// ==================== Types ====================
+
+/// Case transformation applied by a `ChangeCase` step
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseMode {
+    /// all lowercase
+    Lower,
+    /// ALL UPPERCASE
+    Upper,
+    /// First Letter Of Each Word
+    Title,
+    /// First letter of the name only
+    Sentence,
+}
+
+/// A single step of a rename pipeline
+///
+/// Unless noted otherwise, steps operate on the base name and leave the
+/// extension untouched.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PipelineStep {
+    /// Replace literal text
+    Replace {
+        /// Text to find
+        find: String,
+        /// Replacement text
+        replace: String,
+        /// Whether matching is case-sensitive
+        #[serde(default)]
+        case_sensitive: bool,
+        /// Replace only the first occurrence
+        #[serde(default)]
+        first_only: bool,
+        /// Also search the extension
+        #[serde(default)]
+        include_ext: bool,
+    },
+    /// Replace regex matches, with `$1`/`$<name>` references in the replacement
+    RegexReplace {
+        /// Regex pattern to find
+        pattern: String,
+        /// Replacement template
+        replace: String,
+        /// Whether matching is case-sensitive
+        #[serde(default)]
+        case_sensitive: bool,
+        /// Replace only the first match
+        #[serde(default)]
+        first_only: bool,
+        /// Also search the extension
+        #[serde(default)]
+        include_ext: bool,
+    },
+    /// Insert text at a character index (clamped to the base name length)
+    Insert {
+        /// Text to insert
+        text: String,
+        /// Character index to insert at
+        index: usize,
+        /// Count the index from the end of the base name
+        #[serde(default)]
+        from_end: bool,
+    },
+    /// Remove a range of characters
+    RemoveRange {
+        /// Character index where removal starts
+        start: usize,
+        /// Number of characters to remove
+        count: usize,
+        /// Count `start` from the end of the base name
+        #[serde(default)]
+        from_end: bool,
+    },
+    /// Change the letter case
+    ChangeCase {
+        /// Case transformation to apply
+        mode: CaseMode,
+        /// Also change the case of the extension
+        #[serde(default)]
+        include_ext: bool,
+    },
+    /// Trim characters from both ends of the base name
+    Trim {
+        /// Characters to trim; whitespace when empty
+        #[serde(default)]
+        characters: String,
+    },
+    /// Add a sequence number (the `enabled` flag is ignored)
+    Numbering(NumberingOptions),
+    /// Replace the extension; an empty extension removes it
+    ChangeExtension {
+        /// New extension, with or without the leading dot
+        extension: String,
+    },
+}
+
+/// An ordered list of rename steps
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RenamePipeline {
+    /// Steps, applied in order
+    pub steps: Vec<PipelineStep>,
+}
+
+/// The name after a single pipeline step
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StepPreview {
+    /// Index of the step in the pipeline
+    pub step_index: usize,
+    /// File name after this step
+    pub name: String,
+    /// Whether this step changed the name
+    pub changed: bool,
+}
+
+/// Preview of a pipeline applied to a single file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PipelinePreview {
+    /// Full path of the original file
+    pub path: String,
+    /// Original file name
+    pub name: String,
+    /// File name after all steps
+    pub new_name: String,
+    /// Full path with the new file name
+    pub new_path: String,
+    /// The name after each step, in pipeline order
+    pub steps: Vec<StepPreview>,
+    /// Character diff from `name` to `new_name`
+    pub diff: Vec<DiffSegment>,
+}
+
+// ==================== Step Helpers ====================
+
+/// Applies `transform` to the base name, or to the whole name when
+/// `include_ext` is set.
+fn map_base_name(name: &str, include_ext: bool, transform: impl Fn(&str) -> String) -> String {
+    if include_ext {
+        return transform(name);
+    }
+
+    let (base_name, extension) = split_extension(name);
+    format!("{}{}", transform(base_name), extension)
+}
+
+/// Returns the byte offset of character index `index`, clamped to the end.
+fn byte_index(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map(|(byte, _)| byte)
+        .unwrap_or(text.len())
+}
+
+/// Resolves a character index that may be counted from the end.
+fn resolve_index(text: &str, index: usize, from_end: bool) -> usize {
+    let len = text.chars().count();
+    if from_end {
+        len.saturating_sub(index)
+    } else {
+        index.min(len)
+    }
+}
+
+/// Applies a case transformation.
+fn change_case(text: &str, mode: CaseMode) -> String {
+    match mode {
+        CaseMode::Lower => text.to_lowercase(),
+        CaseMode::Upper => text.to_uppercase(),
+        CaseMode::Title => {
+            let mut result = String::with_capacity(text.len());
+            let mut word_start = true;
+            for c in text.chars() {
+                if word_start {
+                    result.extend(c.to_uppercase());
+                } else {
+                    result.extend(c.to_lowercase());
+                }
+                word_start = c.is_whitespace() || c == '_' || c == '-' || c == '.';
+            }
+            result
+        }
+        CaseMode::Sentence => {
+            let mut chars = text.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+// ==================== Compiled Pipeline ====================
+
+/// A pipeline step with its patterns compiled
+enum CompiledStep {
+    /// A replace or regex replace step
+    Replace(RenameEngine),
+    /// Any step that needs no preparation
+    Plain(PipelineStep),
+}
+
+/// A pipeline ready to apply to file names
+///
+/// Regex patterns are compiled once so the same pipeline can be applied to
+/// every file in a batch.
+pub struct CompiledPipeline {
+    steps: Vec<CompiledStep>,
+}
+
+impl RenamePipeline {
+    /// Validates the pipeline and compiles its patterns.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CompiledPipeline)` - The pipeline, ready to apply to file names
+    /// * `Err(String)` - If a regex step has an invalid pattern; the message
+    ///   names the step (1-based)
+    pub fn compile(&self) -> Result<CompiledPipeline, String> {
+        let steps = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let options = match step {
+                    PipelineStep::Replace {
+                        find,
+                        replace,
+                        case_sensitive,
+                        first_only,
+                        include_ext,
+                    } => RenameOptions {
+                        find_text: find.clone(),
+                        replace_text: replace.clone(),
+                        case_sensitive: *case_sensitive,
+                        regex_mode: false,
+                        replace_first_only: *first_only,
+                        include_ext: *include_ext,
+                        ..Default::default()
+                    },
+                    PipelineStep::RegexReplace {
+                        pattern,
+                        replace,
+                        case_sensitive,
+                        first_only,
+                        include_ext,
+                    } => RenameOptions {
+                        find_text: pattern.clone(),
+                        replace_text: replace.clone(),
+                        case_sensitive: *case_sensitive,
+                        regex_mode: true,
+                        replace_first_only: *first_only,
+                        include_ext: *include_ext,
+                        ..Default::default()
+                    },
+                    other => return Ok(CompiledStep::Plain(other.clone())),
+                };
+
+                RenameEngine::new(options)
+                    .map(CompiledStep::Replace)
+                    .map_err(|e| format!("Step {}: {}", index + 1, e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(CompiledPipeline { steps })
+    }
+}
+
+impl CompiledPipeline {
+    /// Applies one step to a file name.
+    fn apply_step(step: &CompiledStep, name: &str, file_index: usize) -> String {
+        let step = match step {
+            CompiledStep::Replace(engine) => return engine.replace(name).0,
+            CompiledStep::Plain(step) => step,
+        };
+
+        match step {
+            PipelineStep::Insert {
+                text,
+                index,
+                from_end,
+            } => map_base_name(name, false, |base| {
+                let at = byte_index(base, resolve_index(base, *index, *from_end));
+                format!("{}{}{}", &base[..at], text, &base[at..])
+            }),
+            PipelineStep::RemoveRange {
+                start,
+                count,
+                from_end,
+            } => map_base_name(name, false, |base| {
+                let first = resolve_index(base, *start, *from_end);
+                let from = byte_index(base, first);
+                let to = byte_index(base, first.saturating_add(*count));
+                format!("{}{}", &base[..from], &base[to..])
+            }),
+            PipelineStep::ChangeCase { mode, include_ext } => {
+                map_base_name(name, *include_ext, |text| change_case(text, *mode))
+            }
+            PipelineStep::Trim { characters } => map_base_name(name, false, |base| {
+                if characters.is_empty() {
+                    base.trim().to_string()
+                } else {
+                    base.trim_matches(|c| characters.contains(c)).to_string()
+                }
+            }),
+            PipelineStep::Numbering(options) => {
+                let options = NumberingOptions {
+                    enabled: true,
+                    ..options.clone()
+                };
+                apply_numbering(name, file_index, &options).0
+            }
+            PipelineStep::ChangeExtension { extension } => {
+                let (base_name, _) = split_extension(name);
+                let extension = extension.trim_start_matches('.');
+                if extension.is_empty() {
+                    base_name.to_string()
+                } else {
+                    format!("{}.{}", base_name, extension)
+                }
+            }
+            PipelineStep::Replace { .. } | PipelineStep::RegexReplace { .. } => {
+                unreachable!("replace steps are compiled")
+            }
+        }
+    }
+
+    /// Applies every step to a single file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Full path of the file
+    /// * `file_index` - Position of the file in the batch, used for numbering
+    ///
+    /// # Returns
+    ///
+    /// A `PipelinePreview` with the final name and the name after each step.
+    pub fn preview(&self, path: &str, file_index: usize) -> PipelinePreview {
+        let file_path = Path::new(path);
+        let name = file_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+
+        let mut current = name.clone();
+        let mut steps = Vec::with_capacity(self.steps.len());
+
+        for (step_index, step) in self.steps.iter().enumerate() {
+            let next = Self::apply_step(step, &current, file_index);
+            steps.push(StepPreview {
+                step_index,
+                changed: next != current,
+                name: next.clone(),
+            });
+            current = next;
+        }
+
+        let new_path = match file_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                parent.join(&current).to_string_lossy().to_string()
+            }
+            _ => current.clone(),
+        };
+
+        PipelinePreview {
+            path: path.to_string(),
+            diff: compute_diff(&name, &current),
+            name,
+            new_name: current,
+            new_path,
+            steps,
+        }
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Applies a rename pipeline to a list of files without renaming anything.
+///
+/// # Arguments
+///
+/// * `paths` - Full paths of the files, in the order used for numbering
+/// * `pipeline` - The steps to apply to each file name
+///
+/// # Returns
+///
+/// * `Ok(Vec<PipelinePreview>)` - One preview per path, in input order
+/// * `Err(String)` - If a regex step has an invalid pattern
+///
+/// # Example
+///
+/// ```ignore
+/// let pipeline = RenamePipeline {
+///     steps: vec![
+///         PipelineStep::Replace { find: "IMG_".into(), replace: "".into(), ..},
+///         PipelineStep::ChangeCase { mode: CaseMode::Lower, include_ext: true },
+///     ],
+/// };
+/// let previews = preview_pipeline(vec!["/photos/IMG_Beach.JPG".to_string()], pipeline)?;
+/// assert_eq!(previews[0].new_name, "beach.jpg");
+/// ```
+#[tauri::command]
+pub fn preview_pipeline(
+    paths: Vec<String>,
+    pipeline: RenamePipeline,
+) -> Result<Vec<PipelinePreview>, String> {
+    let compiled = pipeline.compile()?;
+
+    Ok(paths
+        .iter()
+        .enumerate()
+        .map(|(index, path)| compiled.preview(path, index))
+        .collect())
+}
+
+/// Computes the rename pairs for a pipeline from its preview.
+///
+/// Files whose name the pipeline leaves unchanged are left out.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)` - (old path, new path) pairs, in input order
+/// * `Err(String)` - If a regex step has an invalid pattern
+fn pipeline_renames(
+    paths: Vec<String>,
+    pipeline: RenamePipeline,
+) -> Result<Vec<(String, String)>, String> {
+    Ok(preview_pipeline(paths, pipeline)?
+        .into_iter()
+        .filter(|preview| preview.new_path != preview.path)
+        .map(|preview| (preview.path, preview.new_path))
+        .collect())
+}
+
+/// Renames files with a rename pipeline.
+///
+/// The new names are computed by `preview_pipeline`, so each file gets exactly
+/// the name its preview showed. The renames then go through the same
+/// validation, ordering, approved-root check and undo journal as
+/// `batch_rename`. Files whose name does not change are left alone.
+///
+/// # Arguments
+///
+/// * `paths` - Full paths of the files, in the order used for numbering
+/// * `pipeline` - The steps to apply to each file name
+/// * `overwrite_policy` - How to treat existing targets (defaults to `Refuse`)
+/// * `root` - Root from `choose_operation_root`; every old and new path must be
+///   inside it
+/// * `journal` - The rename journal managed by Tauri
+/// * `roots` - The approved roots managed by Tauri
+///
+/// # Returns
+///
+/// * `Ok(BatchRenameResult)` - The new paths, and the journal batch id or why
+///   the batch could not be recorded
+/// * `Err(String)` - If a regex step has an invalid pattern, or a
+///   newline-separated string of all rename errors
+#[tauri::command]
+pub fn rename_with_pipeline(
+    paths: Vec<String>,
+    pipeline: RenamePipeline,
+    overwrite_policy: Option<OverwritePolicy>,
+    root: String,
+    journal: State<'_, RenameJournal>,
+    roots: State<'_, ApprovedRoots>,
+) -> Result<BatchRenameResult, String> {
+    let root = roots.resolve(&root)?;
+    let files = pipeline_renames(paths, pipeline)?;
+    rename_within_root(
+        &files,
+        overwrite_policy.unwrap_or_default(),
+        &root,
+        &journal,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rename_rules::NumberingPosition;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// Helper to run a pipeline on a single name.
+    fn run(name: &str, steps: Vec<PipelineStep>) -> String {
+        RenamePipeline { steps }
+            .compile()
+            .unwrap()
+            .preview(name, 0)
+            .new_name
+    }
+
+    /// Helper to build a literal replace step.
+    fn replace(find: &str, replace: &str) -> PipelineStep {
+        PipelineStep::Replace {
+            find: find.to_string(),
+            replace: replace.to_string(),
+            case_sensitive: false,
+            first_only: false,
+            include_ext: false,
+        }
+    }
+
+    // ==================== Step Tests ====================
+
+    /// Tests literal and regex replace steps.
+    #[test]
+    fn test_replace_steps() {
+        assert_eq!(run("IMG_001.jpg", vec![replace("img_", "")]), "001.jpg");
+
+        let regex = PipelineStep::RegexReplace {
+            pattern: r"(\d{4})(\d{2})(\d{2})".to_string(),
+            replace: "$1-$2-$3".to_string(),
+            case_sensitive: false,
+            first_only: false,
+            include_ext: false,
+        };
+        assert_eq!(run("20240131.jpg", vec![regex]), "2024-01-31.jpg");
+    }
+
+    /// Tests inserting text from the start and the end.
+    #[test]
+    fn test_insert_step() {
+        let insert = |index, from_end| PipelineStep::Insert {
+            text: "_x".to_string(),
+            index,
+            from_end,
+        };
+        assert_eq!(run("abcd.txt", vec![insert(2, false)]), "ab_xcd.txt");
+        assert_eq!(run("abcd.txt", vec![insert(1, true)]), "abc_xd.txt");
+        assert_eq!(run("abcd.txt", vec![insert(99, false)]), "abcd_x.txt");
+    }
+
+    /// Tests removing character ranges, clamped to the base name.
+    #[test]
+    fn test_remove_range_step() {
+        let remove = |start, count, from_end| PipelineStep::RemoveRange {
+            start,
+            count,
+            from_end,
+        };
+        assert_eq!(run("abcdef.txt", vec![remove(1, 2, false)]), "adef.txt");
+        assert_eq!(run("abcdef.txt", vec![remove(2, 5, true)]), "abcd.txt");
+        assert_eq!(run("abcdef.txt", vec![remove(4, 99, false)]), "abcd.txt");
+        assert_eq!(run("äöü.txt", vec![remove(0, 1, false)]), "öü.txt");
+    }
+
+    /// Tests each case transformation.
+    #[test]
+    fn test_change_case_step() {
+        let case = |mode, include_ext| PipelineStep::ChangeCase { mode, include_ext };
+        assert_eq!(
+            run("My File.TXT", vec![case(CaseMode::Lower, false)]),
+            "my file.TXT"
+        );
+        assert_eq!(
+            run("My File.TXT", vec![case(CaseMode::Lower, true)]),
+            "my file.txt"
+        );
+        assert_eq!(
+            run("my file.txt", vec![case(CaseMode::Upper, false)]),
+            "MY FILE.txt"
+        );
+        assert_eq!(
+            run("hello_big-WORLD.txt", vec![case(CaseMode::Title, false)]),
+            "Hello_Big-World.txt"
+        );
+        assert_eq!(
+            run("hELLO World.txt", vec![case(CaseMode::Sentence, false)]),
+            "Hello world.txt"
+        );
+    }
+
+    /// Tests trimming whitespace and custom characters.
+    #[test]
+    fn test_trim_step() {
+        let trim = |characters: &str| PipelineStep::Trim {
+            characters: characters.to_string(),
+        };
+        assert_eq!(run("  name .txt", vec![trim("")]), "name.txt");
+        assert_eq!(run("__name-_.txt", vec![trim("_-")]), "name.txt");
+    }
+
+    /// Tests numbering steps use the file's position and ignore `enabled`.
+    #[test]
+    fn test_numbering_step() {
+        let pipeline = RenamePipeline {
+            steps: vec![PipelineStep::Numbering(NumberingOptions {
+                padding: 3,
+                position: NumberingPosition::End,
+                ..Default::default()
+            })],
+        };
+        let compiled = pipeline.compile().unwrap();
+
+        assert_eq!(compiled.preview("a.txt", 0).new_name, "a-001.txt");
+        assert_eq!(compiled.preview("b.txt", 4).new_name, "b-005.txt");
+    }
+
+    /// Tests changing and removing the extension.
+    #[test]
+    fn test_change_extension_step() {
+        let ext = |extension: &str| PipelineStep::ChangeExtension {
+            extension: extension.to_string(),
+        };
+        assert_eq!(run("photo.jpeg", vec![ext("jpg")]), "photo.jpg");
+        assert_eq!(run("photo.jpeg", vec![ext(".png")]), "photo.png");
+        assert_eq!(run("photo.jpeg", vec![ext("")]), "photo");
+        assert_eq!(run("README", vec![ext("md")]), "README.md");
+    }
+
+    // ==================== Pipeline Tests ====================
+
+    /// Tests steps are applied in order with a preview after each step.
+    #[test]
+    fn test_pipeline_applies_steps_in_order() {
+        let pipeline = RenamePipeline {
+            steps: vec![
+                replace("IMG_", ""),
+                PipelineStep::RegexReplace {
+                    pattern: r"(\d{4})(\d{2})(\d{2})".to_string(),
+                    replace: "$1-$2-$3".to_string(),
+                    case_sensitive: false,
+                    first_only: true,
+                    include_ext: false,
+                },
+                PipelineStep::ChangeCase {
+                    mode: CaseMode::Lower,
+                    include_ext: true,
+                },
+                PipelineStep::Numbering(NumberingOptions {
+                    separator: "_".to_string(),
+                    ..Default::default()
+                }),
+            ],
+        };
+
+        let previews = preview_pipeline(
+            vec![
+                "/p/IMG_20240131.JPG".to_string(),
+                "/p/IMG_20240201.JPG".to_string(),
+            ],
+            pipeline,
+        )
+        .unwrap();
+
+        let names: Vec<&str> = previews[0].steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "20240131.JPG",
+                "2024-01-31.JPG",
+                "2024-01-31.jpg",
+                "1_2024-01-31.jpg"
+            ]
+        );
+        assert_eq!(previews[0].new_path, "/p/1_2024-01-31.jpg");
+        assert_eq!(previews[1].new_name, "2_2024-02-01.jpg");
+    }
+
+    /// Tests steps that leave the name unchanged are flagged.
+    #[test]
+    fn test_pipeline_reports_unchanged_steps() {
+        let preview = RenamePipeline {
+            steps: vec![replace("missing", "x"), replace("a", "b")],
+        }
+        .compile()
+        .unwrap()
+        .preview("/p/a.txt", 0);
+
+        assert!(!preview.steps[0].changed);
+        assert!(preview.steps[1].changed);
+        assert_eq!(preview.new_name, "b.txt");
+    }
+
+    /// Tests an empty pipeline leaves names unchanged.
+    #[test]
+    fn test_empty_pipeline() {
+        let previews =
+            preview_pipeline(vec!["/p/a.txt".to_string()], RenamePipeline::default()).unwrap();
+        assert_eq!(previews[0].new_name, "a.txt");
+        assert!(previews[0].steps.is_empty());
+    }
+
+    /// Tests invalid regex steps are reported with their position.
+    #[test]
+    fn test_pipeline_invalid_regex() {
+        let pipeline = RenamePipeline {
+            steps: vec![
+                replace("a", "b"),
+                PipelineStep::RegexReplace {
+                    pattern: "[".to_string(),
+                    replace: String::new(),
+                    case_sensitive: false,
+                    first_only: false,
+                    include_ext: false,
+                },
+            ],
+        };
+
+        let err = preview_pipeline(vec!["/p/a.txt".to_string()], pipeline).unwrap_err();
+        assert!(err.starts_with("Step 2:"));
+    }
+
+    // ==================== Execution Tests ====================
+
+    /// Tests files are renamed to their previewed names and the batch is journaled.
+    #[test]
+    fn test_pipeline_renames_match_preview() {
+        let dir = tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        let paths: Vec<String> = ["IMG_b.jpg", "IMG_a.jpg", "notes.txt"]
+            .iter()
+            .map(|name| {
+                let path = root.join(name);
+                fs::write(&path, name).unwrap();
+                path.to_string_lossy().to_string()
+            })
+            .collect();
+        let pipeline = RenamePipeline {
+            steps: vec![
+                replace("img_", ""),
+                PipelineStep::Numbering(NumberingOptions {
+                    position: NumberingPosition::Start,
+                    separator: "-".to_string(),
+                    ..Default::default()
+                }),
+            ],
+        };
+        let previews = preview_pipeline(paths.clone(), pipeline.clone()).unwrap();
+        let journal = RenameJournal::new(root.join("journal.json"));
+
+        let files = pipeline_renames(paths, pipeline).unwrap();
+        let result = rename_within_root(&files, OverwritePolicy::Refuse, &root, &journal).unwrap();
+
+        let expected: Vec<String> = previews.iter().map(|p| p.new_path.clone()).collect();
+        assert_eq!(result.renamed, expected);
+        assert_eq!(fs::read_to_string(&expected[1]).unwrap(), "IMG_a.jpg");
+        assert_eq!(
+            result.batch_id,
+            Some(journal.batches().unwrap()[0].id.clone())
+        );
+    }
+
+    /// Tests files the pipeline leaves unchanged are not renamed.
+    #[test]
+    fn test_pipeline_renames_skip_unchanged() {
+        let paths = vec!["/p/IMG_1.jpg".to_string(), "/p/notes.txt".to_string()];
+
+        let files = pipeline_renames(
+            paths,
+            RenamePipeline {
+                steps: vec![replace("img_", "")],
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            files,
+            vec![("/p/IMG_1.jpg".to_string(), "/p/1.jpg".to_string())]
+        );
+    }
+
+    // ==================== Serialization Tests ====================
+
+    /// Tests pipelines deserialize from tagged JSON steps with defaults.
+    #[test]
+    fn test_pipeline_deserialization() {
+        let json = r#"{ "steps": [
+            { "type": "replace", "find": "a", "replace": "b" },
+            { "type": "regexReplace", "pattern": "x", "replace": "y", "firstOnly": true },
+            { "type": "insert", "text": "-", "index": 1 },
+            { "type": "removeRange", "start": 0, "count": 2, "fromEnd": true },
+            { "type": "changeCase", "mode": "title" },
+            { "type": "trim" },
+            { "type": "numbering", "padding": 2, "position": "end" },
+            { "type": "changeExtension", "extension": "md" }
+        ] }"#;
+        let pipeline: RenamePipeline = serde_json::from_str(json).unwrap();
+
+        assert_eq!(pipeline.steps.len(), 8);
+        assert!(matches!(
+            pipeline.steps[1],
+            PipelineStep::RegexReplace {
+                first_only: true,
+                case_sensitive: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &pipeline.steps[6],
+            PipelineStep::Numbering(options) if options.padding == 2 && options.increment == 1
+        ));
+
+        let round_trip: RenamePipeline =
+            serde_json::from_str(&serde_json::to_string(&pipeline).unwrap()).unwrap();
+        assert_eq!(round_trip, pipeline);
+    }
+}
@@ -0,0 +1,996 @@
+//! Rename rule engine.
+//!
+//! This module computes new file names from the renamer's find/replace and
+//! numbering options, so the backend can produce and verify names with the
+//! same `regex` crate semantics used elsewhere in the app. The preview
+//! command also returns match, replacement and diff segments so the UI only
+//! has to render them.
+
+use regex::{Captures, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// ==================== Types ====================
+
+/// Where the sequence number is placed in the file name
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberingPosition {
+    /// Before the base name
+    #[default]
+    Start,
+    /// After the base name, before the extension
+    End,
+    /// At a character index inside the base name
+    Index,
+}
+
+/// Sequence numbering options, matching the renamer's numbering controls
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NumberingOptions {
+    /// Whether numbering is applied at all
+    pub enabled: bool,
+    /// Number given to the first file
+    pub start_number: i64,
+    /// Amount added for each following file
+    pub increment: i64,
+    /// Minimum number of digits (zero-padded)
+    pub padding: usize,
+    /// Text placed between the number and the name
+    pub separator: String,
+    /// Where the number is placed
+    pub position: NumberingPosition,
+    /// Character index used when `position` is `Index`
+    pub insert_index: usize,
+}
+
+impl Default for NumberingOptions {
+    fn default() -> Self {
+        NumberingOptions {
+            enabled: false,
+            start_number: 1,
+            increment: 1,
+            padding: 1,
+            separator: "-".to_string(),
+            position: NumberingPosition::Start,
+            insert_index: 0,
+        }
+    }
+}
+
+/// Find/replace and numbering options for computing new names
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RenameOptions {
+    /// Text or regex pattern to find
+    pub find_text: String,
+    /// Replacement text; supports `$1`, `$<name>`, `$&`, `` $` ``, `$'` and `$$`
+    pub replace_text: String,
+    /// Whether matching is case-sensitive
+    pub case_sensitive: bool,
+    /// Whether `find_text` is a regular expression
+    pub regex_mode: bool,
+    /// Replace only the first match instead of all matches
+    pub replace_first_only: bool,
+    /// Whether the extension is part of the text being searched
+    pub include_ext: bool,
+    /// Sequence numbering applied after find/replace
+    pub numbering: NumberingOptions,
+}
+
+/// A highlighted span of text.
+///
+/// Offsets are character (not byte) positions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MatchSegment {
+    /// Start character offset
+    pub start: usize,
+    /// End character offset (exclusive)
+    pub end: usize,
+    /// 0 for a whole match, 1+ for capture groups, -1 for literal replacement text
+    pub group_index: i32,
+    /// The highlighted text
+    pub content: String,
+}
+
+/// Kind of change in a diff segment
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffSegmentType {
+    /// Text only present in the new name
+    Added,
+    /// Text only present in the original name
+    Removed,
+    /// Text present in both names
+    Unchanged,
+}
+
+/// A run of characters in a diff between the original and new name
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DiffSegment {
+    /// Kind of change
+    #[serde(rename = "type")]
+    pub kind: DiffSegmentType,
+    /// The text of the run
+    pub text: String,
+}
+
+/// Numbering applied to a file name, for color-coded preview display
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NumberingInfo {
+    /// The zero-padded number
+    pub formatted_number: String,
+    /// The separator that was used
+    pub separator: String,
+    /// Where the number was placed
+    pub position: NumberingPosition,
+    /// Character index in the base name where the numbering was inserted
+    pub insert_index: usize,
+}
+
+/// Preview of the new name for a single file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RenamePreview {
+    /// Full path of the original file
+    pub path: String,
+    /// Original file name
+    pub name: String,
+    /// New file name after find/replace and numbering
+    pub new_name: String,
+    /// Full path with the new file name
+    pub new_path: String,
+    /// File name after find/replace, before numbering
+    pub name_after_replace: String,
+    /// Matches (and capture groups) in the original name
+    pub matches: Vec<MatchSegment>,
+    /// Segments of `name_after_replace` produced by the replacement
+    pub replacement_segments: Vec<MatchSegment>,
+    /// Numbering that was applied, if enabled
+    pub numbering: Option<NumberingInfo>,
+    /// Character diff from `name` to `new_name`
+    pub diff: Vec<DiffSegment>,
+}
+
+// ==================== Name Helpers ====================
+
+/// Splits a file name into base name and extension (including the dot).
+///
+/// Names whose only dot is the first character (hidden files such as
+/// `.bashrc`) have no extension.
+pub(crate) fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(index) if index > 0 => name.split_at(index),
+        _ => (name, ""),
+    }
+}
+
+/// Converts a byte offset in `text` into a character offset.
+pub(crate) fn char_offset(text: &str, byte_offset: usize) -> usize {
+    text[..byte_offset].chars().count()
+}
+
+/// Formats a number with leading zeros up to `padding` digits.
+pub(crate) fn format_number(number: i64, padding: usize) -> String {
+    if number < 0 {
+        format!("-{:0>width$}", number.unsigned_abs(), width = padding)
+    } else {
+        format!("{:0>width$}", number, width = padding)
+    }
+}
+
+/// Inserts the sequence number for the file at `file_index` into `name`.
+///
+/// # Arguments
+///
+/// * `name` - The file name to number
+/// * `file_index` - Position of the file in the batch (0-based)
+/// * `options` - Numbering options
+///
+/// # Returns
+///
+/// The numbered name and the numbering that was applied, or the name
+/// unchanged and `None` if numbering is disabled.
+pub(crate) fn apply_numbering(
+    name: &str,
+    file_index: usize,
+    options: &NumberingOptions,
+) -> (String, Option<NumberingInfo>) {
+    if !options.enabled {
+        return (name.to_string(), None);
+    }
+
+    let number = options
+        .start_number
+        .saturating_add((file_index as i64).saturating_mul(options.increment));
+    let formatted = format_number(number, options.padding);
+    let separator = &options.separator;
+
+    let (base_name, extension) = split_extension(name);
+    let base_len = base_name.chars().count();
+
+    let insert_index = match options.position {
+        NumberingPosition::Start => 0,
+        NumberingPosition::End => base_len,
+        NumberingPosition::Index => options.insert_index.min(base_len),
+    };
+
+    let inserted = match options.position {
+        NumberingPosition::Start => format!("{}{}", formatted, separator),
+        NumberingPosition::End => format!("{}{}", separator, formatted),
+        NumberingPosition::Index if insert_index == 0 => format!("{}{}", formatted, separator),
+        NumberingPosition::Index if insert_index == base_len => {
+            format!("{}{}", separator, formatted)
+        }
+        NumberingPosition::Index => format!("{}{}{}", separator, formatted, separator),
+    };
+
+    let split_at = base_name
+        .char_indices()
+        .nth(insert_index)
+        .map(|(byte, _)| byte)
+        .unwrap_or(base_name.len());
+
+    let numbered = format!(
+        "{}{}{}{}",
+        &base_name[..split_at],
+        inserted,
+        &base_name[split_at..],
+        extension
+    );
+
+    let info = NumberingInfo {
+        formatted_number: formatted,
+        separator: separator.clone(),
+        position: options.position,
+        insert_index,
+    };
+
+    (numbered, Some(info))
+}
+
+// ==================== Replacement ====================
+
+/// Appends `content` to `output`, recording it as a segment of `group_index`.
+fn push_segment(
+    output: &mut String,
+    segments: &mut Vec<MatchSegment>,
+    content: &str,
+    group_index: i32,
+) {
+    if content.is_empty() {
+        return;
+    }
+
+    let start = output.chars().count();
+    output.push_str(content);
+    segments.push(MatchSegment {
+        start,
+        end: start + content.chars().count(),
+        group_index,
+        content: content.to_string(),
+    });
+}
+
+/// Expands a JavaScript-style replacement template for one match.
+///
+/// Supports `$$`, `$&`, `` $` ``, `$'`, `$n`/`$nn` and `$<name>`. As in
+/// JavaScript, a reference to a group that does not exist is kept as literal
+/// text, and `$nn` falls back to `$n` followed by a digit when group `nn`
+/// does not exist.
+fn expand_replacement(
+    caps: &Captures,
+    regex: &Regex,
+    haystack: &str,
+    template: &str,
+    output: &mut String,
+    segments: &mut Vec<MatchSegment>,
+) {
+    let whole = caps.get(0).expect("group 0 always participates");
+    let mut literal = String::new();
+    let mut rest = template;
+
+    // Flushes pending literal text as a "new text" segment
+    fn flush(literal: &mut String, output: &mut String, segments: &mut Vec<MatchSegment>) {
+        push_segment(output, segments, literal, -1);
+        literal.clear();
+    }
+
+    while let Some(dollar) = rest.find('$') {
+        literal.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let mut chars = after.chars();
+
+        match chars.next() {
+            Some('$') => {
+                literal.push('$');
+                rest = &after[1..];
+            }
+            Some('&') => {
+                flush(&mut literal, output, segments);
+                push_segment(output, segments, whole.as_str(), 0);
+                rest = &after[1..];
+            }
+            Some('`') => {
+                // Text from the original name is not highlighted
+                flush(&mut literal, output, segments);
+                output.push_str(&haystack[..whole.start()]);
+                rest = &after[1..];
+            }
+            Some('\'') => {
+                flush(&mut literal, output, segments);
+                output.push_str(&haystack[whole.end()..]);
+                rest = &after[1..];
+            }
+            Some('<') => {
+                let named = after[1..]
+                    .find('>')
+                    .map(|close| &after[1..close + 1])
+                    .and_then(|name| {
+                        regex
+                            .capture_names()
+                            .position(|n| n == Some(name))
+                            .map(|index| (name.len() + 2, index))
+                    });
+
+                match named {
+                    Some((consumed, index)) => {
+                        flush(&mut literal, output, segments);
+                        if let Some(group) = caps.get(index) {
+                            push_segment(output, segments, group.as_str(), index as i32);
+                        }
+                        rest = &after[consumed..];
+                    }
+                    None => {
+                        literal.push('$');
+                        rest = after;
+                    }
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let group_count = regex.captures_len();
+                let two_digit = after
+                    .get(..2)
+                    .filter(|s| s.bytes().all(|b| b.is_ascii_digit()))
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .filter(|&n| n > 0 && n < group_count);
+                let one_digit =
+                    Some(c as usize - '0' as usize).filter(|&n| n > 0 && n < group_count);
+
+                match two_digit.map(|n| (n, 2)).or(one_digit.map(|n| (n, 1))) {
+                    Some((index, consumed)) => {
+                        flush(&mut literal, output, segments);
+                        if let Some(group) = caps.get(index) {
+                            push_segment(output, segments, group.as_str(), index as i32);
+                        }
+                        rest = &after[consumed..];
+                    }
+                    None => {
+                        literal.push('$');
+                        rest = after;
+                    }
+                }
+            }
+            _ => {
+                literal.push('$');
+                rest = after;
+            }
+        }
+    }
+
+    literal.push_str(rest);
+    flush(&mut literal, output, segments);
+}
+
+// ==================== Diff ====================
+
+/// Computes a character-level diff between two strings.
+///
+/// Uses a longest-common-subsequence table and merges consecutive characters
+/// of the same kind into a single segment.
+pub(crate) fn compute_diff(original: &str, modified: &str) -> Vec<DiffSegment> {
+    if original == modified {
+        return vec![DiffSegment {
+            kind: DiffSegmentType::Unchanged,
+            text: original.to_string(),
+        }];
+    }
+
+    let a: Vec<char> = original.chars().collect();
+    let b: Vec<char> = modified.chars().collect();
+    let (m, n) = (a.len(), b.len());
+
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; n + 1]; m + 1];
+    for i in (0..m).rev() {
+        for j in (0..n).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    let mut push = |kind: DiffSegmentType, c: char| match segments.last_mut() {
+        Some(last) if last.kind == kind => last.text.push(c),
+        _ => segments.push(DiffSegment {
+            kind,
+            text: c.to_string(),
+        }),
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < m || j < n {
+        if i < m && j < n && a[i] == b[j] {
+            push(DiffSegmentType::Unchanged, a[i]);
+            i += 1;
+            j += 1;
+        } else if i < m && (j == n || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push(DiffSegmentType::Removed, a[i]);
+            i += 1;
+        } else {
+            push(DiffSegmentType::Added, b[j]);
+            j += 1;
+        }
+    }
+
+    segments
+}
+
+// ==================== Rule Engine ====================
+
+/// Computes new names for files from a set of rename options.
+///
+/// The pattern is compiled once, so the same engine can be applied to every
+/// file in a batch.
+#[derive(Debug, Clone)]
+pub struct RenameEngine {
+    options: RenameOptions,
+    regex: Option<Regex>,
+}
+
+impl RenameEngine {
+    /// Creates an engine for the given options.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RenameEngine)` - The engine, ready to apply to file names
+    /// * `Err(String)` - If `find_text` is not a valid regex in regex mode
+    pub fn new(options: RenameOptions) -> Result<Self, String> {
+        let regex = if options.find_text.is_empty() {
+            None
+        } else {
+            let pattern = if options.regex_mode {
+                options.find_text.clone()
+            } else {
+                regex::escape(&options.find_text)
+            };
+
+            Some(
+                RegexBuilder::new(&pattern)
+                    .case_insensitive(!options.case_sensitive)
+                    .build()
+                    .map_err(|e| e.to_string())?,
+            )
+        };
+
+        Ok(RenameEngine { options, regex })
+    }
+
+    /// Applies find/replace to a file name.
+    ///
+    /// # Returns
+    ///
+    /// The replaced name, the matches in the original name, and the segments
+    /// of the replaced name produced by the replacement.
+    pub(crate) fn replace(&self, name: &str) -> (String, Vec<MatchSegment>, Vec<MatchSegment>) {
+        let Some(regex) = &self.regex else {
+            return (name.to_string(), Vec::new(), Vec::new());
+        };
+
+        let (target, extension) = if self.options.include_ext {
+            (name, "")
+        } else {
+            split_extension(name)
+        };
+
+        let mut matches = Vec::new();
+        let mut segments = Vec::new();
+        let mut output = String::new();
+        let mut last_end = 0;
+
+        let limit = if self.options.replace_first_only {
+            1
+        } else {
+            usize::MAX
+        };
+
+        for caps in regex.captures_iter(target).take(limit) {
+            for (group_index, group) in caps.iter().enumerate() {
+                if let Some(group) = group {
+                    matches.push(MatchSegment {
+                        start: char_offset(target, group.start()),
+                        end: char_offset(target, group.end()),
+                        group_index: group_index as i32,
+                        content: group.as_str().to_string(),
+                    });
+                }
+            }
+
+            let whole = caps.get(0).expect("group 0 always participates");
+            output.push_str(&target[last_end..whole.start()]);
+            expand_replacement(
+                &caps,
+                regex,
+                target,
+                &self.options.replace_text,
+                &mut output,
+                &mut segments,
+            );
+            last_end = whole.end();
+        }
+
+        output.push_str(&target[last_end..]);
+        output.push_str(extension);
+
+        (output, matches, segments)
+    }
+
+    /// Computes the new name for a single file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Full path of the file
+    /// * `file_index` - Position of the file in the batch, used for numbering
+    ///
+    /// # Returns
+    ///
+    /// A `RenamePreview` with the new name and all highlight segments.
+    pub fn preview(&self, path: &str, file_index: usize) -> RenamePreview {
+        let file_path = Path::new(path);
+        let name = file_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+
+        let (name_after_replace, matches, replacement_segments) = self.replace(&name);
+        let (new_name, numbering) =
+            apply_numbering(&name_after_replace, file_index, &self.options.numbering);
+
+        let new_path = match file_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                parent.join(&new_name).to_string_lossy().to_string()
+            }
+            _ => new_name.clone(),
+        };
+
+        RenamePreview {
+            path: path.to_string(),
+            diff: compute_diff(&name, &new_name),
+            name,
+            new_name,
+            new_path,
+            name_after_replace,
+            matches,
+            replacement_segments,
+            numbering,
+        }
+    }
+}
+
+// ==================== Tauri Commands ====================
+
+/// Computes the new names for a list of files without renaming anything.
+///
+/// Applies find/replace (literal or regex, with capture groups) and then
+/// sequence numbering to each file name, in list order.
+///
+/// # Arguments
+///
+/// * `paths` - Full paths of the files, in the order used for numbering
+/// * `options` - Find/replace and numbering options
+///
+/// # Returns
+///
+/// * `Ok(Vec<RenamePreview>)` - One preview per path, in input order
+/// * `Err(String)` - If the regex pattern is invalid
+///
+/// # Example
+///
+/// ```ignore
+/// let options = RenameOptions {
+///     find_text: r"(\d+)-(\w+)".to_string(),
+///     replace_text: "$2-$1".to_string(),
+///     regex_mode: true,
+///     ..Default::default()
+/// };
+/// let previews = preview_renames(vec!["/photos/01-beach.jpg".to_string()], options)?;
+/// assert_eq!(previews[0].new_name, "beach-01.jpg");
+/// ```
+#[tauri::command]
+pub fn preview_renames(
+    paths: Vec<String>,
+    options: RenameOptions,
+) -> Result<Vec<RenamePreview>, String> {
+    let engine = RenameEngine::new(options)?;
+
+    Ok(paths
+        .iter()
+        .enumerate()
+        .map(|(index, path)| engine.preview(path, index))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper to build options for a find/replace.
+    fn replace_options(find: &str, replace: &str) -> RenameOptions {
+        RenameOptions {
+            find_text: find.to_string(),
+            replace_text: replace.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Helper to build regex options.
+    fn regex_options(find: &str, replace: &str) -> RenameOptions {
+        RenameOptions {
+            regex_mode: true,
+            ..replace_options(find, replace)
+        }
+    }
+
+    /// Helper to compute a single new name.
+    fn new_name(name: &str, options: RenameOptions) -> String {
+        RenameEngine::new(options)
+            .unwrap()
+            .preview(name, 0)
+            .new_name
+    }
+
+    // ==================== Find/Replace Tests ====================
+
+    /// Tests literal replacement is case-insensitive by default.
+    #[test]
+    fn test_literal_replace_case_insensitive() {
+        assert_eq!(
+            new_name("Photo_photo.jpg", replace_options("photo", "img")),
+            "img_img.jpg"
+        );
+    }
+
+    /// Tests literal replacement respects case sensitivity.
+    #[test]
+    fn test_literal_replace_case_sensitive() {
+        let options = RenameOptions {
+            case_sensitive: true,
+            ..replace_options("photo", "img")
+        };
+        assert_eq!(new_name("Photo_photo.jpg", options), "Photo_img.jpg");
+    }
+
+    /// Tests literal mode escapes regex metacharacters.
+    #[test]
+    fn test_literal_replace_escapes_special_characters() {
+        assert_eq!(
+            new_name("a.b(1).txt", replace_options("(1)", "")),
+            "a.b.txt"
+        );
+        assert_eq!(new_name("a.b.txt", replace_options(".", "_")), "a_b.txt");
+    }
+
+    /// Tests replacing only the first match.
+    #[test]
+    fn test_replace_first_only() {
+        let options = RenameOptions {
+            replace_first_only: true,
+            ..replace_options("a", "o")
+        };
+        assert_eq!(new_name("banana.txt", options), "bonana.txt");
+    }
+
+    /// Tests the extension is excluded unless requested.
+    #[test]
+    fn test_include_extension() {
+        assert_eq!(new_name("txt.txt", replace_options("txt", "md")), "md.txt");
+
+        let options = RenameOptions {
+            include_ext: true,
+            ..replace_options("txt", "md")
+        };
+        assert_eq!(new_name("txt.txt", options), "md.md");
+    }
+
+    /// Tests hidden files without an extension are fully searched.
+    #[test]
+    fn test_hidden_file_has_no_extension() {
+        assert_eq!(split_extension(".bashrc"), (".bashrc", ""));
+        assert_eq!(split_extension("archive.tar.gz"), ("archive.tar", ".gz"));
+        assert_eq!(
+            new_name(".bashrc", replace_options("rc", "_old")),
+            ".bash_old"
+        );
+    }
+
+    /// Tests an empty find text leaves names unchanged.
+    #[test]
+    fn test_empty_find_text() {
+        let preview = RenameEngine::new(replace_options("", "x"))
+            .unwrap()
+            .preview("/dir/file.txt", 0);
+        assert_eq!(preview.new_name, "file.txt");
+        assert!(preview.matches.is_empty());
+    }
+
+    // ==================== Regex Tests ====================
+
+    /// Tests capture groups can be reordered.
+    #[test]
+    fn test_regex_capture_groups() {
+        assert_eq!(
+            new_name("01-beach.jpg", regex_options(r"(\d+)-(\w+)", "$2-$1")),
+            "beach-01.jpg"
+        );
+    }
+
+    /// Tests named groups and whole-match references.
+    #[test]
+    fn test_regex_named_groups_and_whole_match() {
+        assert_eq!(
+            new_name("2024.txt", regex_options(r"(?<year>\d{4})", "y$<year>")),
+            "y2024.txt"
+        );
+        assert_eq!(new_name("abc.txt", regex_options("b", "[$&]")), "a[b]c.txt");
+    }
+
+    /// Tests JavaScript-compatible handling of special replacement tokens.
+    #[test]
+    fn test_regex_replacement_tokens() {
+        // $$ is a literal dollar
+        assert_eq!(new_name("cost.txt", regex_options("cost", "$$5")), "$5.txt");
+        // Missing groups are kept as literal text
+        assert_eq!(new_name("abc.txt", regex_options("(a)", "$2")), "$2bc.txt");
+        // $10 falls back to $1 followed by 0 when there is no group 10
+        assert_eq!(new_name("abc.txt", regex_options("(a)", "$10")), "a0bc.txt");
+        // Text before and after the match
+        assert_eq!(new_name("abc.txt", regex_options("b", "$`")), "aac.txt");
+        assert_eq!(new_name("abc.txt", regex_options("b", "$'")), "acc.txt");
+    }
+
+    /// Tests invalid regex patterns are rejected.
+    #[test]
+    fn test_regex_invalid_pattern() {
+        assert!(RenameEngine::new(regex_options("[invalid", "")).is_err());
+        assert!(preview_renames(vec!["/a.txt".to_string()], regex_options("(", "")).is_err());
+    }
+
+    /// Tests zero-length matches do not loop forever.
+    #[test]
+    fn test_regex_zero_length_matches() {
+        assert_eq!(new_name("ab.txt", regex_options("x*", "-")), "-a-b-.txt");
+    }
+
+    /// Tests match segments cover the whole match and each capture group.
+    #[test]
+    fn test_regex_match_segments() {
+        let preview = RenameEngine::new(regex_options(r"(\d+)-(\w+)", "$2_$1"))
+            .unwrap()
+            .preview("/p/01-beach.jpg", 0);
+
+        assert_eq!(
+            preview.matches,
+            vec![
+                MatchSegment {
+                    start: 0,
+                    end: 8,
+                    group_index: 0,
+                    content: "01-beach".to_string()
+                },
+                MatchSegment {
+                    start: 0,
+                    end: 2,
+                    group_index: 1,
+                    content: "01".to_string()
+                },
+                MatchSegment {
+                    start: 3,
+                    end: 8,
+                    group_index: 2,
+                    content: "beach".to_string()
+                },
+            ]
+        );
+
+        let groups: Vec<(usize, usize, i32)> = preview
+            .replacement_segments
+            .iter()
+            .map(|s| (s.start, s.end, s.group_index))
+            .collect();
+        assert_eq!(groups, vec![(0, 5, 2), (5, 6, -1), (6, 8, 1)]);
+        assert_eq!(preview.new_path, "/p/beach_01.jpg");
+    }
+
+    /// Tests segment offsets are character offsets for non-ASCII names.
+    #[test]
+    fn test_regex_segments_use_character_offsets() {
+        let preview = RenameEngine::new(regex_options(r"\d+", "N"))
+            .unwrap()
+            .preview("файл12.txt", 0);
+
+        assert_eq!(preview.new_name, "файлN.txt");
+        assert_eq!(preview.matches[0].start, 4);
+        assert_eq!(preview.matches[0].end, 6);
+        assert_eq!(preview.replacement_segments[0].start, 4);
+    }
+
+    // ==================== Numbering Tests ====================
+
+    /// Helper to build enabled numbering options.
+    fn numbering(position: NumberingPosition, padding: usize) -> NumberingOptions {
+        NumberingOptions {
+            enabled: true,
+            padding,
+            position,
+            ..Default::default()
+        }
+    }
+
+    /// Tests number formatting with padding.
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(1, 3), "001");
+        assert_eq!(format_number(1234, 3), "1234");
+        assert_eq!(format_number(-5, 3), "-005");
+    }
+
+    /// Tests numbering at the start and end of the base name.
+    #[test]
+    fn test_numbering_start_and_end() {
+        let (name, info) = apply_numbering("photo.jpg", 0, &numbering(NumberingPosition::Start, 3));
+        assert_eq!(name, "001-photo.jpg");
+        assert_eq!(info.unwrap().insert_index, 0);
+
+        let (name, info) = apply_numbering("photo.jpg", 1, &numbering(NumberingPosition::End, 2));
+        assert_eq!(name, "photo-02.jpg");
+        assert_eq!(info.unwrap().insert_index, 5);
+    }
+
+    /// Tests numbering at an index, including clamping to the base name.
+    #[test]
+    fn test_numbering_at_index() {
+        let mut options = numbering(NumberingPosition::Index, 1);
+        options.insert_index = 2;
+        assert_eq!(apply_numbering("abcd.txt", 0, &options).0, "ab-1-cd.txt");
+
+        options.insert_index = 0;
+        assert_eq!(apply_numbering("abcd.txt", 0, &options).0, "1-abcd.txt");
+
+        options.insert_index = 99;
+        assert_eq!(apply_numbering("abcd.txt", 0, &options).0, "abcd-1.txt");
+    }
+
+    /// Tests start number, increment and separator.
+    #[test]
+    fn test_numbering_start_increment_separator() {
+        let options = NumberingOptions {
+            start_number: 10,
+            increment: 5,
+            separator: "_".to_string(),
+            ..numbering(NumberingPosition::Start, 1)
+        };
+        assert_eq!(apply_numbering("a.txt", 2, &options).0, "20_a.txt");
+    }
+
+    /// Tests numbering is skipped when disabled.
+    #[test]
+    fn test_numbering_disabled() {
+        let (name, info) = apply_numbering("a.txt", 3, &NumberingOptions::default());
+        assert_eq!(name, "a.txt");
+        assert!(info.is_none());
+    }
+
+    /// Tests numbering is applied after find/replace and follows list order.
+    #[test]
+    fn test_preview_renames_replace_then_number() {
+        let options = RenameOptions {
+            numbering: numbering(NumberingPosition::Start, 2),
+            ..replace_options("IMG_", "")
+        };
+        let previews = preview_renames(
+            vec!["/p/IMG_a.jpg".to_string(), "/p/IMG_b.jpg".to_string()],
+            options,
+        )
+        .unwrap();
+
+        assert_eq!(previews[0].name_after_replace, "a.jpg");
+        assert_eq!(previews[0].new_name, "01-a.jpg");
+        assert_eq!(previews[1].new_name, "02-b.jpg");
+        assert_eq!(previews[1].new_path, "/p/02-b.jpg");
+    }
+
+    // ==================== Diff Tests ====================
+
+    /// Tests identical strings produce one unchanged segment.
+    #[test]
+    fn test_compute_diff_identical() {
+        let diff = compute_diff("same.txt", "same.txt");
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].kind, DiffSegmentType::Unchanged);
+    }
+
+    /// Tests a replacement produces removed and added segments.
+    #[test]
+    fn test_compute_diff_replacement() {
+        let diff = compute_diff("cat.txt", "dog.txt");
+        let removed: String = diff
+            .iter()
+            .filter(|s| s.kind == DiffSegmentType::Removed)
+            .map(|s| s.text.as_str())
+            .collect();
+        let added: String = diff
+            .iter()
+            .filter(|s| s.kind == DiffSegmentType::Added)
+            .map(|s| s.text.as_str())
+            .collect();
+
+        assert_eq!(removed, "cat");
+        assert_eq!(added, "dog");
+        assert_eq!(diff.last().unwrap().text, ".txt");
+    }
+
+    /// Tests an insertion keeps the surrounding text unchanged.
+    #[test]
+    fn test_compute_diff_insertion() {
+        let diff = compute_diff("a.txt", "01-a.txt");
+        assert_eq!(
+            diff,
+            vec![
+                DiffSegment {
+                    kind: DiffSegmentType::Added,
+                    text: "01-".to_string()
+                },
+                DiffSegment {
+                    kind: DiffSegmentType::Unchanged,
+                    text: "a.txt".to_string()
+                },
+            ]
+        );
+    }
+
+    // ==================== Serialization Tests ====================
+
+    /// Tests options deserialize from the frontend's camelCase shape with defaults.
+    #[test]
+    fn test_rename_options_deserialization() {
+        let json = r#"{
+            "findText": "a",
+            "replaceText": "b",
+            "regexMode": true,
+            "numbering": { "enabled": true, "startNumber": 5, "position": "end" }
+        }"#;
+        let options: RenameOptions = serde_json::from_str(json).unwrap();
+
+        assert_eq!(options.find_text, "a");
+        assert!(options.regex_mode);
+        assert!(!options.case_sensitive);
+        assert_eq!(options.numbering.start_number, 5);
+        assert_eq!(options.numbering.increment, 1);
+        assert_eq!(options.numbering.separator, "-");
+        assert_eq!(options.numbering.position, NumberingPosition::End);
+    }
+
+    /// Tests diff segments serialize with a `type` tag.
+    #[test]
+    fn test_diff_segment_serialization() {
+        let segment = DiffSegment {
+            kind: DiffSegmentType::Added,
+            text: "x".to_string(),
+        };
+        let json = serde_json::to_string(&segment).unwrap();
+        assert_eq!(json, r#"{"type":"added","text":"x"}"#);
+    }
+}
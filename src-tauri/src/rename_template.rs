@@ -0,0 +1,959 @@
+//! Rename templates.
+//!
+//! A template such as `{mtime:%Y-%m-%d}_{name}` or `{parent}-{counter:3}{ext}`
+//! describes the whole new file name. Tokens in braces are evaluated against
+//! each file's path and metadata; everything else is copied literally (`{{`
+//! and `}}` produce literal braces).
+//!
+//! The template is parsed once for the whole batch, so unknown tokens and bad
+//! arguments fail the request up front, while problems with a single file
+//! (unreadable metadata, no such parent directory, a photo without a capture
+//! date, a track without an artist tag) are reported for that file only.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, Metadata};
+use std::io::Read;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::media_tags::{read_audio_tags, read_exif, AudioTags, ExifInfo};
+use crate::rename_rules::split_extension;
+
+/// Date format used by `{mtime}` and `{ctime}` without an argument
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Number of hex digits used by `{hash}` without an argument
+const DEFAULT_HASH_LENGTH: usize = 8;
+
+// ==================== Types ====================
+
+/// Unit used by the `{size}` token
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SizeUnit {
+    /// Plain byte count
+    Bytes,
+    /// Whole kibibytes
+    Kilobytes,
+    /// Whole mebibytes
+    Megabytes,
+    /// Human-readable, e.g. `1.5MB`
+    Human,
+}
+
+/// A value a template token is replaced with
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// `{name}` - the full file name
+    Name,
+    /// `{stem}` - the file name without its extension
+    Stem,
+    /// `{ext}` - the extension including the dot, or nothing
+    Ext,
+    /// `{parent}` / `{parent:N}` - the name of the Nth parent directory
+    Parent(usize),
+    /// `{size}` / `{size:kb|mb|human}` - the file size
+    Size(SizeUnit),
+    /// `{mtime:FORMAT}` - the modification date, strftime formatted
+    Modified(String),
+    /// `{ctime:FORMAT}` - the creation date, strftime formatted
+    Created(String),
+    /// `{counter}` / `{counter:WIDTH}` - the 1-based position in the batch
+    Counter(usize),
+    /// `{hash}` / `{hash:N}` - the first N hex digits of the SHA-256 of the contents
+    Hash(usize),
+    /// `{exif:FIELD}` - a field from a photo's EXIF data
+    Exif(ExifField),
+    /// `{tag:FIELD}` - a field from an audio file's tags
+    AudioTag(AudioField),
+}
+
+/// EXIF field used by the `{exif:...}` token
+#[derive(Debug, Clone, PartialEq)]
+enum ExifField {
+    /// `{exif:date:FORMAT}` - DateTimeOriginal, strftime formatted
+    Date(String),
+    /// `{exif:make}` - camera manufacturer
+    Make,
+    /// `{exif:model}` - camera model
+    Model,
+    /// `{exif:gps}` - `gps` if the photo has coordinates, `nogps` otherwise
+    Gps,
+}
+
+impl ExifField {
+    /// Name of the EXIF tag, for error messages.
+    fn tag_name(&self) -> &'static str {
+        match self {
+            ExifField::Date(_) => "DateTimeOriginal",
+            ExifField::Make => "Make",
+            ExifField::Model => "Model",
+            ExifField::Gps => "GPS",
+        }
+    }
+}
+
+/// Audio tag field used by the `{tag:...}` token
+#[derive(Debug, Clone, PartialEq)]
+enum AudioField {
+    /// `{tag:title}`
+    Title,
+    /// `{tag:artist}`
+    Artist,
+    /// `{tag:album}`
+    Album,
+    /// `{tag:albumartist}`
+    AlbumArtist,
+    /// `{tag:genre}`
+    Genre,
+    /// `{tag:track}` / `{tag:track:WIDTH}` - zero-padded track number
+    Track(usize),
+    /// `{tag:disc}` / `{tag:disc:WIDTH}` - zero-padded disc number
+    Disc(usize),
+    /// `{tag:year}`
+    Year,
+}
+
+impl AudioField {
+    /// Name of the field as written in the template, for error messages.
+    fn tag_name(&self) -> &'static str {
+        match self {
+            AudioField::Title => "title",
+            AudioField::Artist => "artist",
+            AudioField::Album => "album",
+            AudioField::AlbumArtist => "albumartist",
+            AudioField::Genre => "genre",
+            AudioField::Track(_) => "track",
+            AudioField::Disc(_) => "disc",
+            AudioField::Year => "year",
+        }
+    }
+}
+
+/// A piece of a parsed template
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    /// Text copied as-is
+    Literal(String),
+    /// A token evaluated per file
+    Token(Token),
+}
+
+/// A parsed rename template
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+/// The result of evaluating a template for a single file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TemplatePreview {
+    /// Full path of the original file
+    pub path: String,
+    /// Original file name
+    pub name: String,
+    /// New file name, if the template could be evaluated for this file
+    pub new_name: Option<String>,
+    /// Full path with the new file name
+    pub new_path: Option<String>,
+    /// Why the template could not be evaluated for this file
+    pub error: Option<String>,
+}
+
+// ==================== Parsing ====================
+
+/// Parses an optional numeric token argument.
+fn parse_number_arg(token: &str, arg: Option<&str>, default: usize) -> Result<usize, String> {
+    match arg {
+        None => Ok(default),
+        Some(arg) => arg
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid argument '{}' for {{{}}}", arg, token)),
+    }
+}
+
+/// Checks a strftime format and returns it, or the default format.
+fn parse_date_arg(token: &str, arg: Option<&str>) -> Result<String, String> {
+    let format = arg.unwrap_or(DEFAULT_DATE_FORMAT);
+
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!(
+            "Invalid date format '{}' for {{{}}}",
+            format, token
+        ));
+    }
+
+    Ok(format.to_string())
+}
+
+/// Splits a `FIELD` or `FIELD:ARG` token argument.
+fn split_field(arg: Option<&str>) -> (&str, Option<&str>) {
+    match arg {
+        Some(arg) => match arg.split_once(':') {
+            Some((field, arg)) => (field, Some(arg)),
+            None => (arg, None),
+        },
+        None => ("", None),
+    }
+}
+
+/// Parses the field of an `{exif:...}` token.
+fn parse_exif_field(arg: Option<&str>) -> Result<ExifField, String> {
+    let (field, field_arg) = split_field(arg);
+
+    let parsed = match field {
+        "date" => return Ok(ExifField::Date(parse_date_arg("exif:date", field_arg)?)),
+        "make" => ExifField::Make,
+        "model" => ExifField::Model,
+        "gps" => ExifField::Gps,
+        _ => return Err(format!("Unknown EXIF field '{}'", field)),
+    };
+
+    if field_arg.is_some() {
+        return Err(format!("{{exif:{}}} does not take an argument", field));
+    }
+
+    Ok(parsed)
+}
+
+/// Parses the field of a `{tag:...}` token.
+fn parse_audio_field(arg: Option<&str>) -> Result<AudioField, String> {
+    let (field, field_arg) = split_field(arg);
+
+    let parsed = match field {
+        "track" => {
+            return Ok(AudioField::Track(parse_number_arg(
+                "tag:track",
+                field_arg,
+                1,
+            )?))
+        }
+        "disc" => {
+            return Ok(AudioField::Disc(parse_number_arg(
+                "tag:disc", field_arg, 1,
+            )?))
+        }
+        "title" => AudioField::Title,
+        "artist" => AudioField::Artist,
+        "album" => AudioField::Album,
+        "albumartist" => AudioField::AlbumArtist,
+        "genre" => AudioField::Genre,
+        "year" => AudioField::Year,
+        _ => return Err(format!("Unknown audio tag '{}'", field)),
+    };
+
+    if field_arg.is_some() {
+        return Err(format!("{{tag:{}}} does not take an argument", field));
+    }
+
+    Ok(parsed)
+}
+
+/// Parses the contents of a `{...}` token.
+fn parse_token(content: &str) -> Result<Token, String> {
+    let (name, arg) = match content.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (content, None),
+    };
+
+    let token = match name {
+        "name" => Token::Name,
+        "stem" => Token::Stem,
+        "ext" => Token::Ext,
+        "parent" => {
+            let level = parse_number_arg(name, arg, 1)?;
+            if level == 0 {
+                return Err("Parent level must be at least 1".to_string());
+            }
+            Token::Parent(level)
+        }
+        "size" => Token::Size(match arg {
+            None | Some("b") => SizeUnit::Bytes,
+            Some("kb") => SizeUnit::Kilobytes,
+            Some("mb") => SizeUnit::Megabytes,
+            Some("human") => SizeUnit::Human,
+            Some(other) => return Err(format!("Invalid argument '{}' for {{size}}", other)),
+        }),
+        "mtime" => Token::Modified(parse_date_arg(name, arg)?),
+        "ctime" => Token::Created(parse_date_arg(name, arg)?),
+        "counter" => Token::Counter(parse_number_arg(name, arg, 1)?),
+        "hash" => {
+            let length = parse_number_arg(name, arg, DEFAULT_HASH_LENGTH)?;
+            if !(1..=64).contains(&length) {
+                return Err("Hash length must be between 1 and 64".to_string());
+            }
+            Token::Hash(length)
+        }
+        "exif" => Token::Exif(parse_exif_field(arg)?),
+        "tag" => Token::AudioTag(parse_audio_field(arg)?),
+        _ => return Err(format!("Unknown template token {{{}}}", content)),
+    };
+
+    if arg.is_some() && matches!(token, Token::Name | Token::Stem | Token::Ext) {
+        return Err(format!("{{{}}} does not take an argument", name));
+    }
+
+    Ok(token)
+}
+
+impl RenameTemplate {
+    /// Parses a template string.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RenameTemplate)` - The parsed template
+    /// * `Err(String)` - If a brace is unbalanced, a token is unknown, or a
+    ///   token argument is invalid
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut content = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => content.push(c),
+                            None => return Err("Unclosed '{' in template".to_string()),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(TemplatePart::Token(parse_token(&content)?));
+                }
+                '}' => return Err("Unmatched '}' in template".to_string()),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+
+        if parts.is_empty() {
+            return Err("Template is empty".to_string());
+        }
+
+        Ok(RenameTemplate { parts })
+    }
+}
+
+// ==================== Evaluation ====================
+
+/// Formats a byte count in the given unit.
+fn format_size(bytes: u64, unit: SizeUnit) -> String {
+    match unit {
+        SizeUnit::Bytes => bytes.to_string(),
+        SizeUnit::Kilobytes => (bytes / 1024).to_string(),
+        SizeUnit::Megabytes => (bytes / (1024 * 1024)).to_string(),
+        SizeUnit::Human => {
+            const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+            if bytes < 1024 {
+                return format!("{}B", bytes);
+            }
+            let mut value = bytes as f64;
+            let mut unit = 0;
+            while value >= 1024.0 && unit < UNITS.len() - 1 {
+                value /= 1024.0;
+                unit += 1;
+            }
+            format!("{:.1}{}", value, UNITS[unit])
+        }
+    }
+}
+
+/// Formats a file time in local time.
+fn format_time(time: SystemTime, format: &str) -> String {
+    DateTime::<Local>::from(time).format(format).to_string()
+}
+
+/// Computes the SHA-256 of a file's contents as lowercase hex.
+fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Replaces path separators and control characters in a value read from a
+/// file's tags, so it can be used in a file name.
+fn sanitize_tag_value(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c == '/' || c == '\\' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// A file being renamed, with its metadata, hash and tags read on first use
+struct FileContext<'a> {
+    path: &'a Path,
+    name: &'a str,
+    file_index: usize,
+    metadata: Option<Result<Metadata, String>>,
+    hash: Option<Result<String, String>>,
+    exif: Option<Result<ExifInfo, String>>,
+    audio_tags: Option<Result<AudioTags, String>>,
+}
+
+impl<'a> FileContext<'a> {
+    fn new(path: &'a Path, name: &'a str, file_index: usize) -> Self {
+        FileContext {
+            path,
+            name,
+            file_index,
+            metadata: None,
+            hash: None,
+            exif: None,
+            audio_tags: None,
+        }
+    }
+
+    fn metadata(&mut self) -> Result<&Metadata, String> {
+        let path = self.path;
+        self.metadata
+            .get_or_insert_with(|| {
+                fs::metadata(path).map_err(|e| format!("Failed to read metadata: {}", e))
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    fn hash(&mut self) -> Result<&str, String> {
+        let path = self.path;
+        self.hash
+            .get_or_insert_with(|| hash_file(path))
+            .as_deref()
+            .map_err(Clone::clone)
+    }
+
+    fn exif(&mut self) -> Result<&ExifInfo, String> {
+        let path = self.path;
+        self.exif
+            .get_or_insert_with(|| read_exif(path))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    fn audio_tags(&mut self) -> Result<&AudioTags, String> {
+        let path = self.path;
+        self.audio_tags
+            .get_or_insert_with(|| read_audio_tags(path))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Evaluates a single token for this file.
+    fn evaluate(&mut self, token: &Token) -> Result<String, String> {
+        match token {
+            Token::Name => Ok(self.name.to_string()),
+            Token::Stem => Ok(split_extension(self.name).0.to_string()),
+            Token::Ext => Ok(split_extension(self.name).1.to_string()),
+            Token::Parent(level) => self
+                .path
+                .ancestors()
+                .nth(*level)
+                .and_then(|dir| dir.file_name())
+                .map(|dir| dir.to_string_lossy().to_string())
+                .ok_or_else(|| format!("No parent directory at level {}", level)),
+            Token::Size(unit) => Ok(format_size(self.metadata()?.len(), *unit)),
+            Token::Modified(format) => self
+                .metadata()?
+                .modified()
+                .map(|time| format_time(time, format))
+                .map_err(|e| format!("Modification time is not available: {}", e)),
+            Token::Created(format) => self
+                .metadata()?
+                .created()
+                .map(|time| format_time(time, format))
+                .map_err(|e| format!("Creation time is not available: {}", e)),
+            Token::Counter(width) => {
+                Ok(format!("{:0>width$}", self.file_index + 1, width = *width))
+            }
+            Token::Hash(length) => Ok(self.hash()?[..*length].to_string()),
+            Token::Exif(field) => {
+                let exif = self.exif()?;
+                let value = match field {
+                    ExifField::Date(format) => {
+                        exif.date_taken.map(|date| date.format(format).to_string())
+                    }
+                    ExifField::Make => exif.make.clone(),
+                    ExifField::Model => exif.model.clone(),
+                    ExifField::Gps => Some(if exif.has_gps { "gps" } else { "nogps" }.to_string()),
+                };
+                value
+                    .map(|value| sanitize_tag_value(&value))
+                    .ok_or_else(|| format!("Missing EXIF {}", field.tag_name()))
+            }
+            Token::AudioTag(field) => {
+                let tags = self.audio_tags()?;
+                let value = match field {
+                    AudioField::Title => tags.title.clone(),
+                    AudioField::Artist => tags.artist.clone(),
+                    AudioField::Album => tags.album.clone(),
+                    AudioField::AlbumArtist => tags.album_artist.clone(),
+                    AudioField::Genre => tags.genre.clone(),
+                    AudioField::Track(width) => tags
+                        .track
+                        .map(|track| format!("{:0>width$}", track, width = *width)),
+                    AudioField::Disc(width) => tags
+                        .disc
+                        .map(|disc| format!("{:0>width$}", disc, width = *width)),
+                    AudioField::Year => tags.year.map(|year| year.to_string()),
+                };
+                value
+                    .map(|value| sanitize_tag_value(&value))
+                    .ok_or_else(|| format!("Missing audio tag '{}'", field.tag_name()))
+            }
+        }
+    }
+}
+
+/// Evaluates the template for a single file.
+///
+/// # Arguments
+///
+/// * `template` - The parsed template
+/// * `path` - Full path of the file
+/// * `file_index` - Position of the file in the batch, used by `{counter}`
+///
+/// # Returns
+///
+/// A `TemplatePreview` with either the new name or the reason it could not
+/// be computed.
+pub fn render_template(
+    template: &RenameTemplate,
+    path: &str,
+    file_index: usize,
+) -> TemplatePreview {
+    let file_path = Path::new(path);
+    let name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+
+    let render = || -> Result<String, String> {
+        let mut context = FileContext::new(file_path, &name, file_index);
+        let mut new_name = String::new();
+
+        for part in &template.parts {
+            match part {
+                TemplatePart::Literal(text) => new_name.push_str(text),
+                TemplatePart::Token(token) => new_name.push_str(&context.evaluate(token)?),
+            }
+        }
+
+        if new_name.contains('/') || new_name.contains('\\') {
+            return Err(format!(
+                "Template produced '{}', which contains a path separator",
+                new_name
+            ));
+        }
+
+        Ok(new_name)
+    };
+
+    match render() {
+        Ok(new_name) => {
+            let new_path = match file_path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => {
+                    parent.join(&new_name).to_string_lossy().to_string()
+                }
+                _ => new_name.clone(),
+            };
+
+            TemplatePreview {
+                path: path.to_string(),
+                name,
+                new_name: Some(new_name),
+                new_path: Some(new_path),
+                error: None,
+            }
+        }
+        Err(error) => TemplatePreview {
+            path: path.to_string(),
+            name,
+            new_name: None,
+            new_path: None,
+            error: Some(error),
+        },
+    }
+}
+
+/// Evaluates the template for every file, in parallel.
+fn render_templates(template: &RenameTemplate, paths: &[String]) -> Vec<TemplatePreview> {
+    paths
+        .par_iter()
+        .enumerate()
+        .map(|(index, path)| render_template(template, path, index))
+        .collect()
+}
+
+// ==================== Tauri Commands ====================
+
+/// Computes new names for a list of files from a rename template.
+///
+/// Supported tokens: `{name}`, `{stem}`, `{ext}`, `{parent}`/`{parent:N}`,
+/// `{size}`/`{size:kb|mb|human}`, `{mtime:FORMAT}`, `{ctime:FORMAT}`,
+/// `{counter}`/`{counter:WIDTH}`, `{hash}`/`{hash:N}`,
+/// `{exif:date|make|model|gps}` (`{exif:date:FORMAT}` for a custom date
+/// format) and `{tag:title|artist|album|albumartist|genre|track|disc|year}`
+/// (`{tag:track:WIDTH}`/`{tag:disc:WIDTH}` to zero-pad numbers).
+///
+/// This command runs on a background thread because `{hash}`, `{exif:...}`
+/// and `{tag:...}` read file contents.
+///
+/// # Arguments
+///
+/// * `paths` - Full paths of the files (e.g. from `list_files_recursively`),
+///   in the order used for `{counter}`
+/// * `template` - The template for the new file name
+///
+/// # Returns
+///
+/// * `Ok(Vec<TemplatePreview>)` - One preview per path, in input order; files
+///   the template could not be evaluated for carry an error instead of a name
+/// * `Err(String)` - If the template itself is invalid
+///
+/// # Example
+///
+/// ```ignore
+/// let previews = preview_template(
+///     vec!["/photos/trip/a.jpg".to_string()],
+///     "{parent}-{counter:3}{ext}".to_string(),
+/// ).await?;
+/// assert_eq!(previews[0].new_name.as_deref(), Some("trip-001.jpg"));
+/// ```
+#[tauri::command]
+pub async fn preview_template(
+    paths: Vec<String>,
+    template: String,
+) -> Result<Vec<TemplatePreview>, String> {
+    let template = RenameTemplate::parse(&template)?;
+
+    tokio::task::spawn_blocking(move || Ok(render_templates(&template, &paths)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media_tags::test_files::{flac_with_comments, jpeg_with_exif};
+    use exif::Tag;
+    use std::fs::File;
+    use std::io::Write;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    /// Helper to render a template for one path.
+    fn render(template: &str, path: &str, file_index: usize) -> TemplatePreview {
+        render_template(&RenameTemplate::parse(template).unwrap(), path, file_index)
+    }
+
+    /// Helper to create a file with the given contents.
+    fn create_file(dir: &Path, name: &str, contents: &[u8]) -> String {
+        let path = dir.join(name);
+        File::create(&path).unwrap().write_all(contents).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    // ==================== Parsing Tests ====================
+
+    /// Tests literal text, tokens and escaped braces are parsed.
+    #[test]
+    fn test_parse_template() {
+        let template = RenameTemplate::parse("{{x}}_{stem}{counter:3}").unwrap();
+        assert_eq!(
+            template.parts,
+            vec![
+                TemplatePart::Literal("{x}_".to_string()),
+                TemplatePart::Token(Token::Stem),
+                TemplatePart::Token(Token::Counter(3)),
+            ]
+        );
+    }
+
+    /// Tests date formats may contain colons.
+    #[test]
+    fn test_parse_date_format_with_colons() {
+        let template = RenameTemplate::parse("{mtime:%H:%M}").unwrap();
+        assert_eq!(
+            template.parts,
+            vec![TemplatePart::Token(Token::Modified("%H:%M".to_string()))]
+        );
+    }
+
+    /// Tests invalid templates are rejected.
+    #[test]
+    fn test_parse_invalid_templates() {
+        for template in [
+            "",
+            "{name",
+            "name}",
+            "{unknown}",
+            "{counter:abc}",
+            "{parent:0}",
+            "{hash:65}",
+            "{size:gb}",
+            "{mtime:%Q}",
+            "{name:x}",
+            "{exif}",
+            "{exif:iso}",
+            "{exif:model:x}",
+            "{tag:composer}",
+            "{tag:track:x}",
+        ] {
+            assert!(
+                RenameTemplate::parse(template).is_err(),
+                "expected '{}' to be rejected",
+                template
+            );
+        }
+    }
+
+    // ==================== Token Tests ====================
+
+    /// Tests name, stem and extension tokens.
+    #[test]
+    fn test_name_tokens() {
+        let preview = render("{stem}_copy{ext}", "/dir/photo.jpg", 0);
+        assert_eq!(preview.new_name.as_deref(), Some("photo_copy.jpg"));
+        assert_eq!(preview.new_path.as_deref(), Some("/dir/photo_copy.jpg"));
+
+        let preview = render("[{name}]{ext}", "/dir/.bashrc", 0);
+        assert_eq!(preview.new_name.as_deref(), Some("[.bashrc]"));
+    }
+
+    /// Tests parent directory tokens at several levels.
+    #[test]
+    fn test_parent_tokens() {
+        let preview = render("{parent:2}-{parent}-{name}", "/music/album/track.mp3", 0);
+        assert_eq!(preview.new_name.as_deref(), Some("music-album-track.mp3"));
+
+        let preview = render("{parent:5}{name}", "/music/track.mp3", 0);
+        assert!(preview.new_name.is_none());
+        assert!(preview.error.unwrap().contains("level 5"));
+    }
+
+    /// Tests counters are 1-based and zero-padded.
+    #[test]
+    fn test_counter_token() {
+        assert_eq!(
+            render("{counter:3}", "/a.txt", 0).new_name.as_deref(),
+            Some("001")
+        );
+        assert_eq!(
+            render("{counter}", "/a.txt", 41).new_name.as_deref(),
+            Some("42")
+        );
+    }
+
+    /// Tests size tokens in each unit.
+    #[test]
+    fn test_size_token() {
+        let dir = tempdir().unwrap();
+        let path = create_file(dir.path(), "a.bin", &vec![0u8; 3 * 1024 + 512]);
+
+        assert_eq!(render("{size}", &path, 0).new_name.as_deref(), Some("3584"));
+        assert_eq!(render("{size:kb}", &path, 0).new_name.as_deref(), Some("3"));
+        assert_eq!(render("{size:mb}", &path, 0).new_name.as_deref(), Some("0"));
+        assert_eq!(
+            render("{size:human}", &path, 0).new_name.as_deref(),
+            Some("3.5KB")
+        );
+        assert_eq!(format_size(512, SizeUnit::Human), "512B");
+    }
+
+    /// Tests modification dates are formatted with strftime.
+    #[test]
+    fn test_mtime_token() {
+        let dir = tempdir().unwrap();
+        let path = create_file(dir.path(), "a.txt", b"x");
+
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let expected = DateTime::<Local>::from(modified)
+            .format("%Y-%m-%d")
+            .to_string();
+        let preview = render("{mtime}_{name}", &path, 0);
+        assert_eq!(preview.new_name, Some(format!("{}_a.txt", expected)));
+    }
+
+    /// Tests the hash token uses a prefix of the SHA-256 of the contents.
+    #[test]
+    fn test_hash_token() {
+        let dir = tempdir().unwrap();
+        let path = create_file(dir.path(), "a.txt", b"hello");
+
+        assert_eq!(
+            render("{hash}", &path, 0).new_name.as_deref(),
+            Some("2cf24dba")
+        );
+        assert_eq!(
+            render("{hash:4}", &path, 0).new_name.as_deref(),
+            Some("2cf2")
+        );
+    }
+
+    // ==================== Per-File Error Tests ====================
+
+    /// Tests missing files fail individually instead of failing the batch.
+    #[test]
+    fn test_missing_file_reports_error() {
+        let dir = tempdir().unwrap();
+        let existing = create_file(dir.path(), "a.txt", b"x");
+        let missing = dir.path().join("missing.txt").to_string_lossy().to_string();
+
+        let template = RenameTemplate::parse("{size}{ext}").unwrap();
+        let previews = render_templates(&template, &[existing, missing]);
+
+        assert_eq!(previews[0].new_name.as_deref(), Some("1.txt"));
+        assert!(previews[1].new_name.is_none());
+        assert!(previews[1].error.as_ref().unwrap().contains("metadata"));
+    }
+
+    /// Tests metadata is not read when no token needs it.
+    #[test]
+    fn test_path_tokens_do_not_need_file() {
+        let preview = render("{parent}_{counter:2}{ext}", "/nowhere/x/a.txt", 1);
+        assert_eq!(preview.new_name.as_deref(), Some("x_02.txt"));
+    }
+
+    /// Tests names containing path separators are rejected.
+    #[test]
+    fn test_path_separator_in_result() {
+        let preview = render("a/{name}", "/dir/b.txt", 0);
+        assert!(preview.new_name.is_none());
+        assert!(preview.error.unwrap().contains("path separator"));
+    }
+
+    // ==================== Media Token Tests ====================
+
+    /// Tests EXIF tokens are read from photos.
+    #[test]
+    fn test_exif_tokens() {
+        let dir = tempdir().unwrap();
+        let path = create_file(
+            dir.path(),
+            "IMG_0001.jpg",
+            &jpeg_with_exif(&[
+                (Tag::Model, "Canon EOS R6"),
+                (Tag::DateTimeOriginal, "2024:01:31 10:20:30"),
+            ]),
+        );
+
+        let preview = render(
+            "{exif:date:%Y%m%d-%H%M%S}_{exif:model}_{exif:gps}{ext}",
+            &path,
+            0,
+        );
+        assert_eq!(
+            preview.new_name.as_deref(),
+            Some("20240131-102030_Canon EOS R6_nogps.jpg")
+        );
+    }
+
+    /// Tests a missing EXIF tag fails only the affected file.
+    #[test]
+    fn test_missing_exif_tag_reports_error() {
+        let dir = tempdir().unwrap();
+        let dated = create_file(
+            dir.path(),
+            "a.jpg",
+            &jpeg_with_exif(&[(Tag::DateTimeOriginal, "2024:01:31 10:20:30")]),
+        );
+        let undated = create_file(
+            dir.path(),
+            "b.jpg",
+            &jpeg_with_exif(&[(Tag::Make, "Canon")]),
+        );
+        let not_photo = create_file(dir.path(), "c.jpg", b"not a photo");
+
+        let template = RenameTemplate::parse("{exif:date}{ext}").unwrap();
+        let previews = render_templates(&template, &[dated, undated, not_photo]);
+
+        assert_eq!(previews[0].new_name.as_deref(), Some("2024-01-31.jpg"));
+        assert_eq!(
+            previews[1].error.as_deref(),
+            Some("Missing EXIF DateTimeOriginal")
+        );
+        assert!(previews[2].error.as_ref().unwrap().contains("No EXIF data"));
+    }
+
+    /// Tests audio tag tokens, including track padding.
+    #[test]
+    fn test_audio_tag_tokens() {
+        let dir = tempdir().unwrap();
+        let path = create_file(
+            dir.path(),
+            "01.flac",
+            &flac_with_comments(&["TITLE=Song", "ARTIST=Band", "TRACKNUMBER=7", "DATE=1999"]),
+        );
+
+        let preview = render(
+            "{tag:track:2} - {tag:artist} - {tag:title} ({tag:year}){ext}",
+            &path,
+            0,
+        );
+        assert_eq!(
+            preview.new_name.as_deref(),
+            Some("07 - Band - Song (1999).flac")
+        );
+
+        let preview = render("{tag:album}{ext}", &path, 0);
+        assert_eq!(preview.error.as_deref(), Some("Missing audio tag 'album'"));
+    }
+
+    /// Tests path separators in tag values are replaced.
+    #[test]
+    fn test_tag_values_are_sanitized() {
+        let dir = tempdir().unwrap();
+        let path = create_file(dir.path(), "a.flac", &flac_with_comments(&["ARTIST=AC/DC"]));
+
+        let preview = render("{tag:artist}{ext}", &path, 0);
+        assert_eq!(preview.new_name.as_deref(), Some("AC_DC.flac"));
+    }
+}
@@ -0,0 +1,481 @@
+//! Compound filter expressions for the file remover search.
+//!
+//! `FilterExpression` is the JSON tree the frontend builds and saves: `and`,
+//! `or` and `not` nodes over pattern, size, time and entry type tests. It is
+//! compiled once into a `SearchExpression`, which the parallel matching phase
+//! evaluates for each candidate. A candidate's metadata is read at most once,
+//! and only if a test that needs it is reached.
+
+use crate::journal::now_ms;
+use crate::remove::{MatchTarget, PatternMatcher, PatternType};
+use crate::search_filters::{SearchFilter, SearchFilters, TimeBound, TimeKind, TimeRange};
+use serde::{Deserialize, Serialize};
+use std::cell::OnceCell;
+use std::fs::{self, Metadata};
+use std::path::Path;
+
+// ==================== Types ====================
+
+/// A filter expression, as built and saved by the frontend
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum FilterExpression {
+    /// Matches when every child matches; an empty list matches everything
+    And {
+        /// The expressions that must all match
+        children: Vec<FilterExpression>,
+    },
+    /// Matches when any child matches; an empty list matches nothing
+    Or {
+        /// The expressions of which one must match
+        children: Vec<FilterExpression>,
+    },
+    /// Matches when the child does not match
+    Not {
+        /// The expression that must not match
+        child: Box<FilterExpression>,
+    },
+    /// Matches a pattern against the file name or path
+    Pattern {
+        /// The pattern
+        pattern: String,
+        /// How the pattern is interpreted
+        pattern_type: PatternType,
+        /// Whether the pattern is case-sensitive
+        #[serde(default)]
+        case_sensitive: bool,
+        /// What the pattern is matched against; a glob containing `/`
+        /// defaults to the relative path, anything else to the name
+        match_target: Option<MatchTarget>,
+    },
+    /// Matches files whose size is within inclusive bounds such as `10MB`;
+    /// directories never match
+    Size {
+        /// Smallest size to match
+        min: Option<String>,
+        /// Largest size to match
+        max: Option<String>,
+    },
+    /// Matches entries whose timestamp is strictly between the bounds;
+    /// entries without that timestamp never match
+    Time {
+        /// Which timestamp is compared
+        kind: TimeKind,
+        /// Only match timestamps after this point (newer)
+        after: Option<TimeBound>,
+        /// Only match timestamps before this point (older)
+        before: Option<TimeBound>,
+    },
+    /// Matches entries of one type
+    EntryType {
+        /// The type to match
+        entry_type: EntryType,
+    },
+}
+
+/// The type of a file system entry
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    /// A regular file, or a symbolic link to one
+    File,
+    /// A directory, or a symbolic link to one
+    Directory,
+    /// A symbolic link, whatever it points to
+    Symlink,
+}
+
+/// A compiled expression node
+#[derive(Debug, Clone)]
+enum Node {
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+    Pattern {
+        matcher: PatternMatcher,
+        target: MatchTarget,
+    },
+    Metadata(SearchFilter),
+    EntryType(EntryType),
+}
+
+/// A filter expression with every pattern and bound compiled, ready to check candidates
+#[derive(Debug, Clone)]
+pub struct SearchExpression(Node);
+
+/// A search candidate, with its metadata read on first use
+pub(crate) struct Candidate<'a> {
+    path: &'a Path,
+    base_path: &'a Path,
+    name: String,
+    metadata: OnceCell<Result<Metadata, String>>,
+}
+
+// ==================== Compiling ====================
+
+impl FilterExpression {
+    /// Compiles the expression.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SearchExpression)` - The compiled expression
+    /// * `Err(String)` - If any pattern, size or time bound is invalid
+    pub fn compile(&self) -> Result<SearchExpression, String> {
+        self.compile_at(now_ms()).map(SearchExpression)
+    }
+
+    /// Compiles the expression, resolving relative time bounds against `now`
+    /// (milliseconds since the Unix epoch).
+    fn compile_at(&self, now: u64) -> Result<Node, String> {
+        let compile_all = |children: &[FilterExpression]| {
+            children
+                .iter()
+                .map(|child| child.compile_at(now))
+                .collect::<Result<Vec<_>, String>>()
+        };
+
+        Ok(match self {
+            FilterExpression::And { children } => Node::And(compile_all(children)?),
+            FilterExpression::Or { children } => Node::Or(compile_all(children)?),
+            FilterExpression::Not { child } => Node::Not(Box::new(child.compile_at(now)?)),
+            FilterExpression::Pattern {
+                pattern,
+                pattern_type,
+                case_sensitive,
+                match_target,
+            } => Node::Pattern {
+                matcher: PatternMatcher::compile(pattern, pattern_type, *case_sensitive)?,
+                target: MatchTarget::resolve(*match_target, pattern_type, pattern),
+            },
+            FilterExpression::Size { min, max } => {
+                let filters = SearchFilters {
+                    min_size: min.clone(),
+                    max_size: max.clone(),
+                    ..Default::default()
+                };
+                Node::Metadata(filters.compile_at(now)?)
+            }
+            FilterExpression::Time {
+                kind,
+                after,
+                before,
+            } => {
+                let range = Some(TimeRange {
+                    after: after.clone(),
+                    before: before.clone(),
+                });
+                let filters = match kind {
+                    TimeKind::Modified => SearchFilters {
+                        modified: range,
+                        ..Default::default()
+                    },
+                    TimeKind::Created => SearchFilters {
+                        created: range,
+                        ..Default::default()
+                    },
+                    TimeKind::Accessed => SearchFilters {
+                        accessed: range,
+                        ..Default::default()
+                    },
+                };
+                Node::Metadata(filters.compile_at(now)?)
+            }
+            FilterExpression::EntryType { entry_type } => Node::EntryType(*entry_type),
+        })
+    }
+}
+
+impl From<SearchFilter> for SearchExpression {
+    fn from(filter: SearchFilter) -> Self {
+        SearchExpression(Node::Metadata(filter))
+    }
+}
+
+// ==================== Matching ====================
+
+impl<'a> Candidate<'a> {
+    /// Creates a candidate for a path found under `base_path`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the path has no file name.
+    pub(crate) fn new(path: &'a Path, base_path: &'a Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_string();
+        Some(Candidate {
+            path,
+            base_path,
+            name,
+            metadata: OnceCell::new(),
+        })
+    }
+
+    /// The file name.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The candidate's metadata, following symbolic links.
+    pub(crate) fn metadata(&self) -> Result<&Metadata, String> {
+        self.metadata
+            .get_or_init(|| fs::metadata(self.path).map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Whether the candidate is of a type.
+    fn is(&self, entry_type: EntryType) -> bool {
+        match entry_type {
+            EntryType::File => self.metadata().is_ok_and(|m| m.is_file()),
+            EntryType::Directory => self.metadata().is_ok_and(|m| m.is_dir()),
+            EntryType::Symlink => self.path.is_symlink(),
+        }
+    }
+}
+
+impl Node {
+    /// Checks a candidate against the node.
+    fn matches(&self, candidate: &Candidate) -> bool {
+        match self {
+            Node::And(children) => children.iter().all(|child| child.matches(candidate)),
+            Node::Or(children) => children.iter().any(|child| child.matches(candidate)),
+            Node::Not(child) => !child.matches(candidate),
+            Node::Pattern { matcher, target } => matcher
+                .find(&target.text(candidate.path, candidate.base_path, &candidate.name))
+                .is_some(),
+            Node::Metadata(filter) => candidate.metadata().is_ok_and(|m| filter.matches(m)),
+            Node::EntryType(entry_type) => candidate.is(*entry_type),
+        }
+    }
+
+    /// Finds the first pattern outside a `not` that matches the candidate.
+    fn highlight(&self, candidate: &Candidate) -> Option<(MatchTarget, Vec<(usize, usize)>)> {
+        match self {
+            Node::And(children) | Node::Or(children) => {
+                children.iter().find_map(|child| child.highlight(candidate))
+            }
+            Node::Pattern { matcher, target } => matcher
+                .find(&target.text(candidate.path, candidate.base_path, &candidate.name))
+                .map(|ranges| (*target, ranges)),
+            Node::Not(_) | Node::Metadata(_) | Node::EntryType(_) => None,
+        }
+    }
+}
+
+impl SearchExpression {
+    /// Combines expressions that must all match.
+    pub(crate) fn all(parts: Vec<SearchExpression>) -> Self {
+        SearchExpression(Node::And(parts.into_iter().map(|part| part.0).collect()))
+    }
+
+    /// Checks a candidate against the expression.
+    pub(crate) fn matches(&self, candidate: &Candidate) -> bool {
+        self.0.matches(candidate)
+    }
+
+    /// Returns the ranges to highlight in a matching candidate.
+    ///
+    /// The ranges come from the first pattern outside a `not` that matches,
+    /// along with the text they index into. A candidate matched only by
+    /// metadata or `not` tests has nothing to highlight.
+    pub(crate) fn highlight(&self, candidate: &Candidate) -> (MatchTarget, Vec<(usize, usize)>) {
+        self.0
+            .highlight(candidate)
+            .unwrap_or((MatchTarget::Name, Vec::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    /// Parses and compiles an expression from JSON.
+    fn compile(json: &str) -> SearchExpression {
+        serde_json::from_str::<FilterExpression>(json)
+            .unwrap()
+            .compile()
+            .unwrap()
+    }
+
+    /// Tests excluding a folder and a name from a glob match.
+    #[test]
+    fn test_exclude_patterns() {
+        let dir = tempdir().unwrap();
+        let base = dir.path();
+        fs::create_dir(base.join("keep")).unwrap();
+        for name in ["app.log", "important.log", "notes.txt", "keep/old.log"] {
+            File::create(base.join(name)).unwrap();
+        }
+
+        let expression = compile(
+            r#"{
+                "type": "and",
+                "children": [
+                    { "type": "pattern", "pattern": "*.log", "patternType": "glob" },
+                    { "type": "not", "child":
+                        { "type": "pattern", "pattern": "keep/**", "patternType": "glob" } },
+                    { "type": "not", "child":
+                        { "type": "pattern", "pattern": "important", "patternType": "simple" } }
+                ]
+            }"#,
+        );
+        let matches = |name: &str| {
+            let path = base.join(name);
+            expression.matches(&Candidate::new(&path, base).unwrap())
+        };
+
+        assert!(matches("app.log"));
+        assert!(!matches("important.log"));
+        assert!(!matches("notes.txt"));
+        assert!(!matches("keep/old.log"));
+    }
+
+    /// Tests `or`, and the identities of empty `and` and `or`.
+    #[test]
+    fn test_or_and_empty_lists() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("photo.png");
+        File::create(&path).unwrap();
+        let candidate = Candidate::new(&path, dir.path()).unwrap();
+
+        let either = compile(
+            r#"{ "type": "or", "children": [
+                { "type": "pattern", "pattern": "jpg", "patternType": "extension" },
+                { "type": "pattern", "pattern": "png", "patternType": "extension" }
+            ] }"#,
+        );
+        assert!(either.matches(&candidate));
+        assert!(compile(r#"{ "type": "and", "children": [] }"#).matches(&candidate));
+        assert!(!compile(r#"{ "type": "or", "children": [] }"#).matches(&candidate));
+    }
+
+    /// Tests size, time and entry type tests.
+    #[test]
+    fn test_metadata_tests() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("big.bin");
+        let subdir = dir.path().join("sub");
+        fs::write(&file, vec![0u8; 2048]).unwrap();
+        fs::create_dir(&subdir).unwrap();
+        let file = Candidate::new(&file, dir.path()).unwrap();
+        let subdir = Candidate::new(&subdir, dir.path()).unwrap();
+
+        let large = compile(r#"{ "type": "size", "min": "1KB" }"#);
+        assert!(large.matches(&file));
+        assert!(!large.matches(&subdir));
+
+        let recent = compile(
+            r#"{ "type": "time", "kind": "modified", "after": { "type": "ago", "duration": "1h" } }"#,
+        );
+        assert!(recent.matches(&file));
+        let old = compile(
+            r#"{ "type": "time", "kind": "modified", "before": { "type": "ago", "duration": "1h" } }"#,
+        );
+        assert!(!old.matches(&file));
+
+        let directories = compile(r#"{ "type": "entryType", "entryType": "directory" }"#);
+        assert!(directories.matches(&subdir));
+        assert!(!directories.matches(&file));
+    }
+
+    /// Tests symbolic links are matched as links and as their targets.
+    #[cfg(unix)]
+    #[test]
+    fn test_entry_type_symlink() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target.txt");
+        let link = dir.path().join("link.txt");
+        File::create(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let link = Candidate::new(&link, dir.path()).unwrap();
+        let target = Candidate::new(&target, dir.path()).unwrap();
+
+        let symlinks = compile(r#"{ "type": "entryType", "entryType": "symlink" }"#);
+        assert!(symlinks.matches(&link));
+        assert!(!symlinks.matches(&target));
+        assert!(compile(r#"{ "type": "entryType", "entryType": "file" }"#).matches(&link));
+    }
+
+    /// Tests highlights come from the first matching pattern outside a `not`.
+    #[test]
+    fn test_highlight() {
+        let base = Path::new("/data");
+        let path = base.join("build").join("app.o");
+        let candidate = Candidate::new(&path, base).unwrap();
+
+        let expression = compile(
+            r#"{ "type": "and", "children": [
+                { "type": "not", "child": { "type": "pattern", "pattern": "app", "patternType": "simple" } },
+                { "type": "or", "children": [
+                    { "type": "pattern", "pattern": "zzz", "patternType": "simple" },
+                    { "type": "pattern", "pattern": "build", "patternType": "simple",
+                      "matchTarget": "relativePath" }
+                ] }
+            ] }"#,
+        );
+        assert_eq!(
+            expression.highlight(&candidate),
+            (MatchTarget::RelativePath, vec![(0, 5)])
+        );
+
+        let size_only = compile(r#"{ "type": "size", "max": "1GB" }"#);
+        assert_eq!(
+            size_only.highlight(&candidate),
+            (MatchTarget::Name, Vec::new())
+        );
+    }
+
+    /// Tests an invalid leaf anywhere in the tree fails compilation.
+    #[test]
+    fn test_compile_errors() {
+        let compile = |json: &str| {
+            serde_json::from_str::<FilterExpression>(json)
+                .unwrap()
+                .compile()
+        };
+
+        let nested_regex = r#"{ "type": "not", "child": { "type": "or", "children": [
+            { "type": "pattern", "pattern": "[invalid", "patternType": "regex" }
+        ] } }"#;
+        assert!(compile(nested_regex).is_err());
+        assert_eq!(
+            compile(r#"{ "type": "pattern", "pattern": " ", "patternType": "simple" }"#)
+                .unwrap_err(),
+            "Pattern cannot be empty"
+        );
+        assert!(compile(r#"{ "type": "size", "min": "2MB", "max": "1MB" }"#).is_err());
+        assert!(compile(
+            r#"{ "type": "time", "kind": "created", "after": { "type": "date", "date": "soon" } }"#
+        )
+        .is_err());
+    }
+
+    /// Tests an expression survives a save and load unchanged.
+    #[test]
+    fn test_serialization_round_trip() {
+        let expression = FilterExpression::And {
+            children: vec![
+                FilterExpression::Pattern {
+                    pattern: "*.log".to_string(),
+                    pattern_type: PatternType::Glob,
+                    case_sensitive: false,
+                    match_target: Some(MatchTarget::Name),
+                },
+                FilterExpression::Not {
+                    child: Box::new(FilterExpression::EntryType {
+                        entry_type: EntryType::Directory,
+                    }),
+                },
+            ],
+        };
+
+        let json = serde_json::to_string(&expression).unwrap();
+        assert!(json.contains(r#""type":"entryType","entryType":"directory""#));
+        assert!(json.contains(r#""patternType":"glob""#));
+        assert_eq!(
+            serde_json::from_str::<FilterExpression>(&json).unwrap(),
+            expression
+        );
+    }
+}
@@ -0,0 +1,636 @@
+//! Metadata filters for the file remover search.
+//!
+//! `SearchFilters` is the JSON shape the frontend sends alongside the name
+//! pattern. It is compiled once into a `SearchFilter`, which the matching
+//! phase checks against each candidate's metadata. Relative and file-based
+//! time bounds are resolved when the filter is compiled, so every candidate
+//! is compared against the same instant.
+
+use crate::journal::now_ms;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, Metadata};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Binary size units, longest suffix first so `KiB` is not read as `B`
+const SIZE_UNITS: &[(&str, u64)] = &[
+    ("kib", 1 << 10),
+    ("mib", 1 << 20),
+    ("gib", 1 << 30),
+    ("tib", 1 << 40),
+    ("kb", 1 << 10),
+    ("mb", 1 << 20),
+    ("gb", 1 << 30),
+    ("tb", 1 << 40),
+    ("k", 1 << 10),
+    ("m", 1 << 20),
+    ("g", 1 << 30),
+    ("t", 1 << 40),
+    ("b", 1),
+];
+
+/// Duration units in milliseconds, by every accepted spelling
+const DURATION_UNITS: &[(&[&str], u64)] = &[
+    (&["s", "sec", "secs", "second", "seconds"], 1000),
+    (&["m", "min", "mins", "minute", "minutes"], 60 * 1000),
+    (&["h", "hr", "hrs", "hour", "hours"], 60 * 60 * 1000),
+    (&["d", "day", "days"], 24 * 60 * 60 * 1000),
+    (&["w", "week", "weeks"], 7 * 24 * 60 * 60 * 1000),
+    (&["y", "year", "years"], 365 * 24 * 60 * 60 * 1000),
+];
+
+/// Accepted local date-time formats, besides RFC 3339 and plain dates
+const DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+
+// ==================== Types ====================
+
+/// Optional metadata filters for a search, as sent by the frontend
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchFilters {
+    /// Smallest file size to match, such as `10MB` or `1.5 GiB`
+    pub min_size: Option<String>,
+    /// Largest file size to match
+    pub max_size: Option<String>,
+    /// Bounds on the modification time
+    pub modified: Option<TimeRange>,
+    /// Bounds on the creation time
+    pub created: Option<TimeRange>,
+    /// Bounds on the last access time
+    pub accessed: Option<TimeRange>,
+}
+
+/// Which timestamp of a file a time filter looks at
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeKind {
+    /// When the contents last changed
+    Modified,
+    /// When the file was created; not recorded on every platform and filesystem
+    Created,
+    /// When the file was last read; often updated lazily or not at all
+    Accessed,
+}
+
+/// A point in time a timestamp is compared against
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TimeBound {
+    /// A duration before now, such as `30d` or `12 hours`
+    Ago {
+        /// The duration
+        duration: String,
+    },
+    /// A local date (`2024-01-31`), local date and time (`2024-01-31 08:30`),
+    /// or RFC 3339 timestamp
+    Date {
+        /// The date
+        date: String,
+    },
+    /// The same timestamp of another file
+    File {
+        /// Path of the reference file
+        path: String,
+    },
+}
+
+/// Bounds on one timestamp; both are exclusive
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeRange {
+    /// Only match timestamps strictly after this point (newer)
+    pub after: Option<TimeBound>,
+    /// Only match timestamps strictly before this point (older)
+    pub before: Option<TimeBound>,
+}
+
+/// A time range with both bounds resolved to milliseconds since the Unix epoch
+#[derive(Debug, Clone, PartialEq)]
+struct ResolvedTimeRange {
+    kind: TimeKind,
+    after: Option<u64>,
+    before: Option<u64>,
+}
+
+/// Search filters with every value parsed, ready to check candidates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilter {
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    times: Vec<ResolvedTimeRange>,
+}
+
+// ==================== Parsing ====================
+
+/// Parses a size with an optional unit.
+///
+/// Units are case-insensitive and may be separated from the number by
+/// spaces. `K`, `KB` and `KiB` all mean 1024 bytes, matching how sizes are
+/// displayed; the same goes for `M`, `G` and `T`. A number without a unit
+/// is a byte count.
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The size in bytes, rounded down
+/// * `Err(String)` - If the text is not a non-negative number with a known unit
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid size: '{}'", text);
+    let lower = text.trim().to_lowercase();
+
+    let (number, multiplier) = SIZE_UNITS
+        .iter()
+        .find_map(|(suffix, multiplier)| {
+            lower
+                .strip_suffix(suffix)
+                .map(|number| (number.trim_end(), *multiplier))
+        })
+        .unwrap_or((lower.as_str(), 1));
+
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return Err(invalid());
+    }
+    let value: f64 = number.parse().map_err(|_| invalid())?;
+
+    let bytes = value * multiplier as f64;
+    if bytes > u64::MAX as f64 {
+        return Err(invalid());
+    }
+    Ok(bytes as u64)
+}
+
+/// Parses a duration such as `30d`, `12 hours` or `90 min`.
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The duration in milliseconds
+/// * `Err(String)` - If the text is not a whole number with a known unit
+pub fn parse_duration(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid duration: '{}'", text);
+    let lower = text.trim().to_lowercase();
+
+    let split = lower
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (number, unit) = lower.split_at(split);
+    let value: u64 = number.parse().map_err(|_| invalid())?;
+
+    let (_, multiplier) = DURATION_UNITS
+        .iter()
+        .find(|(names, _)| names.contains(&unit.trim_start()))
+        .ok_or_else(invalid)?;
+
+    value.checked_mul(*multiplier).ok_or_else(invalid)
+}
+
+/// Parses a local date, local date and time, or RFC 3339 timestamp.
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The instant in milliseconds since the Unix epoch
+/// * `Err(String)` - If the text is not a supported date or is before 1970
+pub fn parse_date(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid date: '{}'", text);
+    let text = text.trim();
+
+    let millis = if let Ok(date_time) = DateTime::parse_from_rfc3339(text) {
+        date_time.timestamp_millis()
+    } else {
+        let naive = DATE_TIME_FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+            .or_else(|| {
+                NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+            })
+            .ok_or_else(invalid)?;
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(invalid)?
+            .timestamp_millis()
+    };
+
+    u64::try_from(millis).map_err(|_| invalid())
+}
+
+/// Converts a timestamp to milliseconds since the Unix epoch.
+fn system_time_ms(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+impl TimeKind {
+    /// Reads this timestamp from file metadata.
+    ///
+    /// # Returns
+    ///
+    /// Milliseconds since the Unix epoch, or `None` if the platform or
+    /// filesystem does not record it.
+    pub fn read(self, metadata: &Metadata) -> Option<u64> {
+        let time = match self {
+            TimeKind::Modified => metadata.modified(),
+            TimeKind::Created => metadata.created(),
+            TimeKind::Accessed => metadata.accessed(),
+        };
+        time.ok().and_then(system_time_ms)
+    }
+
+    /// The name used in error messages.
+    fn describe(self) -> &'static str {
+        match self {
+            TimeKind::Modified => "modification",
+            TimeKind::Created => "creation",
+            TimeKind::Accessed => "access",
+        }
+    }
+}
+
+impl TimeBound {
+    /// Resolves the bound to milliseconds since the Unix epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The timestamp being compared, used for file bounds
+    /// * `now` - The current time in milliseconds since the Unix epoch
+    fn resolve(&self, kind: TimeKind, now: u64) -> Result<u64, String> {
+        match self {
+            TimeBound::Ago { duration } => Ok(now.saturating_sub(parse_duration(duration)?)),
+            TimeBound::Date { date } => parse_date(date),
+            TimeBound::File { path } => {
+                let metadata = fs::metadata(path)
+                    .map_err(|e| format!("Cannot read reference file {}: {}", path, e))?;
+                kind.read(&metadata).ok_or_else(|| {
+                    format!("The {} time of {} is not available", kind.describe(), path)
+                })
+            }
+        }
+    }
+}
+
+impl TimeRange {
+    /// Resolves both bounds.
+    fn resolve(&self, kind: TimeKind, now: u64) -> Result<ResolvedTimeRange, String> {
+        let after = self
+            .after
+            .as_ref()
+            .map(|bound| bound.resolve(kind, now))
+            .transpose()?;
+        let before = self
+            .before
+            .as_ref()
+            .map(|bound| bound.resolve(kind, now))
+            .transpose()?;
+
+        if let (Some(after), Some(before)) = (after, before) {
+            if after >= before {
+                return Err(format!(
+                    "The {} time range is empty: its start is not before its end",
+                    kind.describe()
+                ));
+            }
+        }
+
+        Ok(ResolvedTimeRange {
+            kind,
+            after,
+            before,
+        })
+    }
+}
+
+// ==================== Filtering ====================
+
+impl SearchFilters {
+    /// Parses the filters.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SearchFilter)` - The compiled filter
+    /// * `Err(String)` - If a value is invalid or the minimum size exceeds the maximum
+    pub fn compile(&self) -> Result<SearchFilter, String> {
+        self.compile_at(now_ms())
+    }
+
+    /// Parses the filters, resolving relative time bounds against `now`
+    /// (milliseconds since the Unix epoch).
+    pub(crate) fn compile_at(&self, now: u64) -> Result<SearchFilter, String> {
+        let min_size = self.min_size.as_deref().map(parse_size).transpose()?;
+        let max_size = self.max_size.as_deref().map(parse_size).transpose()?;
+
+        if let (Some(min), Some(max)) = (min_size, max_size) {
+            if min > max {
+                return Err(format!(
+                    "Minimum size ({} bytes) is larger than maximum size ({} bytes)",
+                    min, max
+                ));
+            }
+        }
+
+        let times = [
+            (TimeKind::Modified, &self.modified),
+            (TimeKind::Created, &self.created),
+            (TimeKind::Accessed, &self.accessed),
+        ]
+        .into_iter()
+        .filter_map(|(kind, range)| range.as_ref().map(|range| range.resolve(kind, now)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(SearchFilter {
+            min_size,
+            max_size,
+            times,
+        })
+    }
+}
+
+impl SearchFilter {
+    /// Whether any size bound is set.
+    fn has_size_bounds(&self) -> bool {
+        self.min_size.is_some() || self.max_size.is_some()
+    }
+
+    /// Checks a candidate's metadata against the filter.
+    ///
+    /// Size bounds only apply to files; directories never match while a size
+    /// bound is set. A candidate whose filtered timestamp is not recorded
+    /// never matches.
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        if self.has_size_bounds() {
+            if metadata.is_dir() {
+                return false;
+            }
+            let size = metadata.len();
+            if self.min_size.is_some_and(|min| size < min)
+                || self.max_size.is_some_and(|max| size > max)
+            {
+                return false;
+            }
+        }
+
+        self.times.iter().all(|range| {
+            range.kind.read(metadata).is_some_and(|time| {
+                range.after.is_none_or(|after| time > after)
+                    && range.before.is_none_or(|before| time < before)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// Tests sizes with and without units.
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("512B").unwrap(), 512);
+        assert_eq!(parse_size("10MB").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(parse_size("1.5 GiB").unwrap(), 3 * 512 * 1024 * 1024);
+        assert_eq!(parse_size(" 2k ").unwrap(), 2048);
+        assert_eq!(parse_size("1tb").unwrap(), 1 << 40);
+    }
+
+    /// Tests malformed sizes are rejected.
+    #[test]
+    fn test_parse_size_invalid() {
+        for text in ["", "MB", "-5MB", "ten MB", "5 PB", "1.2.3K", "5 M B"] {
+            assert!(parse_size(text).is_err(), "{} should be invalid", text);
+        }
+    }
+
+    /// Tests a minimum above the maximum is rejected.
+    #[test]
+    fn test_compile_rejects_inverted_bounds() {
+        let filters = SearchFilters {
+            min_size: Some("2MB".to_string()),
+            max_size: Some("1MB".to_string()),
+            ..Default::default()
+        };
+        assert!(filters.compile().is_err());
+    }
+
+    /// Tests size bounds are inclusive and skip directories.
+    #[test]
+    fn test_size_bounds() {
+        let dir = tempdir().unwrap();
+        let small = dir.path().join("small.bin");
+        let large = dir.path().join("large.bin");
+        fs::write(&small, vec![0u8; 1024]).unwrap();
+        fs::write(&large, vec![0u8; 4096]).unwrap();
+        let metadata = |p: &std::path::Path| fs::metadata(p).unwrap();
+
+        let filter = SearchFilters {
+            min_size: Some("1K".to_string()),
+            max_size: Some("2K".to_string()),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(filter.matches(&metadata(&small)));
+        assert!(!filter.matches(&metadata(&large)));
+        assert!(!filter.matches(&metadata(dir.path())));
+
+        // Without bounds everything matches
+        let filter = SearchFilters::default().compile().unwrap();
+        assert!(filter.matches(&metadata(&large)));
+        assert!(filter.matches(&metadata(dir.path())));
+    }
+
+    /// Tests durations in short and long forms.
+    #[test]
+    fn test_parse_duration() {
+        const DAY: u64 = 24 * 60 * 60 * 1000;
+        assert_eq!(parse_duration("30d").unwrap(), 30 * DAY);
+        assert_eq!(parse_duration("12 hours").unwrap(), 12 * 60 * 60 * 1000);
+        assert_eq!(parse_duration("90min").unwrap(), 90 * 60 * 1000);
+        assert_eq!(parse_duration(" 2W ").unwrap(), 14 * DAY);
+
+        for text in ["", "d", "30", "1.5d", "-3d", "5 fortnights"] {
+            assert!(parse_duration(text).is_err(), "{} should be invalid", text);
+        }
+    }
+
+    /// Tests RFC 3339 timestamps and local dates.
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-02T00:00:00Z").unwrap(), 86_400_000);
+        assert_eq!(parse_date("1970-01-02T01:00:00+01:00").unwrap(), 86_400_000);
+
+        let midnight = Local
+            .with_ymd_and_hms(2024, 1, 31, 0, 0, 0)
+            .earliest()
+            .unwrap()
+            .timestamp_millis() as u64;
+        assert_eq!(parse_date("2024-01-31").unwrap(), midnight);
+        assert_eq!(
+            parse_date("2024-01-31 08:30").unwrap(),
+            midnight + (8 * 60 + 30) * 60 * 1000
+        );
+
+        for text in [
+            "31/01/2024",
+            "2024-02-30",
+            "yesterday",
+            "1969-12-31T00:00:00Z",
+        ] {
+            assert!(parse_date(text).is_err(), "{} should be invalid", text);
+        }
+    }
+
+    /// Tests "older than" and "newer than" bounds relative to now.
+    #[test]
+    fn test_time_filter_ago() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("old.txt");
+        let new = dir.path().join("new.txt");
+        fs::write(&old, "x").unwrap();
+        fs::write(&new, "x").unwrap();
+        let forty_days = std::time::Duration::from_secs(40 * 24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(SystemTime::now() - forty_days)
+            .unwrap();
+        let ago = |duration: &str| {
+            Some(TimeBound::Ago {
+                duration: duration.to_string(),
+            })
+        };
+
+        let older = SearchFilters {
+            modified: Some(TimeRange {
+                before: ago("30 days"),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(older.matches(&fs::metadata(&old).unwrap()));
+        assert!(!older.matches(&fs::metadata(&new).unwrap()));
+
+        let newer = SearchFilters {
+            modified: Some(TimeRange {
+                after: ago("1d"),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(!newer.matches(&fs::metadata(&old).unwrap()));
+        assert!(newer.matches(&fs::metadata(&new).unwrap()));
+    }
+
+    /// Tests bounds taken from a reference file and between two dates.
+    #[test]
+    fn test_time_filter_file_and_dates() {
+        let dir = tempdir().unwrap();
+        let set_modified = |name: &str, date: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, "x").unwrap();
+            let time = UNIX_EPOCH + std::time::Duration::from_millis(parse_date(date).unwrap());
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+            path
+        };
+        let reference = set_modified("reference.txt", "2024-06-01T00:00:00Z");
+        let older = set_modified("older.txt", "2024-01-15T00:00:00Z");
+        let newer = set_modified("newer.txt", "2024-07-01T00:00:00Z");
+
+        let newer_than_reference = SearchFilters {
+            modified: Some(TimeRange {
+                after: Some(TimeBound::File {
+                    path: reference.to_string_lossy().to_string(),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(newer_than_reference.matches(&fs::metadata(&newer).unwrap()));
+        assert!(!newer_than_reference.matches(&fs::metadata(&reference).unwrap()));
+        assert!(!newer_than_reference.matches(&fs::metadata(&older).unwrap()));
+
+        let january = SearchFilters {
+            modified: Some(TimeRange {
+                after: Some(TimeBound::Date {
+                    date: "2024-01-01T00:00:00Z".to_string(),
+                }),
+                before: Some(TimeBound::Date {
+                    date: "2024-02-01T00:00:00Z".to_string(),
+                }),
+            }),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(january.matches(&fs::metadata(&older).unwrap()));
+        assert!(!january.matches(&fs::metadata(&newer).unwrap()));
+    }
+
+    /// Tests empty ranges and missing reference files are rejected.
+    #[test]
+    fn test_time_filter_errors() {
+        let date = |date: &str| {
+            Some(TimeBound::Date {
+                date: date.to_string(),
+            })
+        };
+        let empty = SearchFilters {
+            created: Some(TimeRange {
+                after: date("2024-02-01"),
+                before: date("2024-01-01"),
+            }),
+            ..Default::default()
+        };
+        assert!(empty
+            .compile()
+            .unwrap_err()
+            .contains("creation time range is empty"));
+
+        let missing = SearchFilters {
+            accessed: Some(TimeRange {
+                after: Some(TimeBound::File {
+                    path: "/nonexistent/reference.txt".to_string(),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(missing.compile().is_err());
+    }
+
+    /// Tests time bounds use a tagged camelCase JSON shape.
+    #[test]
+    fn test_time_filter_deserialization() {
+        let filters: SearchFilters = serde_json::from_str(
+            r#"{"minSize":"1MB","modified":{"before":{"type":"ago","duration":"30d"}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(filters.min_size.as_deref(), Some("1MB"));
+        assert_eq!(
+            filters.modified.unwrap().before,
+            Some(TimeBound::Ago {
+                duration: "30d".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,421 @@
+//! Moving files to the desktop trash.
+//!
+//! Implements the freedesktop.org Trash specification used by Linux desktops:
+//! a trashed item is moved into the `files/` directory of a trash can and a
+//! matching `.trashinfo` file in `info/` records its original path and the
+//! deletion date, so file managers can list and restore it.
+//!
+//! Items on the same filesystem as the home trash (`$XDG_DATA_HOME/Trash`) go
+//! there. Items on other mounts go to `$topdir/.Trash/$uid` when the
+//! administrator has set up a shared `.Trash` directory, otherwise to
+//! `$topdir/.Trash-$uid`.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use chrono::Local;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the directory holding trashed items
+const FILES_DIR: &str = "files";
+
+/// Name of the directory holding `.trashinfo` files
+const INFO_DIR: &str = "info";
+
+/// Extension of trash info files
+const INFO_EXTENSION: &str = ".trashinfo";
+
+/// Format of the `DeletionDate` key
+const DELETION_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// A trash can: a directory with `files/` and `info/` subdirectories.
+#[derive(Debug, Clone, PartialEq)]
+struct TrashCan {
+    /// The trash directory itself
+    root: PathBuf,
+    /// Directory that relative `Path=` values are resolved against, for
+    /// per-mount trash cans; `None` for the home trash, which uses absolute paths
+    top_dir: Option<PathBuf>,
+}
+
+impl TrashCan {
+    /// Creates `files/` and `info/` if they are missing.
+    fn ensure_dirs(&self) -> io::Result<()> {
+        create_private_dir(&self.root)?;
+        create_private_dir(&self.root.join(FILES_DIR))?;
+        create_private_dir(&self.root.join(INFO_DIR))
+    }
+
+    /// The `Path=` value recorded for an item.
+    fn info_path_value(&self, path: &Path) -> String {
+        let recorded = self
+            .top_dir
+            .as_ref()
+            .and_then(|top| path.strip_prefix(top).ok())
+            .unwrap_or(path);
+        encode_trash_path(recorded)
+    }
+}
+
+/// Creates a directory readable only by its owner, if it does not exist.
+fn create_private_dir(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        return Ok(());
+    }
+
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(path)
+}
+
+/// Percent-encodes a path for the `Path=` key, keeping `/` and unreserved characters.
+fn encode_trash_path(path: &Path) -> String {
+    let mut encoded = String::new();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Returns the home trash directory, `$XDG_DATA_HOME/Trash`.
+///
+/// Falls back to `~/.local/share/Trash` when `XDG_DATA_HOME` is unset or not
+/// absolute, as the XDG base directory specification requires.
+fn home_trash_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(data_home.join("Trash"))
+}
+
+/// Returns the id of the current user.
+#[cfg(target_os = "linux")]
+fn current_uid() -> io::Result<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    // /proc/self is owned by the process's real user
+    fs::metadata("/proc/self").map(|metadata| metadata.uid())
+}
+
+/// Finds the mount point containing `path` by walking up until the device changes.
+#[cfg(target_os = "linux")]
+fn mount_top_dir(path: &Path) -> io::Result<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let device = path.symlink_metadata()?.dev();
+    let mut top = path.to_path_buf();
+
+    while let Some(parent) = top.parent() {
+        if parent.metadata()?.dev() != device {
+            break;
+        }
+        top = parent.to_path_buf();
+    }
+
+    Ok(top)
+}
+
+/// Checks whether `path` is on the same filesystem as `dir` (or its nearest existing ancestor).
+#[cfg(target_os = "linux")]
+fn same_device(path: &Path, dir: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(device) = path.symlink_metadata().map(|m| m.dev()) else {
+        return false;
+    };
+    dir.ancestors()
+        .find_map(|ancestor| ancestor.metadata().ok())
+        .is_some_and(|metadata| metadata.dev() == device)
+}
+
+/// Picks the trash can for an item on another filesystem than the home trash.
+///
+/// Uses `$topdir/.Trash/$uid` if `$topdir/.Trash` is a real directory with
+/// the sticky bit set, and `$topdir/.Trash-$uid` otherwise.
+#[cfg(target_os = "linux")]
+fn top_dir_trash(top_dir: &Path, uid: u32) -> TrashCan {
+    use std::os::unix::fs::PermissionsExt;
+
+    let shared = top_dir.join(".Trash");
+    let shared_is_valid = shared
+        .symlink_metadata()
+        .is_ok_and(|metadata| metadata.is_dir() && metadata.permissions().mode() & 0o1000 != 0);
+
+    let root = if shared_is_valid {
+        shared.join(uid.to_string())
+    } else {
+        top_dir.join(format!(".Trash-{}", uid))
+    };
+
+    TrashCan {
+        root,
+        top_dir: Some(top_dir.to_path_buf()),
+    }
+}
+
+/// Writes the `.trashinfo` file for an item, choosing a name not yet in use.
+///
+/// The info file is created exclusively first, which reserves the name in
+/// `files/` as the specification requires.
+///
+/// # Returns
+///
+/// The reserved name and the path of the info file.
+fn reserve_trash_name(trash: &TrashCan, path: &Path) -> io::Result<(String, PathBuf)> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+
+    let contents = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        trash.info_path_value(path),
+        Local::now().format(DELETION_DATE_FORMAT)
+    );
+
+    for attempt in 1.. {
+        let name = if attempt == 1 {
+            file_name.clone()
+        } else {
+            format!("{}.{}", file_name, attempt)
+        };
+        let info_path = trash
+            .root
+            .join(INFO_DIR)
+            .join(format!("{}{}", name, INFO_EXTENSION));
+
+        if trash
+            .root
+            .join(FILES_DIR)
+            .join(&name)
+            .symlink_metadata()
+            .is_ok()
+        {
+            continue;
+        }
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())?;
+                return Ok((name, info_path));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    unreachable!("the name search only ends by returning")
+}
+
+/// Moves an item into a trash can.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` - Where the item now lives inside the trash
+/// * `Err(io::Error)` - The item could not be trashed and was left in place
+fn move_to_trash_can(trash: &TrashCan, path: &Path, same_filesystem: bool) -> io::Result<PathBuf> {
+    trash.ensure_dirs()?;
+    let (name, info_path) = reserve_trash_name(trash, path)?;
+    let destination = trash.root.join(FILES_DIR).join(name);
+
+    let result = if same_filesystem {
+        fs::rename(path, &destination)
+    } else {
+        crate::file_move::move_file(path, &destination, |_, _| {})
+    };
+
+    match result {
+        Ok(()) => Ok(destination),
+        Err(e) => {
+            // Release the reserved name
+            let _ = fs::remove_file(&info_path);
+            Err(e)
+        }
+    }
+}
+
+/// Moves a file or directory to the trash.
+///
+/// # Arguments
+///
+/// * `path` - The item to trash; symbolic links are trashed, not followed
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` - Where the item now lives inside the trash
+/// * `Err(String)` - The item could not be trashed and was left in place
+#[cfg(target_os = "linux")]
+pub(crate) fn move_to_trash(path: &Path) -> Result<PathBuf, String> {
+    let home_trash = home_trash_dir().ok_or_else(|| "Cannot locate the home trash".to_string())?;
+    move_to_trash_with_home(path, &home_trash)
+}
+
+/// Moves a file or directory to the trash.
+///
+/// Only the freedesktop.org trash used on Linux is supported.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn move_to_trash(_path: &Path) -> Result<PathBuf, String> {
+    Err("Moving to the trash is not supported on this platform".to_string())
+}
+
+/// Moves an item to the trash, using `home_trash` as the home trash directory.
+#[cfg(target_os = "linux")]
+fn move_to_trash_with_home(path: &Path, home_trash: &Path) -> Result<PathBuf, String> {
+    // Recorded paths must be absolute; symlinks are not resolved
+    let path = std::path::absolute(path).map_err(|e| e.to_string())?;
+    path.symlink_metadata().map_err(|e| e.to_string())?;
+
+    let home = TrashCan {
+        root: home_trash.to_path_buf(),
+        top_dir: None,
+    };
+
+    if same_device(&path, home_trash) {
+        return move_to_trash_can(&home, &path, true).map_err(|e| e.to_string());
+    }
+
+    // The item lives on another mount; use that mount's trash when possible
+    let top_dir_result = current_uid()
+        .and_then(|uid| Ok(top_dir_trash(&mount_top_dir(&path)?, uid)))
+        .and_then(|trash| move_to_trash_can(&trash, &path, true));
+
+    match top_dir_result {
+        Ok(destination) => Ok(destination),
+        // The mount has no usable trash; copy into the home trash instead
+        Err(_) => move_to_trash_can(&home, &path, false).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Tests paths are percent-encoded except for `/` and unreserved characters.
+    #[test]
+    fn test_encode_trash_path() {
+        assert_eq!(
+            encode_trash_path(Path::new("/home/me/a file%.txt")),
+            "/home/me/a%20file%25.txt"
+        );
+        assert_eq!(encode_trash_path(Path::new("/tmp/é")), "/tmp/%C3%A9");
+    }
+
+    /// Tests a file is moved into files/ with a matching info file.
+    #[test]
+    fn test_move_to_trash_writes_info() {
+        let dir = tempdir().unwrap();
+        let trash = dir.path().join("Trash");
+        let file = dir.path().join("my notes.txt");
+        fs::write(&file, "data").unwrap();
+
+        let destination = move_to_trash_with_home(&file, &trash).unwrap();
+
+        assert!(!file.exists());
+        assert_eq!(destination, trash.join("files").join("my notes.txt"));
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "data");
+
+        let info = fs::read_to_string(trash.join("info/my notes.txt.trashinfo")).unwrap();
+        let lines: Vec<&str> = info.lines().collect();
+        assert_eq!(lines[0], "[Trash Info]");
+        assert_eq!(lines[1], format!("Path={}", encode_trash_path(&file)));
+        assert!(lines[1].ends_with("my%20notes.txt"));
+        assert!(lines[2].starts_with("DeletionDate="));
+        assert!(chrono::NaiveDateTime::parse_from_str(
+            &lines[2]["DeletionDate=".len()..],
+            DELETION_DATE_FORMAT
+        )
+        .is_ok());
+    }
+
+    /// Tests items with the same name get distinct trash names.
+    #[test]
+    fn test_move_to_trash_name_conflict() {
+        let dir = tempdir().unwrap();
+        let trash = dir.path().join("Trash");
+        let first = dir.path().join("a").join("report.txt");
+        let second = dir.path().join("b").join("report.txt");
+        for path in [&first, &second] {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, path.to_string_lossy().as_bytes()).unwrap();
+        }
+
+        let first_dest = move_to_trash_with_home(&first, &trash).unwrap();
+        let second_dest = move_to_trash_with_home(&second, &trash).unwrap();
+
+        assert_eq!(first_dest, trash.join("files/report.txt"));
+        assert_eq!(second_dest, trash.join("files/report.txt.2"));
+        assert!(trash.join("info/report.txt.2.trashinfo").exists());
+        assert_eq!(
+            fs::read_to_string(&second_dest).unwrap(),
+            second.to_string_lossy()
+        );
+    }
+
+    /// Tests directories are trashed with their contents.
+    #[test]
+    fn test_move_to_trash_directory() {
+        let dir = tempdir().unwrap();
+        let trash = dir.path().join("Trash");
+        let folder = dir.path().join("folder");
+        fs::create_dir_all(folder.join("nested")).unwrap();
+        fs::write(folder.join("nested/file.txt"), "x").unwrap();
+
+        let destination = move_to_trash_with_home(&folder, &trash).unwrap();
+
+        assert!(!folder.exists());
+        assert!(destination.join("nested/file.txt").exists());
+    }
+
+    /// Tests a missing item fails without leaving an info file.
+    #[test]
+    fn test_move_to_trash_missing_item() {
+        let dir = tempdir().unwrap();
+        let trash = dir.path().join("Trash");
+
+        assert!(move_to_trash_with_home(&dir.path().join("missing"), &trash).is_err());
+        assert!(!trash.join("info/missing.trashinfo").exists());
+    }
+
+    /// Tests per-mount trash cans record paths relative to the mount.
+    #[test]
+    fn test_top_dir_trash() {
+        let dir = tempdir().unwrap();
+
+        let trash = top_dir_trash(dir.path(), 1000);
+        assert_eq!(trash.root, dir.path().join(".Trash-1000"));
+        assert_eq!(
+            trash.info_path_value(&dir.path().join("photos/a b.jpg")),
+            "photos/a%20b.jpg"
+        );
+
+        // A shared .Trash is only used when it has the sticky bit
+        use std::os::unix::fs::PermissionsExt;
+        let shared = dir.path().join(".Trash");
+        fs::create_dir(&shared).unwrap();
+        assert_eq!(
+            top_dir_trash(dir.path(), 1000).root,
+            dir.path().join(".Trash-1000")
+        );
+
+        fs::set_permissions(&shared, fs::Permissions::from_mode(0o1777)).unwrap();
+        assert_eq!(top_dir_trash(dir.path(), 1000).root, shared.join("1000"));
+    }
+}
@@ -11,6 +11,29 @@ vi.mock("@tauri-apps/plugin-dialog", () => ({
   message: vi.fn(),
 }));
 
+// Stands in for the backend preview: a plain find/replace on each name
+const previewRenames = (args: {
+  paths: string[];
+  options: { findText: string; replaceText: string };
+}) =>
+  args.paths.map((path) => {
+    const name = path.split("/").pop()!;
+    const newName = args.options.findText
+      ? name.replace(args.options.findText, args.options.replaceText)
+      : name;
+    return {
+      path,
+      name,
+      new_name: newName,
+      new_path: path.replace(name, newName),
+      name_after_replace: newName,
+      matches: [],
+      replacement_segments: [],
+      numbering: null,
+      diff: [],
+    };
+  });
+
 describe("BatchRenamer", () => {
   beforeEach(() => {
     vi.clearAllMocks();
@@ -27,8 +50,16 @@ describe("BatchRenamer", () => {
     const mockOpen = vi.mocked(dialog.open);
     mockOpen.mockResolvedValue(["/path/to/file1.txt", "/path/to/file2.txt"]);
 
-    // Mock rename invoke
-    mockInvoke.mockResolvedValue(["file1_new.txt", "file2.txt"]);
+    // Mock preview, root selection and rename invokes
+    mockInvoke.mockImplementation(async (command: string, args) => {
+      if (command === "preview_renames") return previewRenames(args);
+      if (command === "choose_operation_root") return "/path/to";
+      return {
+        renamed: ["/path/to/test1.txt", "/path/to/test2.txt"],
+        batch_id: "1",
+        journal_error: null,
+      };
+    });
 
     render(() => <BatchRenamer />);
 
@@ -50,18 +81,106 @@ describe("BatchRenamer", () => {
     const replaceInput = screen.getByLabelText("Replace with");
     fireEvent.input(replaceInput, { target: { value: "test" } });
 
-    // Click Rename Files
+    // Click Rename Files once the preview has arrived
     const renameButton = screen.getByText("Rename Files");
-    expect(renameButton).not.toBeDisabled();
+    await waitFor(() => expect(renameButton).not.toBeDisabled());
     fireEvent.click(renameButton);
 
-    // Verify invoke was called
+    // Verify the root was chosen and invoke was called with it
     await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("choose_operation_root", {
+        title: "Choose the folder to rename files in",
+        defaultPath: "/path/to",
+      });
       expect(mockInvoke).toHaveBeenCalledWith("batch_rename", {
         files: [
           ["/path/to/file1.txt", "/path/to/test1.txt"],
           ["/path/to/file2.txt", "/path/to/test2.txt"],
         ],
+        root: "/path/to",
+      });
+    });
+  });
+
+  it("does not rename when no root is chosen", async () => {
+    const mockOpen = vi.mocked(dialog.open);
+    mockOpen.mockResolvedValue(["/path/to/file1.txt"]);
+    mockInvoke.mockImplementation(async (command: string, args) =>
+      command === "preview_renames" ? previewRenames(args) : null
+    );
+
+    render(() => <BatchRenamer />);
+    fireEvent.click(screen.getByText("Select Files"));
+    await waitFor(() => {
+      expect(screen.getAllByText("file1.txt")[0]).toBeInTheDocument();
+    });
+
+    fireEvent.input(screen.getByLabelText("Find"), {
+      target: { value: "file" },
+    });
+    fireEvent.input(screen.getByLabelText("Replace with"), {
+      target: { value: "test" },
+    });
+    await waitFor(() =>
+      expect(screen.getByText("Rename Files")).not.toBeDisabled()
+    );
+    fireEvent.click(screen.getByText("Rename Files"));
+
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith(
+        "choose_operation_root",
+        expect.anything()
+      );
+    });
+    expect(mockInvoke).not.toHaveBeenCalledWith(
+      "batch_rename",
+      expect.anything()
+    );
+  });
+
+  it("renames files to the paths computed by preview_renames", async () => {
+    const mockOpen = vi.mocked(dialog.open);
+    mockOpen.mockResolvedValue(["/path/to/file1.txt"]);
+    mockInvoke.mockImplementation(async (command: string, args) => {
+      if (command === "preview_renames") {
+        return previewRenames(args).map((preview) =>
+          args.options.findText
+            ? {
+                ...preview,
+                new_name: "001-test1.txt",
+                new_path: "/path/to/001-test1.txt",
+              }
+            : preview
+        );
+      }
+      if (command === "choose_operation_root") return "/path/to";
+      return { renamed: [], batch_id: "1", journal_error: null };
+    });
+
+    render(() => <BatchRenamer />);
+    fireEvent.click(screen.getByText("Select Files"));
+    await waitFor(() => {
+      expect(screen.getAllByText("file1.txt")[0]).toBeInTheDocument();
+    });
+
+    fireEvent.input(screen.getByLabelText("Find"), {
+      target: { value: "file" },
+    });
+
+    // The backend's name is shown and submitted as-is
+    await waitFor(() => {
+      expect(screen.getByText("Rename Files")).not.toBeDisabled();
+    });
+    expect(mockInvoke).toHaveBeenCalledWith("preview_renames", {
+      paths: ["/path/to/file1.txt"],
+      options: expect.objectContaining({ findText: "file" }),
+    });
+    fireEvent.click(screen.getByText("Rename Files"));
+
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("batch_rename", {
+        files: [["/path/to/file1.txt", "/path/to/001-test1.txt"]],
+        root: "/path/to",
       });
     });
   });
@@ -33,12 +33,9 @@ const RegexHighlightText: Component<RegexHighlightTextProps> = (props) => {
 
     // Apply styles. Later matches (higher group indices usually) overwrite earlier ones.
     // Since matches are flattened, we need to be careful.
-    // getRegexMatches returns matches sorted by their occurrence in the string, 
+    // preview_renames returns matches sorted by their occurrence in the string,
     // and for each occurrence, it includes all groups.
-    // But the order in the array is: Match1-Group0, Match1-Group1, Match1-Group2, Match2-Group0...
-    // Or it might be mixed if I implemented it to push all groups for a match.
-    // My implementation:
-    // for (let i = 0; i < indices.length; i++) { matches.push(...) }
+    // The order in the array is: Match1-Group0, Match1-Group1, Match1-Group2, Match2-Group0...
     // So for a single match, Group 0 comes first, then Group 1, etc.
     // So iterating the array in order means higher group indices overwrite lower ones.
     // This is exactly what we want for nesting (innermost is usually higher index).
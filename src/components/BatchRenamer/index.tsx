@@ -1,4 +1,4 @@
-import { createSignal, createMemo, Show } from "solid-js";
+import { createSignal, createMemo, createEffect, Show } from "solid-js";
 import { open } from "@tauri-apps/plugin-dialog";
 import { invoke, Channel } from "@tauri-apps/api/core";
 import RenamerControls from "./RenamerControls";
@@ -8,18 +8,22 @@ import Header from "../ui/Header";
 import ActionButtons from "./ActionButtons";
 import ProgressBar from "../ui/ProgressBar";
 import {
-  calculateNewName,
-  getRegexMatches,
-  getReplacementSegments,
-  applyNumbering,
+  toRegexMatch,
   getNumberingInfo,
   NumberingOptions,
   DEFAULT_NUMBERING_OPTIONS,
 } from "./renamingUtils";
-import { getFileName, getDirectory, joinPath } from "../../utils/path";
 import {
+  getFileName,
+  isInsideDirectory,
+  getCommonDirectory,
+} from "../../utils/path";
+import {
+  BatchRenameResult,
   ListProgressEvent,
   ListProgressState,
+  RenameOptions,
+  RenamePreview,
   RenameProgressEvent,
   RenameProgressState,
 } from "./types";
@@ -42,6 +46,9 @@ export default function BatchRenamer() {
     createSignal<NumberingOptions>(DEFAULT_NUMBERING_OPTIONS);
   const [numberingExpanded, setNumberingExpanded] = createSignal(false);
 
+  // Root folder approved by the backend for renames
+  const [renameRoot, setRenameRoot] = createSignal<string | null>(null);
+
   // Progress states
   const [isScanning, setIsScanning] = createSignal(false);
   const [isRenaming, setIsRenaming] = createSignal(false);
@@ -95,107 +102,89 @@ export default function BatchRenamer() {
     setStatusMap({});
   };
 
-  const fileItems = createMemo(() => {
-    const paths = selectedPaths();
-    const currentStatus = statusMap();
-    const numOptions = numberingOptions();
-
-    // First pass: calculate new names
-    const items = paths.map((path, index) => {
-      const name = getFileName(path);
-      let newName = name;
-
-      const result = calculateNewName(
-        name,
-        findText(),
-        replaceText(),
-        caseSensitive(),
-        regexMode(),
-        replaceFirstOnly(),
-        includeExt()
-      );
+  // New names are computed by the backend; the UI only renders them
+  const [previews, setPreviews] = createSignal<RenamePreview[]>([]);
+  let previewRequest = 0;
 
-      newName = result.newName;
+  createEffect(() => {
+    const paths = selectedPaths();
+    const options: RenameOptions = {
+      findText: findText(),
+      replaceText: replaceText(),
+      caseSensitive: caseSensitive(),
+      regexMode: regexMode(),
+      replaceFirstOnly: replaceFirstOnly(),
+      includeExt: includeExt(),
+      numbering: numberingOptions(),
+    };
+
+    // Only the latest request may update the previews
+    const request = ++previewRequest;
+    if (paths.length === 0) {
+      setPreviews([]);
+      return;
+    }
 
-      if (result.error) {
-        if (regexMode()) {
-          setRegexError(result.error);
-        }
-        console.error("Renaming error", result.error);
-      } else if (regexMode()) {
+    invoke<RenamePreview[]>("preview_renames", { paths, options })
+      .then((result) => {
+        if (request !== previewRequest) return;
+        setPreviews(result);
         setRegexError(undefined);
-      }
+      })
+      .catch((error) => {
+        if (request !== previewRequest) return;
+        setPreviews([]);
+        setRegexError(String(error));
+        console.error("Renaming error", error);
+      });
+  });
 
-      // Store name after find/replace (before numbering) for diff display
-      const nameAfterReplace = newName;
-
-      // Get numbering info before applying (for color-coded preview)
-      const numberingInfo = getNumberingInfo(newName, index, numOptions);
-
-      // Apply numbering after find/replace
-      newName = applyNumbering(newName, index, numOptions);
-
-      let regexMatches;
-      let newNameRegexMatches;
-      if (!result.error && regexMode() && findText()) {
-        try {
-          const flags = caseSensitive()
-            ? replaceFirstOnly()
-              ? ""
-              : "g"
-            : replaceFirstOnly()
-            ? "i"
-            : "gi";
-          const regex = new RegExp(findText(), flags);
-
-          let targetText = name;
-          if (!includeExt()) {
-            const lastDotIndex = name.lastIndexOf(".");
-            if (lastDotIndex > 0) {
-              targetText = name.substring(0, lastDotIndex);
-            }
-          }
+  const fileItems = createMemo(() => {
+    const currentStatus = statusMap();
+    const previewList = previews();
 
-          regexMatches = getRegexMatches(targetText, regex);
+    const items = selectedPaths().map((path, index) => {
+      const preview = previewList[index];
 
-          const replacementResult = getReplacementSegments(
-            targetText,
-            regex,
-            replaceText()
-          );
-          newNameRegexMatches = replacementResult.segments;
-        } catch {
-          // Ignore errors, they are handled above
-        }
+      // Keep the name until the preview for this path arrives
+      if (!preview || preview.path !== path) {
+        const name = getFileName(path);
+        return { path, name, newName: name, newPath: path };
       }
 
       return {
         path,
-        name,
-        newName,
-        nameAfterReplace,
-        regexMatches,
-        newNameRegexMatches,
-        numberingInfo,
+        name: preview.name,
+        newName: preview.new_name,
+        newPath: preview.new_path,
+        nameAfterReplace: preview.name_after_replace,
+        regexMatches: regexMode()
+          ? preview.matches.map(toRegexMatch)
+          : undefined,
+        newNameRegexMatches: regexMode()
+          ? preview.replacement_segments.map(toRegexMatch)
+          : undefined,
+        numberingInfo: getNumberingInfo(preview),
       };
     });
 
     // Check for collisions
-    const newNameCounts = new Map<string, number>();
+    const newPathCounts = new Map<string, number>();
     items.forEach((item) => {
-      const fullNewPath = item.path.replace(item.name, item.newName);
-      newNameCounts.set(fullNewPath, (newNameCounts.get(fullNewPath) || 0) + 1);
+      newPathCounts.set(
+        item.newPath,
+        (newPathCounts.get(item.newPath) || 0) + 1
+      );
     });
 
     return items.map((item) => {
-      const fullNewPath = item.path.replace(item.name, item.newName);
-      const hasCollision = newNameCounts.get(fullNewPath)! > 1;
+      const hasCollision = newPathCounts.get(item.newPath)! > 1;
 
       return {
         ...item,
         status: currentStatus[item.path] || "idle",
         hasCollision,
-      } as FileItem & { hasCollision: boolean };
+      } as FileItem & { newPath: string; hasCollision: boolean };
     });
   });
 
@@ -280,21 +269,40 @@ export default function BatchRenamer() {
     }
   }
 
+  /**
+   * Returns an approved root containing all paths, asking the user to choose
+   * one if the current root does not cover them.
+   */
+  async function ensureRenameRoot(paths: string[]): Promise<string | null> {
+    const current = renameRoot();
+    if (current && paths.every((path) => isInsideDirectory(path, current))) {
+      return current;
+    }
+
+    const root = await invoke<string | null>("choose_operation_root", {
+      title: "Choose the folder to rename files in",
+      defaultPath: getCommonDirectory(paths),
+    });
+    if (root) setRenameRoot(root);
+    return root;
+  }
+
   async function handleRename() {
     const filesToRename = fileItems()
       .filter((f) => f.name !== f.newName)
-      .map((f) => {
-        const dir = getDirectory(f.path);
-        const newPath = joinPath(dir, f.newName);
-        return [f.path, newPath] as [string, string];
-      });
+      .map((f) => [f.path, f.newPath] as [string, string]);
 
     if (filesToRename.length === 0) return;
 
     setIsRenaming(true);
 
     try {
-      let result: string[];
+      const root = await ensureRenameRoot(
+        filesToRename.map(([path]) => path)
+      );
+      if (!root) return;
+
+      let result: BatchRenameResult;
 
       // Use streaming progress for larger rename operations
       if (filesToRename.length > 10) {
@@ -323,6 +331,14 @@ export default function BatchRenamer() {
                 currentPath: event.currentPath,
               });
               break;
+            case "copying":
+              setRenameProgress((prev) => ({
+                ...prev,
+                currentPath: event.currentPath,
+                bytesCopied: event.bytesCopied,
+                totalBytes: event.totalBytes,
+              }));
+              break;
             case "completed":
               setRenameProgress({
                 phase: "completed",
@@ -333,17 +349,19 @@ export default function BatchRenamer() {
           }
         };
 
-        result = await invoke<string[]>("batch_rename_with_progress", {
+        result = await invoke<BatchRenameResult>("batch_rename_with_progress", {
           files: filesToRename,
+          root,
           onProgress: progressChannel,
         });
       } else {
-        result = await invoke<string[]>("batch_rename", {
+        result = await invoke<BatchRenameResult>("batch_rename", {
           files: filesToRename,
+          root,
         });
       }
 
-      console.log("Renamed files:", result);
+      console.log("Renamed files:", result.renamed);
 
       const newPathsMap = new Map(filesToRename);
       const newStatusMap: Record<string, "idle" | "success" | "error"> = {};
@@ -359,6 +377,12 @@ export default function BatchRenamer() {
 
       setSelectedPaths(updatedPaths);
       setStatusMap(newStatusMap);
+
+      if (result.journal_error) {
+        alert(
+          `Renamed, but this batch cannot be undone: ${result.journal_error}`
+        );
+      }
     } catch (error) {
       console.error("Rename failed:", error);
       const errorStatusMap: Record<string, "idle" | "success" | "error"> = {};
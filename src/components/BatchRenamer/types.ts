@@ -1,3 +1,64 @@
+import { DiffSegment } from "../../utils/diff";
+import { NumberingOptions, NumberingPosition } from "./renamingUtils";
+
+/**
+ * Result of batch_rename and batch_rename_with_progress.
+ */
+export interface BatchRenameResult {
+  renamed: string[];
+  batch_id: string | null;
+  // Set when the batch could not be recorded in the undo journal
+  journal_error: string | null;
+}
+
+// ==================== Preview Types ====================
+// These match the backend rename rule engine in rename_rules.rs
+
+/**
+ * Find/replace and numbering options sent to preview_renames.
+ */
+export interface RenameOptions {
+  findText: string;
+  replaceText: string;
+  caseSensitive: boolean;
+  regexMode: boolean;
+  replaceFirstOnly: boolean;
+  includeExt: boolean;
+  numbering: NumberingOptions;
+}
+
+/**
+ * A highlighted span of a name; offsets are character positions.
+ */
+export interface MatchSegment {
+  start: number;
+  end: number;
+  // 0 for a whole match, 1+ for capture groups, -1 for literal replacement text
+  group_index: number;
+  content: string;
+}
+
+/**
+ * Preview of the new name for a single file, returned by preview_renames.
+ */
+export interface RenamePreview {
+  path: string;
+  name: string;
+  new_name: string;
+  new_path: string;
+  // Name after find/replace, before numbering
+  name_after_replace: string;
+  matches: MatchSegment[];
+  replacement_segments: MatchSegment[];
+  numbering: {
+    formatted_number: string;
+    separator: string;
+    position: NumberingPosition;
+    insert_index: number;
+  } | null;
+  diff: DiffSegment[];
+}
+
 // ==================== Streaming Progress Types ====================
 // These match the backend Rust enum variants serialized with camelCase
 
@@ -8,7 +69,8 @@
 export type ListProgressEvent =
   | { type: "started"; basePath: string }
   | { type: "scanning"; currentDir: string; filesFound: number }
-  | { type: "completed"; totalFiles: number };
+  | { type: "completed"; totalFiles: number }
+  | { type: "cancelled"; filesFound: number };
 
 /**
  * Progress events for batch rename operations.
@@ -17,7 +79,14 @@ export type ListProgressEvent =
 export type RenameProgressEvent =
   | { type: "started"; totalFiles: number }
   | { type: "progress"; current: number; total: number; currentPath: string }
-  | { type: "completed"; successful: number; failed: number };
+  | {
+      type: "copying";
+      currentPath: string;
+      bytesCopied: number;
+      totalBytes: number;
+    }
+  | { type: "completed"; successful: number; failed: number }
+  | { type: "cancelled"; successful: number; failed: number; total: number };
 
 /**
  * State for tracking folder scanning progress in UI
@@ -37,5 +106,7 @@ export interface RenameProgressState {
   current: number;
   total: number;
   currentPath?: string;
+  bytesCopied?: number;
+  totalBytes?: number;
 }
 
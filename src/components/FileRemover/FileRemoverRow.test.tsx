@@ -164,6 +164,21 @@ describe("FileRemoverRow", () => {
       expect(highlightedSpans).toHaveLength(0);
       expect(screen.getByText("noMatch.txt")).toBeInTheDocument();
     });
+
+    it("highlights the path when matched against a path", () => {
+      const file = createMockFile({
+        path: "/test/build/app.o",
+        name: "app.o",
+        matchRanges: [[6, 11]], // "build" is highlighted
+        matchTarget: "relativePath",
+      });
+      render(() => <FileRemoverRow file={file} onToggleSelect={vi.fn()} />);
+
+      const highlightedSpans = document.querySelectorAll(".bg-warning\\/30");
+      expect(highlightedSpans).toHaveLength(1);
+      expect(highlightedSpans[0]).toHaveTextContent("build");
+      expect(screen.getByText("app.o")).toBeInTheDocument();
+    });
   });
 
   describe("Selection styling", () => {
@@ -14,9 +14,24 @@ interface FileRemoverRowProps {
  * Supports keyboard navigation for accessibility.
  */
 const FileRemoverRow: Component<FileRemoverRowProps> = (props) => {
+  // Matches against a path are highlighted in the path line
+  const matchesPath = () =>
+    props.file.matchTarget === "relativePath" || props.file.matchTarget === "fullPath";
+
   // Build highlighted name segments
   const highlightedName = () => {
-    return buildHighlightedSegments(props.file.name, props.file.matchRanges);
+    return buildHighlightedSegments(
+      props.file.name,
+      matchesPath() ? [] : props.file.matchRanges
+    );
+  };
+
+  // Build highlighted path segments
+  const highlightedPath = () => {
+    return buildHighlightedSegments(
+      props.file.path,
+      matchesPath() ? props.file.matchRanges : []
+    );
   };
 
   const handleKeyDown = (e: KeyboardEvent) => {
@@ -72,7 +87,21 @@ const FileRemoverRow: Component<FileRemoverRowProps> = (props) => {
             )}
           </For>
         </div>
-        <div class="text-xs text-base-content/50 truncate">{props.file.path}</div>
+        <div class="text-xs text-base-content/50 truncate">
+          <For each={highlightedPath()}>
+            {(segment) => (
+              <span
+                class={
+                  segment.isMatch
+                    ? "match-highlight bg-warning/30 text-warning-content rounded px-0.5"
+                    : ""
+                }
+              >
+                {segment.text}
+              </span>
+            )}
+          </For>
+        </div>
       </div>
 
       <div class="flex-shrink-0 text-xs text-base-content/50">
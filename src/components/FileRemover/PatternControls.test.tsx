@@ -99,6 +99,16 @@ describe("PatternControls", () => {
       expect(setPatternType).toHaveBeenCalledWith("regex");
     });
 
+    it("calls setPatternType for Glob button", async () => {
+      const setPatternType = vi.fn();
+      render(() => (
+        <PatternControls {...defaultProps} setPatternType={setPatternType} />
+      ));
+
+      await fireEvent.click(screen.getByText("Glob"));
+      expect(setPatternType).toHaveBeenCalledWith("glob");
+    });
+
     it("calls setPatternType for Simple button", async () => {
       const setPatternType = vi.fn();
       render(() => (
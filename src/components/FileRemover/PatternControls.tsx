@@ -63,6 +63,8 @@ const PatternControls: Component<PatternControlsProps> = (props) => {
         return ".tmp, .log, .bak";
       case "regex":
         return "Enter regex pattern (e.g., \\d+ for numbers)...";
+      case "glob":
+        return "*.log, **/cache/*.tmp, photo_{001,002}.jpg";
     }
   };
 
@@ -74,6 +76,8 @@ const PatternControls: Component<PatternControlsProps> = (props) => {
         return "Comma-separated list of extensions (with or without dots)";
       case "regex":
         return "Regular expression pattern for advanced matching";
+      case "glob":
+        return "Shell glob; patterns containing / match the path below the folder";
     }
   };
 
@@ -154,6 +158,14 @@ const PatternControls: Component<PatternControlsProps> = (props) => {
             >
               Regex
             </button>
+            <button
+              class={`btn join-item flex-1 ${
+                props.patternType === "glob" ? "btn-active" : ""
+              }`}
+              onClick={() => props.setPatternType("glob")}
+            >
+              Glob
+            </button>
           </div>
         </div>
 
@@ -1,5 +1,4 @@
 import { createSignal, createMemo, onMount, Show } from "solid-js";
-import { open } from "@tauri-apps/plugin-dialog";
 import { invoke, Channel } from "@tauri-apps/api/core";
 import PatternControls from "./PatternControls";
 import ActionButtons from "./ActionButtons";
@@ -16,7 +15,7 @@ import {
   SearchProgressState,
   StreamingDeleteProgress,
 } from "./types";
-import { validatePattern, checkDangerousOperation } from "./utils";
+import { validatePattern, checkDangerousOperation, toFullPathRanges } from "./utils";
 import Header from "../ui/Header";
 
 // localStorage keys for preferences
@@ -70,7 +69,7 @@ export default function FileRemover() {
   onMount(() => {
     try {
       const savedType = localStorage.getItem(STORAGE_KEYS.patternType);
-      if (savedType && ["simple", "extension", "regex"].includes(savedType)) {
+      if (savedType && ["simple", "extension", "regex", "glob"].includes(savedType)) {
         setPatternType(savedType as PatternType);
       }
 
@@ -136,12 +135,12 @@ export default function FileRemover() {
 
   // Actions
   async function selectFolder() {
-    const selected = await open({
-      multiple: false,
-      directory: true,
+    // The backend only deletes inside a folder chosen through its own dialog
+    const selected = await invoke<string | null>("choose_operation_root", {
+      title: "Choose the folder to remove files from",
     });
 
-    if (selected && typeof selected === "string") {
+    if (selected) {
       setBasePath(selected);
     }
   }
@@ -206,9 +205,16 @@ export default function FileRemover() {
         results.map((r) => ({
           path: r.path,
           name: r.name,
-          matchRanges: r.match_ranges,
+          matchRanges:
+            r.match_target === "relativePath"
+              ? toFullPathRanges(basePath(), r.match_ranges)
+              : r.match_ranges,
+          matchTarget: r.match_target,
           size: r.size,
           isDirectory: r.is_directory,
+          modifiedMs: r.modified_ms,
+          createdMs: r.created_ms,
+          accessedMs: r.accessed_ms,
           selected: true,
         }))
       );
@@ -276,6 +282,8 @@ export default function FileRemover() {
         result = await invoke<DeleteResult>("batch_delete", {
           files: filesToDelete.map((f) => f.path),
           deleteEmptyDirs: deleteEmptyDirs(),
+          basePath: basePath(),
+          root: basePath(),
         });
       }
 
@@ -325,6 +333,8 @@ export default function FileRemover() {
     const result = await invoke<DeleteResult>("batch_delete_with_progress", {
       files: filesToDelete.map((f) => f.path),
       deleteEmptyDirs: deleteEmptyDirs(),
+      basePath: basePath(),
+      root: basePath(),
       onProgress: progressChannel,
     });
 
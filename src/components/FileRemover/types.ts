@@ -1,31 +1,216 @@
-export type PatternType = "simple" | "extension" | "regex";
+export type PatternType = "simple" | "extension" | "regex" | "glob";
+
+export type MatchTarget = "name" | "relativePath" | "fullPath";
+
+export type DeleteMode = "permanent" | "trash" | "quarantine";
+
+export type ProtectionReason =
+  | "systemDirectory"
+  | "homeDirectory"
+  | "mountPoint"
+  | "appData"
+  | "userDefined"
+  | "outsideRoot";
 
 export interface FileMatchItem {
   path: string;
   name: string;
+  // Positions in the name, or in the full path when matched against a path
   matchRanges: [number, number][];
+  matchTarget?: MatchTarget;
   size: number;
   isDirectory: boolean;
+  modifiedMs?: number | null;
+  createdMs?: number | null;
+  accessedMs?: number | null;
+  // First matching lines, for content search matches
+  contentHits?: ContentHit[];
   selected: boolean;
 }
 
+export interface ContentHit {
+  line: number;
+  snippet: string;
+  // Character ranges within the snippet
+  matchRanges: [number, number][];
+}
+
+export type TimeBound =
+  | { type: "ago"; duration: string }
+  | { type: "date"; date: string }
+  | { type: "file"; path: string };
+
+export interface TimeRange {
+  after?: TimeBound;
+  before?: TimeBound;
+}
+
+export interface SearchFilters {
+  minSize?: string;
+  maxSize?: string;
+  modified?: TimeRange;
+  created?: TimeRange;
+  accessed?: TimeRange;
+}
+
+export type EntryType = "file" | "directory" | "symlink";
+
+// A filter expression tree; plain JSON, so it can be saved and reloaded
+export type FilterExpression =
+  | { type: "and"; children: FilterExpression[] }
+  | { type: "or"; children: FilterExpression[] }
+  | { type: "not"; child: FilterExpression }
+  | {
+      type: "pattern";
+      pattern: string;
+      patternType: PatternType;
+      caseSensitive?: boolean;
+      matchTarget?: MatchTarget;
+    }
+  | { type: "size"; min?: string; max?: string }
+  | {
+      type: "time";
+      kind: "modified" | "created" | "accessed";
+      after?: TimeBound;
+      before?: TimeBound;
+    }
+  | { type: "entryType"; entryType: EntryType };
+
 export interface SearchFilesParams {
+  basePath: string;
+  // A pattern, an expression, or both
+  pattern?: string;
+  patternType?: PatternType;
+  includeSubdirs: boolean;
+  caseSensitive: boolean;
+  matchTarget?: MatchTarget;
+  filters?: SearchFilters;
+  expression?: FilterExpression;
+}
+
+export interface ContentSearchOptions {
+  maxFileSize?: string;
+  maxHits?: number;
+}
+
+export interface SearchFileContentsParams {
   basePath: string;
   pattern: string;
-  patternType: PatternType;
+  patternType?: "simple" | "regex";
   includeSubdirs: boolean;
   caseSensitive: boolean;
+  // Selects which files are read
+  expression?: FilterExpression;
+  options?: ContentSearchOptions;
+}
+
+export type KeepStrategy =
+  | { type: "oldest" }
+  | { type: "shortestPath" }
+  | { type: "inFolder"; folder: string };
+
+export interface FindDuplicatesParams {
+  basePath: string;
+  minSize?: string;
+  keep?: KeepStrategy;
+  expression?: FilterExpression;
+}
+
+export interface DuplicateFile {
+  path: string;
+  modified_ms: number | null;
+}
+
+export interface DuplicateGroup {
+  size: number;
+  hash: string;
+  keep: DuplicateFile;
+  // The extra copies; their paths can be passed to batch_delete
+  duplicates: DuplicateFile[];
+}
+
+export interface DuplicateReport {
+  groups: DuplicateGroup[];
+  files_scanned: number;
+  wasted_bytes: number;
+}
+
+export type LinkMode = "auto" | "hardLink" | "reflink";
+
+export type LinkKind = "hardLink" | "reflink";
+
+export interface DedupeGroup {
+  keep: string;
+  duplicates: string[];
+}
+
+export interface DedupeWithLinksParams {
+  groups: DedupeGroup[];
+  mode?: LinkMode;
+  root: string;
+}
+
+export interface DedupeResult {
+  replaced: [string, LinkKind][];
+  already_linked: string[];
+  failed: [string, string][];
+  protected: [string, ProtectionReason][];
+  bytes_reclaimed: number;
+  root: string | null;
+}
+
+export interface EmptyDir {
+  path: string;
+  // Junk files directly inside, removed along with the directory
+  junk_files: string[];
+}
+
+export interface EmptyDirReport {
+  // Deepest first
+  dirs: EmptyDir[];
+  dirs_scanned: number;
+}
+
+export interface FindEmptyDirsParams {
+  basePath: string;
+  includeJunk?: boolean;
+}
+
+export interface DeleteEmptyDirsParams {
+  dirs: string[];
+  includeJunk?: boolean;
+  root: string;
+}
+
+export interface EmptyDirCleanup {
+  deleted_dirs: string[];
+  junk_files: string[];
+  failed: [string, string][];
+  protected: [string, ProtectionReason][];
+  root: string | null;
 }
 
 export interface DeleteFilesParams {
   files: string[];
   deleteEmptyDirs: boolean;
+  // Empty parent cleanup never removes or climbs above this; defaults to root
+  basePath?: string;
+  mode?: DeleteMode;
+  dryRun?: boolean;
+  root: string;
 }
 
 export interface DeleteResult {
   successful: string[];
   failed: [string, string][];
   deletedDirs: string[];
+  destinations?: [string, string][];
+  batchId?: string | null;
+  quarantineError?: string | null;
+  dryRun?: boolean;
+  directoryEntries?: [string, number][];
+  protected?: [string, ProtectionReason][];
+  root?: string | null;
 }
 
 // Legacy simple progress type for batched operations
@@ -45,7 +230,8 @@ export type SearchProgressEvent =
   | { type: "started"; basePath: string }
   | { type: "scanning"; currentDir: string; filesFound: number }
   | { type: "matching"; totalFiles: number }
-  | { type: "completed"; matchesFound: number };
+  | { type: "completed"; matchesFound: number }
+  | { type: "cancelled"; filesFound: number; matchesFound: number };
 
 /**
  * Progress events for batch delete operations.
@@ -54,7 +240,50 @@ export type SearchProgressEvent =
 export type StreamingDeleteProgress =
   | { type: "started"; totalFiles: number }
   | { type: "progress"; current: number; total: number; currentPath: string }
-  | { type: "completed"; successful: number; failed: number };
+  | { type: "completed"; successful: number; failed: number }
+  | { type: "cancelled"; successful: number; failed: number; total: number };
+
+/**
+ * Progress events for the duplicate finder.
+ * Received via Tauri Channel during find_duplicates.
+ */
+export type DuplicateProgressEvent =
+  | { type: "started"; basePath: string }
+  | { type: "scanning"; currentDir: string; filesFound: number }
+  | {
+      type: "hashing";
+      stage: "partial" | "full";
+      filesHashed: number;
+      totalFiles: number;
+    }
+  | { type: "completed"; groupsFound: number; wastedBytes: number }
+  | { type: "cancelled"; filesFound: number };
+
+/**
+ * Progress events for replacing duplicates with links.
+ * Received via Tauri Channel during dedupe_with_links.
+ */
+export type DedupeProgressEvent =
+  | { type: "started"; totalFiles: number }
+  | { type: "progress"; current: number; total: number; currentPath: string }
+  | {
+      type: "completed";
+      replaced: number;
+      failed: number;
+      bytesReclaimed: number;
+    }
+  | { type: "cancelled"; replaced: number; failed: number; total: number };
+
+/**
+ * Progress events for the empty directory finder.
+ * Received via Tauri Channel during find_empty_dirs; delete_empty_dirs
+ * sends StreamingDeleteProgress events.
+ */
+export type EmptyDirProgressEvent =
+  | { type: "started"; basePath: string }
+  | { type: "scanning"; currentDir: string; dirsScanned: number }
+  | { type: "completed"; emptyDirs: number }
+  | { type: "cancelled"; dirsScanned: number };
 
 /**
  * State for tracking search progress in UI
@@ -2,6 +2,7 @@ import { describe, it, expect } from "vitest";
 import {
   formatFileSize,
   buildHighlightedSegments,
+  toFullPathRanges,
   validatePattern,
   checkDangerousOperation,
 } from "./utils";
@@ -44,6 +45,20 @@ describe("utils", () => {
     });
   });
 
+  describe("toFullPathRanges", () => {
+    it("shifts ranges past the base path and separator", () => {
+      expect(toFullPathRanges("/home/user", [[0, 5], [6, 9]])).toEqual([
+        [11, 16],
+        [17, 20],
+      ]);
+    });
+
+    it("handles a base path with a trailing separator", () => {
+      expect(toFullPathRanges("C:\\", [[0, 3]])).toEqual([[3, 6]]);
+      expect(toFullPathRanges("/", [[0, 3]])).toEqual([[1, 4]]);
+    });
+  });
+
   describe("buildHighlightedSegments", () => {
     it("returns full text as non-match when no ranges provided", () => {
       const result = buildHighlightedSegments("hello world", []);
@@ -161,6 +176,21 @@ describe("utils", () => {
       expect(result).toContain("Invalid regex:");
     });
 
+    it("validates valid glob patterns", () => {
+      expect(validatePattern("*.log", "glob")).toBeUndefined();
+      expect(validatePattern("**/cache/*.{tmp,bak}", "glob")).toBeUndefined();
+      expect(validatePattern("file[0-9].txt", "glob")).toBeUndefined();
+    });
+
+    it("returns error for malformed glob patterns", () => {
+      expect(validatePattern("file[0-9.txt", "glob")).toBe(
+        "Invalid glob: unclosed character class"
+      );
+      expect(validatePattern("*.{tmp,bak", "glob")).toBe(
+        "Invalid glob: unbalanced braces"
+      );
+    });
+
     it("validates valid extension patterns", () => {
       expect(validatePattern(".txt", "extension")).toBeUndefined();
       expect(validatePattern("txt", "extension")).toBeUndefined();
@@ -33,6 +33,18 @@ export function validatePattern(
     }
   }
 
+  if (type === "glob") {
+    // The backend's glob parser is stricter; catch the common typos early
+    if (/\[[^\]]*$/.test(pattern)) {
+      return "Invalid glob: unclosed character class";
+    }
+    const opened = (pattern.match(/\{/g) || []).length;
+    const closed = (pattern.match(/\}/g) || []).length;
+    if (opened !== closed) {
+      return "Invalid glob: unbalanced braces";
+    }
+  }
+
   if (type === "extension") {
     const extensions = pattern.split(",").map((s) => s.trim());
     if (extensions.some((ext) => !ext)) {
@@ -96,6 +108,18 @@ export function checkDangerousOperation(
   return undefined;
 }
 
+/**
+ * Shift match ranges in a path relative to the base path so they index into
+ * the full path instead
+ */
+export function toFullPathRanges(
+  basePath: string,
+  ranges: [number, number][]
+): [number, number][] {
+  const offset = /[\\/]$/.test(basePath) ? basePath.length : basePath.length + 1;
+  return ranges.map(([start, end]) => [start + offset, end + offset]);
+}
+
 /**
  * Build highlighted segments from match ranges
  */
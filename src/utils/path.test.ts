@@ -4,6 +4,8 @@ import {
   getDirectory,
   getPathSeparator,
   joinPath,
+  isInsideDirectory,
+  getCommonDirectory,
 } from "./path";
 
 describe("getFileName", () => {
@@ -93,3 +95,45 @@ describe("joinPath", () => {
   });
 });
 
+describe("isInsideDirectory", () => {
+  it("should accept paths below the directory", () => {
+    expect(isInsideDirectory("/home/user/a/file.txt", "/home/user")).toBe(true);
+  });
+
+  it("should reject the directory itself and siblings with the same prefix", () => {
+    expect(isInsideDirectory("/home/user", "/home/user")).toBe(false);
+    expect(isInsideDirectory("/home/username/file.txt", "/home/user")).toBe(
+      false
+    );
+  });
+
+  it("should handle Windows-style paths", () => {
+    expect(isInsideDirectory("C:\\data\\file.txt", "C:\\data")).toBe(true);
+  });
+});
+
+describe("getCommonDirectory", () => {
+  it("should return the directory of a single path", () => {
+    expect(getCommonDirectory(["/home/user/file.txt"])).toBe("/home/user");
+  });
+
+  it("should return the deepest shared directory", () => {
+    expect(
+      getCommonDirectory(["/home/user/a/one.txt", "/home/user/b/two.txt"])
+    ).toBe("/home/user");
+  });
+
+  it("should not match partial directory names", () => {
+    expect(getCommonDirectory(["/data/ab/one.txt", "/data/ac/two.txt"])).toBe(
+      "/data"
+    );
+  });
+
+  it("should return the root for files directly below it", () => {
+    expect(getCommonDirectory(["/one.txt", "/two.txt"])).toBe("/");
+  });
+
+  it("should return an empty string for no paths", () => {
+    expect(getCommonDirectory([])).toBe("");
+  });
+});
@@ -48,3 +48,49 @@ export function joinPath(directory: string, filename: string): string {
   return `${directory}${separator}${filename}`;
 }
 
+
+/**
+ * Checks whether a path lies inside a directory.
+ * Only compares the strings; symbolic links and ".." are not resolved.
+ *
+ * @param path - The path to check
+ * @param directory - The directory that should contain the path
+ * @returns True if the path is below the directory
+ */
+export function isInsideDirectory(path: string, directory: string): boolean {
+  const separator = getPathSeparator(directory);
+  const prefix = directory.endsWith(separator)
+    ? directory
+    : `${directory}${separator}`;
+  return path.startsWith(prefix);
+}
+
+/**
+ * Finds the deepest directory containing all of the given paths.
+ *
+ * @param paths - The file paths
+ * @returns The common directory (without trailing separator)
+ */
+export function getCommonDirectory(paths: string[]): string {
+  if (paths.length === 0) return "";
+
+  const separator = getPathSeparator(paths[0]);
+  let common = getDirectory(paths[0]).split(separator);
+
+  for (const path of paths.slice(1)) {
+    const parts = getDirectory(path).split(separator);
+    let length = 0;
+    while (
+      length < common.length &&
+      length < parts.length &&
+      common[length] === parts[length]
+    ) {
+      length++;
+    }
+    common = common.slice(0, length);
+  }
+
+  const directory = common.join(separator);
+  // Paths directly below the filesystem root
+  return directory === "" ? separator : directory;
+}